@@ -0,0 +1,59 @@
+use thiserror::Error;
+
+/// Errors raised while building a FRU image from its areas or editing an existing one.
+#[derive(Debug, Clone, PartialEq, Error)]
+pub enum FruError {
+    #[error("String length of {field} ({area}) exceed limitation\nExp:[0x3F], Act:[0x{len:02X}]")]
+    FieldTooLong { area: String, field: String, len: usize },
+
+    #[error("Field '{field}' ({area}): {message}")]
+    FieldEncoding { area: String, field: String, message: String },
+
+    #[error("Unknown encoding '{name}' for '{field}' (expected binary, bcd_plus, 6bit_ascii, 8bit_ascii or auto)")]
+    UnknownEncoding { field: String, name: String },
+
+    #[error("Unknown padding '{name}' for '{field}' (expected space, nul, ff, a byte such as 0x20, or after_end)")]
+    UnknownPadding { field: String, name: String },
+
+    #[error("reserve_bytes of {reserved} for '{field}' exceeds the {max} bytes a field can hold")]
+    ReserveTooLarge { field: String, reserved: usize, max: usize },
+
+    #[error("Expected {expected} field configs, one per fixed field, got {actual}")]
+    FieldConfigCount { expected: usize, actual: usize },
+
+    #[error("fru data total size exceed limitation\nExp:[{limit}], Act:[{actual}]")]
+    ImageTooLarge { limit: usize, actual: usize },
+
+    #[error("{area} starts at offset 0x{offset:X}, beyond what the common header can address (0x7F8)")]
+    OffsetOverflow { area: String, offset: usize },
+
+    #[error("Unknown chassis type '{0}' (expected a name from the SMBIOS table or a hex code)")]
+    InvalidChassisType(String),
+
+    #[error("Unknown language '{value}' for '{field}' (expected an ISO 639 code such as \"de\", a language name or an IPMI code)")]
+    InvalidLanguage { field: String, value: String },
+
+    #[error("Internal use payload of {len} bytes exceeds its reservation of {reserved} bytes")]
+    InternalUseTooLarge { len: usize, reserved: usize },
+
+    #[error("Internal use area of {len} bytes leaves no room below the common header's 0x7F8 offset limit (at most {max} bytes)")]
+    InternalUseOutOfRange { len: usize, max: usize },
+
+    #[error("{area} is {len} bytes, more than the {max} bytes its layout slot allows")]
+    AreaTooLarge { area: String, len: usize, max: usize },
+
+    #[error("Invalid layout: {0}")]
+    InvalidLayout(String),
+
+    #[error("Invalid multi-record: {0}")]
+    InvalidRecord(String),
+
+    #[error("Unknown field '{0}' (expected a config key such as product_asset_tag, chassis_type or board_mfg_date_time)")]
+    UnknownField(String),
+
+    #[error("{0} is not present in the image")]
+    MissingArea(String),
+
+    #[error("Invalid FRU image: {0}")]
+    InvalidImage(String),
+}
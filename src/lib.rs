@@ -1,7 +1,10 @@
+pub mod error;
 pub mod modules;
+pub use error::FruError;
+use modules::multirecord_area::Record;
 use anyhow::Result;
 use config::{Config, File, FileFormat};
-use serde::Deserialize;
+use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use std::io::Write;
 
@@ -12,23 +15,243 @@ pub enum ConfigField {
     Detailed {
         value: String,
         reserve_bytes: Option<usize>,
+        encoding: Option<String>,
+        /// How reserved bytes are filled: `space`, `nul`, `ff`, `0xNN` or `after_end`.
+        pad: Option<String>,
     },
+    /// Several fields under one key, e.g. `Board_Custom = ["REV A", { value = "LOT 42" }]`.
+    List(Vec<ConfigField>),
 }
 
 impl ConfigField {
+    /// The value; for a list, the item values joined by ", ".
     pub fn value(&self) -> String {
         match self {
             ConfigField::Simple(s) => s.clone(),
             ConfigField::Detailed { value, .. } => value.clone(),
+            ConfigField::List(items) => items.iter().map(|f| f.value()).collect::<Vec<_>>().join(", "),
         }
     }
 
     pub fn reserve_bytes(&self) -> Option<usize> {
         match self {
-            ConfigField::Simple(_) => None,
+            ConfigField::Simple(_) | ConfigField::List(_) => None,
             ConfigField::Detailed { reserve_bytes, .. } => *reserve_bytes,
         }
     }
+
+    /// Requested type/length encoding (`binary`, `bcd_plus`, `6bit_ascii`, `8bit_ascii` or `auto`).
+    pub fn encoding(&self) -> Option<String> {
+        match self {
+            ConfigField::Simple(_) | ConfigField::List(_) => None,
+            ConfigField::Detailed { encoding, .. } => encoding.clone(),
+        }
+    }
+
+    /// Requested padding of reserved bytes (`space`, `nul`, `ff`, `0xNN` or `after_end`).
+    pub fn pad(&self) -> Option<String> {
+        match self {
+            ConfigField::Simple(_) | ConfigField::List(_) => None,
+            ConfigField::Detailed { pad, .. } => pad.clone(),
+        }
+    }
+
+    /// The fields of a list, or the field itself.
+    pub fn items(&self) -> Vec<ConfigField> {
+        match self {
+            ConfigField::List(items) => items.clone(),
+            field => vec![field.clone()],
+        }
+    }
+
+    /// The same field with another value, keeping reserve/encoding/pad settings.
+    ///
+    /// A list becomes a single field.
+    pub fn with_value(&self, value: String) -> ConfigField {
+        match self {
+            ConfigField::Simple(_) | ConfigField::List(_) => ConfigField::Simple(value),
+            ConfigField::Detailed { reserve_bytes, encoding, pad, .. } => ConfigField::Detailed {
+                value,
+                reserve_bytes: *reserve_bytes,
+                encoding: encoding.clone(),
+                pad: pad.clone(),
+            },
+        }
+    }
+}
+
+/// Binary data given in config as exactly one of a hex string, base64 text or a file path;
+/// a relative path is taken from the config file's directory.
+///
+/// ```toml
+/// payload = { hex = "00:1B:21:3A:4F:5C" }
+/// payload = { base64 = "ABshOk9c" }
+/// payload = { file = "blobs/mac_block.bin" }
+/// ```
+#[derive(Debug, Clone, Default, Deserialize, Serialize)]
+#[serde(deny_unknown_fields)]
+pub struct PayloadSource {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub hex: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub base64: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub file: Option<String>,
+}
+
+impl PayloadSource {
+    /// Resolve the configured source into bytes. The config loaders anchor relative file
+    /// paths at the config file's directory; other relative paths are taken from the
+    /// current working directory.
+    pub fn resolve(&self) -> Result<Vec<u8>, String> {
+        match (&self.hex, &self.base64, &self.file) {
+            (Some(hex), None, None) => parse_hex_bytes(hex),
+            (None, Some(text), None) => {
+                use base64::Engine;
+                base64::engine::general_purpose::STANDARD
+                    .decode(text.trim())
+                    .map_err(|e| format!("Invalid base64 payload: {}", e))
+            }
+            (None, None, Some(path)) => std::fs::read(path)
+                .map_err(|e| format!("Could not read payload file '{}': {}", path, e)),
+            (None, None, None) => Err("Payload needs one of 'hex', 'base64' or 'file'".to_string()),
+            _ => Err("Payload accepts only one of 'hex', 'base64' or 'file'".to_string()),
+        }
+    }
+}
+
+///
+/// Parse a hex byte string such as `"0x001122"`, `"00 11 22"` or `"00:11:22"`.
+///
+/// Spaces, colons and dashes between bytes are ignored.
+pub fn parse_hex_bytes(input: &str) -> Result<Vec<u8>, String> {
+    let trimmed = input.trim();
+    let trimmed = trimmed
+        .strip_prefix("0x")
+        .or_else(|| trimmed.strip_prefix("0X"))
+        .unwrap_or(trimmed);
+    let digits: String = trimmed
+        .chars()
+        .filter(|c| !matches!(c, ' ' | ':' | '-' | '_'))
+        .collect();
+
+    if let Some(c) = digits.chars().find(|c| !c.is_ascii_hexdigit()) {
+        return Err(format!("Invalid hex digit '{}' in '{}'", c, input));
+    }
+    if !digits.len().is_multiple_of(2) {
+        return Err(format!("Odd number of hex digits in '{}'", input));
+    }
+    Ok((0..digits.len())
+        .step_by(2)
+        .map(|i| u8::from_str_radix(&digits[i..i + 2], 16).unwrap())
+        .collect())
+}
+
+/// Tables of the sectioned config schema; `[board] manufacturer` is `Board_Manufacturer`.
+pub const CONFIG_SECTIONS: &[&str] = &["chassis", "board", "product"];
+
+/// Flat keys generation reads besides the fixed fields in `FIELD_KEYS`.
+const LANGUAGE_KEYS: &[&str] = &["board_language", "product_language"];
+
+/// Every flat key generation reads.
+fn field_keys() -> impl Iterator<Item = &'static str> {
+    modules::fru_image::FIELD_KEYS
+        .iter()
+        .chain(modules::fru_image::CUSTOM_KEYS)
+        .chain(modules::fru_image::PAD_KEYS)
+        .chain(LANGUAGE_KEYS)
+        .copied()
+}
+
+/// Whether `key` (lowercase, flat form) is a field generation reads.
+pub fn is_field_key(key: &str) -> bool {
+    field_keys().any(|k| k == key)
+}
+
+fn edit_distance(a: &str, b: &str) -> usize {
+    let b: Vec<char> = b.chars().collect();
+    let mut row: Vec<usize> = (0..=b.len()).collect();
+    for (i, ca) in a.chars().enumerate() {
+        let mut diagonal = row[0];
+        row[0] = i + 1;
+        for (j, cb) in b.iter().enumerate() {
+            let next = (row[j + 1] + 1).min(row[j] + 1).min(diagonal + usize::from(ca != *cb));
+            diagonal = row[j + 1];
+            row[j + 1] = next;
+        }
+    }
+    row[b.len()]
+}
+
+/// The known candidate closest to `key`, if it is close enough to be a likely misspelling.
+fn closest<'a>(key: &str, candidates: impl IntoIterator<Item = &'a str>) -> Option<&'a str> {
+    let limit = (key.chars().count() / 3).max(2);
+    candidates
+        .into_iter()
+        .map(|candidate| (edit_distance(key, candidate), candidate))
+        .filter(|(distance, _)| *distance <= limit)
+        .min_by_key(|(distance, _)| *distance)
+        .map(|(_, candidate)| candidate)
+}
+
+/// The field key `key` was probably meant to be, e.g. `board_serial_number` for `board_serial_num`.
+pub fn suggest_key(key: &str) -> Option<&'static str> {
+    let key = key.to_lowercase();
+    closest(&key, field_keys())
+}
+
+///
+/// Keys of a loaded config that generation does not read, with "did you mean" hints.
+///
+/// Such keys are otherwise silently ignored, leaving the intended field disabled.
+/// The `_comment` key that JSON exports carry is not reported.
+///
+/// # Example
+/// ```no_run
+/// use fru_gen::{load_config, unknown_config_keys};
+///
+/// for message in unknown_config_keys(&load_config("fru_gen.toml")?) {
+///     eprintln!("Warning: {}", message);
+/// }
+/// # Ok::<(), config::ConfigError>(())
+/// ```
+pub fn unknown_config_keys(config_map: &HashMap<String, ConfigField>) -> Vec<String> {
+    let mut keys: Vec<&String> = config_map
+        .keys()
+        .filter(|k| !is_field_key(k) && k.as_str() != modules::export::COMMENT_KEY)
+        .collect();
+    keys.sort();
+    keys.into_iter()
+        .map(|key| match suggest_key(key) {
+            Some(known) => format!("Unknown config key '{}' (did you mean '{}'?)", key, known),
+            None => format!("Unknown config key '{}'", key),
+        })
+        .collect()
+}
+
+/// Flatten one section table into `section_key` entries, rejecting keys the section lacks.
+fn section_fields(
+    section: &str,
+    table: config::Value,
+) -> Result<Vec<(String, ConfigField)>, config::ConfigError> {
+    let table = table
+        .into_table()
+        .map_err(|_| config::ConfigError::Message(format!("[{}] must be a table of fields", section)))?;
+    let mut fields = Vec::with_capacity(table.len());
+    for (key, value) in table {
+        let flat = format!("{}_{}", section, key.to_lowercase());
+        if !is_field_key(&flat) {
+            let prefix = format!("{}_", section);
+            let expected: Vec<&str> = field_keys().filter_map(|k| k.strip_prefix(prefix.as_str())).collect();
+            let hint = match closest(&key.to_lowercase(), expected.iter().copied()) {
+                Some(known) => format!("did you mean '{}'?", known),
+                None => format!("expected one of: {}", expected.join(", ")),
+            };
+            return Err(config::ConfigError::Message(format!("Unknown key '{}' in [{}] ({})", key, section, hint)));
+        }
+        fields.push((flat, value.try_deserialize::<ConfigField>()?));
+    }
+    Ok(fields)
 }
 
 ///
@@ -36,9 +259,10 @@ impl ConfigField {
 ///
 /// # Parameters
 /// - `file`: Name of configure file.
+/// - `section`: One of [`CONFIG_SECTIONS`].
 ///
 /// # Returns
-/// a HashMap with
+/// a HashMap keyed by the flat config key, e.g. `board_manufacturer`
 ///
 /// # Example
 /// ```no_run
@@ -54,16 +278,16 @@ pub fn read_config_section(
     file: &str,
     section: &str,
 ) -> Result<HashMap<String, ConfigField>, config::ConfigError> {
-    let builder = Config::builder().add_source(File::new(file, FileFormat::Toml));
-    let settings = builder.build()?;
-    if let Some(section_values) = settings.get::<HashMap<String, ConfigField>>(section).ok() {
-        Ok(section_values)
-    } else {
-        Err(config::ConfigError::NotFound(format!(
-            "Section '{}' not found",
-            section
-        )))
-    }
+    let section = section.to_lowercase();
+    load_with_fallback(file, |file, format| {
+        let settings = Config::builder().add_source(File::new(file, format)).build()?;
+        let mut config_map: HashMap<String, config::Value> = settings.try_deserialize()?;
+        let key = config_map.keys().find(|k| k.eq_ignore_ascii_case(&section)).cloned();
+        match key.and_then(|k| config_map.remove(&k)) {
+            Some(table) => Ok(section_fields(&section, table)?.into_iter().collect()),
+            None => Err(config::ConfigError::NotFound(format!("Section '{}' not found", section))),
+        }
+    })
 }
 
 ///
@@ -84,6 +308,239 @@ pub fn read_config_section(
 /// # Ok::<(), config::ConfigError>(())
 /// ```
 pub fn load_config(file: &str) -> Result<HashMap<String, ConfigField>, config::ConfigError> {
+    load_with_fallback(file, try_load_with_format)
+}
+
+/// Table key holding the list of multi-record entries in a config file.
+pub const MULTIRECORD_KEY: &str = "multirecord";
+
+/// Table key holding the internal use area settings in a config file.
+pub const INTERNAL_USE_KEY: &str = "internal_use";
+
+/// The `[internal_use]` table: data for the Internal Use Area and the space kept for it.
+///
+/// ```toml
+/// [internal_use]
+/// payload = { file = "calibration.bin" }   # or { hex = "..." } / { base64 = "..." }
+/// reserve_bytes = 256                      # padded with 0x00 for later writes by the BMC
+/// ```
+#[derive(Debug, Clone, Default, Deserialize, Serialize)]
+#[serde(deny_unknown_fields)]
+pub struct InternalUseConfig {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub payload: Option<PayloadSource>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub reserve_bytes: Option<usize>,
+}
+
+/// Table key holding the fixed area layout in a config file.
+pub const LAYOUT_KEY: &str = "layout";
+
+/// Where one area is pinned in a fixed layout, in 8-byte units like the common header.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(deny_unknown_fields)]
+pub struct AreaSlot {
+    /// Offset of the area; 0 is the common header and cannot be used.
+    pub offset: usize,
+    /// Space kept for the area, so it can grow without moving its neighbours.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_size: Option<usize>,
+}
+
+/// The `[layout]` table: fixed area offsets and the byte filling the space around them.
+///
+/// Areas without a slot are packed after the ones placed before them, as without a layout.
+///
+/// ```toml
+/// [layout]
+/// pad_byte = 0xFF                            # erased EEPROM; default 0x00
+/// chassis = { offset = 2, max_size = 16 }    # bytes 0x10-0x8F
+/// board = { offset = 18, max_size = 32 }
+/// product = { offset = 50, max_size = 40 }
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(deny_unknown_fields)]
+pub struct LayoutConfig {
+    #[serde(default, deserialize_with = "deserialize_byte", skip_serializing_if = "Option::is_none")]
+    pub pad_byte: Option<u8>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub internal_use: Option<AreaSlot>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub chassis: Option<AreaSlot>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub board: Option<AreaSlot>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub product: Option<AreaSlot>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub multirecord: Option<AreaSlot>,
+}
+
+impl LayoutConfig {
+    /// Slots in common header order: internal use, chassis, board, product, multi-record.
+    pub fn slots(&self) -> [Option<AreaSlot>; 5] {
+        [self.internal_use, self.chassis, self.board, self.product, self.multirecord]
+    }
+}
+
+/// A byte given as a number (`0xFF` in TOML/YAML, `255` in JSON) or a hex string (`"0xFF"`).
+fn deserialize_byte<'de, D: serde::Deserializer<'de>>(deserializer: D) -> Result<Option<u8>, D::Error> {
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum Byte {
+        Number(u8),
+        Text(String),
+    }
+    match Byte::deserialize(deserializer)? {
+        Byte::Number(byte) => Ok(Some(byte)),
+        Byte::Text(text) => parser_hex_string(&text)
+            .map(Some)
+            .map_err(|_| serde::de::Error::custom(format!("invalid byte '{}' (expected 0x00-0xFF)", text))),
+    }
+}
+
+///
+/// Read the `multirecord` list from the designated file.
+///
+/// # Returns
+/// The typed records in file order, or an empty list when the file has none.
+///
+/// # Example
+/// ```no_run
+/// use fru_gen::load_multirecords;
+///
+/// let records = load_multirecords("fru_gen.toml")?;
+/// # Ok::<(), config::ConfigError>(())
+/// ```
+pub fn load_multirecords(file: &str) -> Result<Vec<Record>, config::ConfigError> {
+    load_multirecords_with_overrides(file, &[])
+}
+
+///
+/// Load the multi-record list with per-unit overrides applied on top of the file.
+///
+/// Override keys are config paths into the list, e.g. `multirecord[1].payload.hex`.
+pub fn load_multirecords_with_overrides(
+    file: &str,
+    overrides: &[(String, String)],
+) -> Result<Vec<Record>, config::ConfigError> {
+    load_with_fallback(file, |file, format| {
+        let settings = build_anchored(file, format, overrides, |settings| {
+            let count = settings.get_array(MULTIRECORD_KEY).map(|records| records.len()).unwrap_or(0);
+            (0..count).map(|i| format!("{}[{}].payload.file", MULTIRECORD_KEY, i)).collect()
+        })?;
+        let records = match settings.get::<Vec<Record>>(MULTIRECORD_KEY) {
+            Ok(records) => records,
+            Err(config::ConfigError::NotFound(_)) => return Ok(Vec::new()),
+            Err(e) => return Err(e),
+        };
+        for (i, record) in records.iter().enumerate() {
+            record.check().map_err(|e| {
+                config::ConfigError::Message(format!("{}[{}]: {}", MULTIRECORD_KEY, i, e))
+            })?;
+        }
+        Ok(records)
+    })
+}
+
+///
+/// Read the `internal_use` table from the designated file.
+///
+/// # Returns
+/// `None` when the file has no such table.
+///
+/// # Example
+/// ```no_run
+/// use fru_gen::load_internal_use;
+///
+/// let internal_use = load_internal_use("fru_gen.toml")?;
+/// # Ok::<(), config::ConfigError>(())
+/// ```
+pub fn load_internal_use(file: &str) -> Result<Option<InternalUseConfig>, config::ConfigError> {
+    load_internal_use_with_overrides(file, &[])
+}
+
+///
+/// Load the `internal_use` table with per-unit overrides applied on top of the file.
+///
+/// Override keys are config paths into the table, e.g. `internal_use.payload.file`.
+pub fn load_internal_use_with_overrides(
+    file: &str,
+    overrides: &[(String, String)],
+) -> Result<Option<InternalUseConfig>, config::ConfigError> {
+    load_with_fallback(file, |file, format| {
+        let settings = build_anchored(file, format, overrides, |_| vec![format!("{}.payload.file", INTERNAL_USE_KEY)])?;
+        match settings.get::<InternalUseConfig>(INTERNAL_USE_KEY) {
+            Ok(internal_use) => Ok(Some(internal_use)),
+            Err(config::ConfigError::NotFound(_)) => Ok(None),
+            Err(e) => Err(e),
+        }
+    })
+}
+
+///
+/// Read the `layout` table from the designated file.
+///
+/// # Returns
+/// `None` when the file has no such table, i.e. areas are packed back-to-back.
+///
+/// # Example
+/// ```no_run
+/// use fru_gen::load_layout;
+///
+/// let layout = load_layout("fru_gen.toml")?;
+/// # Ok::<(), config::ConfigError>(())
+/// ```
+pub fn load_layout(file: &str) -> Result<Option<LayoutConfig>, config::ConfigError> {
+    load_with_fallback(file, |file, format| {
+        let settings = Config::builder().add_source(config::File::new(file, format)).build()?;
+        match settings.get::<LayoutConfig>(LAYOUT_KEY) {
+            Ok(layout) => Ok(Some(layout)),
+            Err(config::ConfigError::NotFound(_)) => Ok(None),
+            Err(e) => Err(e),
+        }
+    })
+}
+
+///
+/// Build `file` with `overrides`, then anchor the relative payload `file` paths found at
+/// `file_keys` at the config file's directory, so a config reads the same files wherever
+/// it is run from. Paths set by `overrides` are left as given.
+fn build_anchored(
+    file: &str,
+    format: FileFormat,
+    overrides: &[(String, String)],
+    file_keys: impl Fn(&Config) -> Vec<String>,
+) -> Result<Config, config::ConfigError> {
+    let build = |anchored: &[(String, String)]| {
+        let mut builder = Config::builder().add_source(config::File::new(file, format));
+        for (key, value) in overrides.iter().chain(anchored) {
+            builder = builder.set_override(key.as_str(), value.as_str())?;
+        }
+        builder.build()
+    };
+    let settings = build(&[])?;
+    let dir = std::path::Path::new(file).parent().unwrap_or(std::path::Path::new(""));
+    let anchored: Vec<(String, String)> = file_keys(&settings)
+        .into_iter()
+        .filter(|key| !overrides.iter().any(|(k, _)| k == key))
+        .filter_map(|key| {
+            let path = settings.get_string(&key).ok()?;
+            std::path::Path::new(&path)
+                .is_relative()
+                .then(|| (key, dir.join(&path).to_string_lossy().into_owned()))
+        })
+        .collect();
+    if anchored.is_empty() {
+        Ok(settings)
+    } else {
+        build(&anchored)
+    }
+}
+
+fn load_with_fallback<T>(
+    file: &str,
+    load: impl Fn(&str, FileFormat) -> Result<T, config::ConfigError>,
+) -> Result<T, config::ConfigError> {
     let path = std::path::Path::new(file);
     let ext = path.extension().and_then(|s| s.to_str()).unwrap_or("").to_lowercase();
 
@@ -91,37 +548,71 @@ pub fn load_config(file: &str) -> Result<HashMap<String, ConfigField>, config::C
     let primary_format = match ext.as_str() {
         "toml" => Some(FileFormat::Toml),
         "yaml" | "yml" => Some(FileFormat::Yaml),
+        "json" => Some(FileFormat::Json),
         _ => None,
     };
 
+    let mut primary_error = None;
     if let Some(fmt) = primary_format {
-        if let Ok(map) = try_load_with_format(file, fmt) {
-            return Ok(map);
+        match load(file, fmt) {
+            Ok(value) => return Ok(value),
+            Err(e) => primary_error = Some(e),
         }
     }
 
     // 2. Fallback: Try TOML then YAML if extension was unknown or primary failed
-    if let Ok(map) = try_load_with_format(file, FileFormat::Toml) {
-        return Ok(map);
+    if let Ok(value) = load(file, FileFormat::Toml) {
+        return Ok(value);
     }
-    
-    if let Ok(map) = try_load_with_format(file, FileFormat::Yaml) {
-        return Ok(map);
+
+    if let Ok(value) = load(file, FileFormat::Yaml) {
+        return Ok(value);
     }
 
-    // 3. Final attempt with default (Yaml) to return the actual error if it still fails
-    try_load_with_format(file, FileFormat::Yaml)
+    // 3. Report the error of the detected format, or of a final (Yaml) attempt if unknown
+    match primary_error {
+        Some(e) => Err(e),
+        None => load(file, FileFormat::Yaml),
+    }
 }
 
 fn try_load_with_format(file: &str, format: FileFormat) -> Result<HashMap<String, ConfigField>, config::ConfigError> {
     let builder = Config::builder().add_source(config::File::new(file, format));
     let settings = builder.build()?;
 
-    let config_map: HashMap<String, ConfigField> = settings.try_deserialize()?;
-    Ok(config_map
-        .into_iter()
-        .map(|(k, v)| (k.to_lowercase(), v))
-        .collect())
+    // Structured tables (e.g. the multi-record list) are loaded separately.
+    let config_map: HashMap<String, config::Value> = settings.try_deserialize()?;
+    let mut fields = HashMap::new();
+    let mut sectioned = Vec::new();
+    for (key, value) in config_map {
+        let key = key.to_lowercase();
+        if key == MULTIRECORD_KEY || key == INTERNAL_USE_KEY || key == LAYOUT_KEY {
+            continue;
+        }
+        if CONFIG_SECTIONS.contains(&key.as_str()) {
+            sectioned.extend(section_fields(&key, value)?);
+        } else {
+            fields.insert(key, value.try_deserialize::<ConfigField>()?);
+        }
+    }
+    // Both schemas may be mixed, but each field is set only once
+    for (key, field) in sectioned {
+        if fields.contains_key(&key) {
+            let (section, name) = key.split_once('_').unwrap_or((&key, ""));
+            return Err(config::ConfigError::Message(format!(
+                "'{}' is set both as a flat key and as '{}' in [{}]",
+                key, name, section
+            )));
+        }
+        fields.insert(key, field);
+    }
+    Ok(fields)
+}
+
+/// Whether a path names a config file (TOML, YAML or JSON) rather than a FRU binary.
+pub fn is_config_path(path: &std::path::Path) -> bool {
+    let ext = path.extension().and_then(|s| s.to_str()).unwrap_or("").to_lowercase();
+    matches!(ext.as_str(), "toml" | "yaml" | "yml" | "json")
 }
 
 pub fn load_yaml(file: &str) -> Result<HashMap<String, ConfigField>, config::ConfigError> {
@@ -131,10 +622,25 @@ pub fn load_yaml(file: &str) -> Result<HashMap<String, ConfigField>, config::Con
 pub fn build_config_template(filename: &str) -> Result<()> {
     let mut file = std::fs::File::create(filename)?;
     let default_content = r#"
+# Any string field may also be written as
+#   Field = { value = "...", reserve_bytes = 16, encoding = "auto" }
+# with encoding one of: 8bit_ascii (default), 6bit_ascii, bcd_plus, binary (hex), auto.
+# reserve_bytes is at most 63, the longest field a type/length byte can describe.
+# A `pad` key sets how the reserved bytes are filled: space (default), nul, ff, 0xNN, or
+# after_end to keep them as free space after the area's end marker. Chassis_Pad,
+# Board_Pad and Product_Pad set it for a whole area.
+#
+# Board_Language / Product_Language take an ISO 639 code ("de"), a language name or an
+# IPMI code; for any language other than English, 8-bit text is stored as UNICODE.
+#
+# The same fields may instead be grouped into [chassis], [board] and [product] tables
+# without the area prefix, e.g. [board] manufacturer = "..." for Board_Manufacturer.
 Chassis_type = "Rack Mount Chassis"
 Chassis_Part_Number = "CHS1234"
 Chassis_Serial_Number = "SN5678"
 Chassis_Extra = "Chassis extra"
+# Further custom fields of an area, after its *_Extra; each item may be a table as above:
+# Chassis_Custom = ["REV A", { value = "LOT 42", encoding = "6bit_ascii", reserve_bytes = 8 }]
 Board_mfg_date_time = "0"
 Board_Manufacturer = "qwfqwfg"
 Board_Product_Name = "Board124"
@@ -150,6 +656,47 @@ Product_Serial_Number = "SN123456"
 Product_Asset_Tag = "AssetTag"
 Product_Fruid = "PFRU123"
 Product_Extra = "Product extra"
+
+# Optional data for the Internal Use Area, with space kept for later writes:
+# [internal_use]
+# payload = { hex = "01:02:03" }   # or { base64 = "..." } / { file = "calibration.bin" }
+# reserve_bytes = 64
+
+# Optional fixed layout: pin areas to offsets (in 8-byte units) with room to grow,
+# and fill unused space with pad_byte (0xFF matches erased EEPROM):
+# [layout]
+# pad_byte = 0xFF
+# internal_use = { offset = 1, max_size = 1 }
+# chassis = { offset = 2, max_size = 16 }    # bytes 0x10-0x8F
+# board = { offset = 18, max_size = 32 }
+# product = { offset = 50, max_size = 40 }
+
+# Optional multi-record entries, written after the product area:
+# [[multirecord]]
+# type = "power_supply"        # power_supply | dc_output | dc_load | management_access
+#                              # | base_compatibility | extended_compatibility | oem
+# overall_capacity = 800       # W
+# low_input_voltage_1 = 9000   # 10 mV units
+# high_input_voltage_1 = 26400
+# hot_swap = true
+#
+# [[multirecord]]
+# type = "dc_output"
+# output_number = 1
+# nominal_voltage = 1200       # 10 mV units
+# max_current = 60000          # mA
+#
+# [[multirecord]]
+# type = "management_access"
+# sub_record = "system_url"    # system_url | system_name | system_ping_address | component_url
+#                              # | component_name | component_ping_address | system_unique_id
+# value = "https://bmc.example.com/"
+#
+# [[multirecord]]
+# type = "oem"
+# type_id = 0xC0               # 0xC0-0xFF
+# manufacturer_id = 0x00A2B3   # IANA enterprise number
+# payload = { hex = "00:1B:21:3A:4F:5C" }   # or { base64 = "..." } / { file = "blob.bin" }
 "#;
 
     file.write_all(default_content.as_bytes())?;
@@ -199,6 +746,18 @@ pub const CHASSIS_TYPE_TABLE: &[&str] = &[
     "Stick PC",
 ];
 
+/// Strict variant of [`parse_chassis_type`] that rejects unknown names.
+pub fn try_parse_chassis_type(input: &str) -> Result<u8, FruError> {
+    if let Ok(code) = parser_hex_string(input) {
+        return Ok(code);
+    }
+    CHASSIS_TYPE_TABLE
+        .iter()
+        .position(|name| name.eq_ignore_ascii_case(input.trim()))
+        .map(|i| i as u8)
+        .ok_or_else(|| FruError::InvalidChassisType(input.trim().to_string()))
+}
+
 pub fn parse_chassis_type(input: &str) -> u8 {
     // Try parsing as hex first
     if let Ok(code) = parser_hex_string(input) {
@@ -215,3 +774,68 @@ pub fn parse_chassis_type(input: &str) -> u8 {
     // Default to 0x02 (Unknown) if all fails
     0x02
 }
+
+/// IPMI FRU language codes, indexed by code: (ISO 639 code, language name).
+///
+/// Codes 0 and 25 both mean English; any other language stores 8-bit text as UNICODE.
+pub const LANGUAGE_CODE_TABLE: &[(&str, &str)] = &[
+    ("en", "English"), ("aa", "Afar"), ("ab", "Abkhazian"), ("af", "Afrikaans"),
+    ("am", "Amharic"), ("ar", "Arabic"), ("as", "Assamese"), ("ay", "Aymara"),
+    ("az", "Azerbaijani"), ("ba", "Bashkir"), ("be", "Byelorussian"), ("bg", "Bulgarian"),
+    ("bh", "Bihari"), ("bi", "Bislama"), ("bn", "Bengali"), ("bo", "Tibetan"),
+    ("br", "Breton"), ("ca", "Catalan"), ("co", "Corsican"), ("cs", "Czech"),
+    ("cy", "Welsh"), ("da", "Danish"), ("de", "German"), ("dz", "Bhutani"),
+    ("el", "Greek"), ("en", "English"), ("eo", "Esperanto"), ("es", "Spanish"),
+    ("et", "Estonian"), ("eu", "Basque"), ("fa", "Persian"), ("fi", "Finnish"),
+    ("fj", "Fiji"), ("fo", "Faeroese"), ("fr", "French"), ("fy", "Frisian"),
+    ("ga", "Irish"), ("gd", "Scots Gaelic"), ("gl", "Galician"), ("gn", "Guarani"),
+    ("gu", "Gujarati"), ("ha", "Hausa"), ("hi", "Hindi"), ("hr", "Croatian"),
+    ("hu", "Hungarian"), ("hy", "Armenian"), ("ia", "Interlingua"), ("ie", "Interlingue"),
+    ("ik", "Inupiak"), ("in", "Indonesian"), ("is", "Icelandic"), ("it", "Italian"),
+    ("iw", "Hebrew"), ("ja", "Japanese"), ("ji", "Yiddish"), ("jw", "Javanese"),
+    ("ka", "Georgian"), ("kk", "Kazakh"), ("kl", "Greenlandic"), ("km", "Cambodian"),
+    ("kn", "Kannada"), ("ko", "Korean"), ("ks", "Kashmiri"), ("ku", "Kurdish"),
+    ("ky", "Kirghiz"), ("la", "Latin"), ("ln", "Lingala"), ("lo", "Laothian"),
+    ("lt", "Lithuanian"), ("lv", "Latvian"), ("mg", "Malagasy"), ("mi", "Maori"),
+    ("mk", "Macedonian"), ("ml", "Malayalam"), ("mn", "Mongolian"), ("mo", "Moldavian"),
+    ("mr", "Marathi"), ("ms", "Malay"), ("mt", "Maltese"), ("my", "Burmese"),
+    ("na", "Nauru"), ("ne", "Nepali"), ("nl", "Dutch"), ("no", "Norwegian"),
+    ("oc", "Occitan"), ("om", "Oromo"), ("or", "Oriya"), ("pa", "Punjabi"),
+    ("pl", "Polish"), ("ps", "Pashto"), ("pt", "Portuguese"), ("qu", "Quechua"),
+    ("rm", "Rhaeto-Romance"), ("rn", "Kirundi"), ("ro", "Romanian"), ("ru", "Russian"),
+    ("rw", "Kinyarwanda"), ("sa", "Sanskrit"), ("sd", "Sindhi"), ("sg", "Sangro"),
+    ("sh", "Serbo-Croatian"), ("si", "Singhalese"), ("sk", "Slovak"), ("sl", "Slovenian"),
+    ("sm", "Samoan"), ("sn", "Shona"), ("so", "Somali"), ("sq", "Albanian"),
+    ("sr", "Serbian"), ("ss", "Siswati"), ("st", "Sesotho"), ("su", "Sudanese"),
+    ("sv", "Swedish"), ("sw", "Swahili"), ("ta", "Tamil"), ("te", "Tegulu"),
+    ("tg", "Tajik"), ("th", "Thai"), ("ti", "Tigrinya"), ("tk", "Turkmen"),
+    ("tl", "Tagalog"), ("tn", "Setswana"), ("to", "Tonga"), ("tr", "Turkish"),
+    ("ts", "Tsonga"), ("tt", "Tatar"), ("tw", "Twi"), ("uk", "Ukrainian"),
+    ("ur", "Urdu"), ("uz", "Uzbek"), ("vi", "Vietnamese"), ("vo", "Volapuk"),
+    ("wo", "Wolof"), ("xh", "Xhosa"), ("yo", "Yoruba"), ("zh", "Chinese"),
+    ("zu", "Zulu"),
+];
+
+/// Parse a language given as ISO 639 code ("de"), name ("German") or IPMI code ("0x16").
+///
+/// Codes and names are matched before numbers, since codes such as "de" are also valid hex.
+pub fn parse_language_code(input: &str) -> Option<u8> {
+    let trimmed = input.trim();
+    if trimmed.is_empty() {
+        return Some(0);
+    }
+
+    if let Some(i) = LANGUAGE_CODE_TABLE
+        .iter()
+        .position(|(code, name)| code.eq_ignore_ascii_case(trimmed) || name.eq_ignore_ascii_case(trimmed))
+    {
+        return Some(i as u8);
+    }
+
+    parser_hex_string(trimmed).ok()
+}
+
+/// Language name for an IPMI language code, as printed by the decoder.
+pub fn language_name(code: u8) -> &'static str {
+    LANGUAGE_CODE_TABLE.get(code as usize).map(|(_, name)| *name).unwrap_or("Reserved")
+}
@@ -21,18 +21,26 @@
     SOFTWARE.
 \**********************************************************************************/
 
-use clap::Parser;
-use std::{io::{self, Write}, path::PathBuf};
+use clap::{Parser, Subcommand};
+use std::{collections::HashMap, io::Write, path::{Path, PathBuf}};
 use anyhow::Result;
 use tempfile::NamedTempFile;
 
 use fru_gen::modules::{
-    board_area::Board, chassis_area::Chassis,
-    fru_editor::{FRUEditor, UI, Line}, internal_area::Internal, product_area::Product,
-    area::{Area, FieldConfig},
+    fru_editor::{FRUEditor, UI, Line},
+    fru_image::{FruBuilder, FruSize, FIELD_KEYS},
+    image_format::{encode_image, read_image_data, ImageFormat, LoadedImage},
+    area::FieldConfig,
+    decode::{decode_fru, format_fru_json, format_fru_print},
+    diff::diff_images,
+    export::{export_image, ConfigFormat},
+    patch::patch_image,
+    validate::{validate_config_file, validate_image},
+    batch::{blank_rows, read_manifest, run_batch, BatchOptions, DEFAULT_PATTERN},
+    sequence::{SequenceAllocator, SequenceState, DEFAULT_STATE_FILE},
 };
 
-use fru_gen::{load_config, load_yaml, build_config_template, parse_chassis_type};
+use fru_gen::{load_config, load_yaml, load_internal_use, load_layout, load_multirecords, build_config_template, is_config_path, unknown_config_keys, ConfigField, FruError};
 
 
 const VERSION: &str = "1.0.2";
@@ -56,6 +64,9 @@ TUI CONTROLS:
     PageUp/Down  Scroll Hint/Hex View panels
     e            Toggle Enable/Disable field (Settings page)
     +/-          Adjust Reserved Bytes for field (Settings page)
+    t            Cycle field encoding: 8bit/auto/6bit/bcd/binary (Settings page)
+    p            Cycle field padding: space/nul/ff/after_end (Settings page)
+    a / x        Add / remove a custom field of the selected area (Settings page)
 
 EXAMPLES:
     1. Launch TUI to create/edit FRU data:
@@ -64,12 +75,42 @@ EXAMPLES:
     2. Load existing config and launch TUI:
        fru_gen -u -r test.toml
 
-    3. Generate FRU binary from config with custom size:
-       fru_gen -r test.yaml -o output.bin --size 4096
+    3. Generate FRU binary from config with custom size (bytes, EEPROM part or 'auto'):
+       fru_gen -r test.yaml -o output.bin --size 24C16
 
     4. Generate a default config template:
        fru_gen -b my_config.toml
 
+    5. Print an existing FRU binary like 'ipmitool fru print':
+       fru_gen decode fru_gen.bin
+
+    6. Check a config or binary against the FRU specification:
+       fru_gen validate fru_gen.toml
+
+    7. Generate one binary per unit from a CSV manifest:
+       fru_gen -r fru_gen.toml batch units.csv --out-dir lot42 --report lot42.json
+
+    8. Generate 50 binaries with Board_Serial_Number = \"SN{YY}{WW}{seq:05}\":
+       fru_gen -r fru_gen.toml batch --count 50 --out-dir lot43
+
+    9. Change fields of an existing binary, keeping everything else:
+       fru_gen patch dump.bin --set product_asset_tag=ABC123 --set board_serial_number=SN42
+
+    10. Compare a golden image (or its config) with an EEPROM read-back:
+       fru_gen diff golden.bin readback.bin
+
+    11. Recover a config from a vendor-supplied binary:
+       fru_gen export vendor.bin -o recovered.toml
+
+    12. Dump a binary as JSON (areas, fields, offsets, checksums):
+       fru_gen decode fru_gen.bin --json
+
+    13. Refuse to generate when the config has unknown or misspelled keys:
+       fru_gen --strict -r fru_gen.toml -o fru_gen.bin
+
+    14. Generate Intel HEX for an EEPROM programmer at base address 0x1000 (also: srec, c, base64):
+       fru_gen -r fru_gen.toml -o fru_gen.hex --format ihex --base-address 0x1000
+
 {after-help}
 ";
 
@@ -87,7 +128,7 @@ struct ToolArgument {
     #[arg(short = 'o', long = "output-file", default_value = "fru_gen.bin")]
     file: String,
 
-    #[doc = r"Specify config file path (automatically detects TOML/YAML format)"]
+    #[doc = r"Specify config file path (automatically detects TOML/YAML/JSON format)"]
     #[arg(short = 'r', long = "read-config")]
     path: Option<std::path::PathBuf>,
     
@@ -103,171 +144,392 @@ struct ToolArgument {
     #[arg(short = 'u', long = "ui")]
     user_interface_mode: bool,
 
-    #[doc = r"Total size of the output FRU binary in bytes (default = 4096)"]
-    #[arg(short = 's', long = "size", default_value = "4096")]
-    size: usize
+    #[doc = r"Total size of the output FRU binary: bytes, an EEPROM part (24C02, 24C04, 24C08, 24C16, 24C32, 24C64, 24C128, 24C256) or 'auto' for the smallest image (default = 4096)"]
+    #[arg(short = 's', long = "size", default_value = "4096", value_parser = FruSize::parse)]
+    size: FruSize,
+
+    #[doc = r"File keeping the last used sequence number of each serial pattern such as 'SN{YY}{WW}{seq:05}'"]
+    #[arg(long = "state", default_value = DEFAULT_STATE_FILE)]
+    state: PathBuf,
+
+    #[doc = r"Treat unknown or misspelled config keys as errors instead of warnings"]
+    #[arg(long = "strict")]
+    strict: bool,
+
+    #[doc = r"Output format: binary, ihex, srec, c or base64 (default: from the output file extension, else binary)"]
+    #[arg(long = "format", value_parser = parse_image_format)]
+    format: Option<ImageFormat>,
+
+    #[doc = r"Address the image starts at in Intel HEX / S-record output, e.g. '0x1000' (default = 0)"]
+    #[arg(long = "base-address", value_parser = parse_address)]
+    base_address: Option<u32>,
+
+    #[command(subcommand)]
+    command: Option<Command>,
 }
 
-pub
-fn process_fru_data(config_path: &str, size: usize, debug: bool, ui_settings: Option<&[Line]>) -> Result<Vec<u8>> {
-    let mut fru_data = Vec::new();
-    let fru_size = size;
-
-    // Common Header
-    fru_data.push(0x01);        // FRU format version
-    fru_data.push(0x00);        // Internal area offset ( No use, set to 0 )
-    fru_data.push(0x00);        // Chassis area offset
-    fru_data.push(0x00);        // Board area offset
-    fru_data.push(0x00);        // Product area offset
-    fru_data.push(0x00);        // Multi Record area offset
-    fru_data.push(0x00);        // Pad Byte area offset
-    fru_data.push(0x00);        // Checksum
-    
+#[derive(Subcommand, Debug)]
+enum Command {
+    #[doc = r"Decode a FRU binary and print it in 'ipmitool fru print' layout"]
+    Decode {
+        #[doc = r"FRU binary file to decode, or a config to decode the image it generates"]
+        file: PathBuf,
+
+        #[doc = r"Also print FRU file IDs (as 'ipmitool -v fru print' does)"]
+        #[arg(short = 'v', long = "verbose")]
+        verbose: bool,
+
+        #[doc = r"Print areas, fields, offsets and checksums as JSON instead"]
+        #[arg(long = "json")]
+        json: bool,
+    },
+
+    #[doc = r"Generate one FRU binary per row of a CSV/JSON manifest (or per --count unit) on top of the '-r' config"]
+    Batch {
+        #[doc = r"Manifest of per-unit overrides: CSV with config keys as header, or a JSON array"]
+        #[arg(required_unless_present = "count")]
+        manifest: Option<PathBuf>,
+
+        #[doc = r"Generate this many units from the config alone, without a manifest"]
+        #[arg(short = 'n', long = "count", conflicts_with = "manifest")]
+        count: Option<usize>,
+
+        #[doc = r"First sequence number for {seq} patterns (default = one past the last used)"]
+        #[arg(long = "start")]
+        start: Option<u64>,
+
+        #[doc = r"Output file name pattern; {key} expands to the unit's config value, {row} to the row number"]
+        #[arg(short = 'p', long = "pattern", default_value = DEFAULT_PATTERN)]
+        pattern: String,
+
+        #[doc = r"Directory for the generated binaries"]
+        #[arg(long = "out-dir", default_value = ".")]
+        out_dir: PathBuf,
+
+        #[doc = r"Also write the summary report as JSON to this file"]
+        #[arg(long = "report")]
+        report: Option<PathBuf>,
+    },
+
+    #[doc = r"Write the contents of a FRU binary as a TOML/YAML/JSON config that regenerates it"]
+    Export {
+        #[doc = r"FRU binary to export"]
+        file: PathBuf,
+
+        #[doc = r"Config file to write; '.yaml'/'.yml' selects YAML, '.json' JSON, anything else TOML (default: print TOML)"]
+        #[arg(short = 'o', long = "output")]
+        output: Option<PathBuf>,
+    },
+
+    #[doc = r"Compare two FRU binaries (or a binary and a config) area by area and field by field"]
+    Diff {
+        #[doc = r"Reference FRU binary or config (.toml/.yaml/.json, built at '--size' bytes)"]
+        left: PathBuf,
+
+        #[doc = r"FRU binary or config to compare against it"]
+        right: PathBuf,
+    },
+
+    #[doc = r"Replace fields of an existing FRU binary, fixing lengths, checksums and offsets"]
+    Patch {
+        #[doc = r"FRU binary to patch (overwritten unless '--output' is given)"]
+        file: PathBuf,
+
+        #[doc = r"Field to change as KEY=VALUE, e.g. 'product_asset_tag=ABC123'; repeatable"]
+        #[arg(long = "set", required = true, value_parser = parse_assignment)]
+        set: Vec<(String, String)>,
+
+        #[doc = r"Write the patched image here instead of overwriting FILE"]
+        #[arg(long = "output")]
+        output: Option<PathBuf>,
+    },
+
+    #[doc = r"Check a config (.toml/.yaml/.json) or FRU binary against the IPMI FRU spec"]
+    Validate {
+        #[doc = r"Config file or FRU binary to check; configs are checked at '--size' bytes"]
+        file: PathBuf,
+    },
+}
 
-    let config_map = load_yaml(config_path)?;
-    let internal = Internal::new("".to_string());
-
-    let chassis_type_string = config_map.get("chassis_type").map(|f| f.value()).unwrap_or_else(|| "0x02".to_string());
-    let chassis_type_code = parse_chassis_type(&chassis_type_string);
-
-
-    let chassis = Chassis::new(
-        chassis_type_code,
-        config_map.get("chassis_part_number").map(|f| f.value()).unwrap_or_default(),
-        config_map.get("chassis_serial_number").map(|f| f.value()).unwrap_or_default(),
-        config_map.get("chassis_extra").map(|f| f.value()).unwrap_or_default(),
-    );
-
-    let board = Board::new(
-        config_map.get("board_mfg_date_time").map(|f| f.value()).unwrap_or_else(|| "0".to_string()),
-        config_map.get("board_manufacturer").map(|f| f.value()).unwrap_or_default(),
-        config_map.get("board_product_name").map(|f| f.value()).unwrap_or_default(),
-        config_map.get("board_serial_number").map(|f| f.value()).unwrap_or_default(),
-        config_map.get("board_part_number").map(|f| f.value()).unwrap_or_default(),
-        config_map.get("board_fruid").map(|f| f.value()).unwrap_or_default(),
-        config_map.get("board_extra").map(|f| f.value()).unwrap_or_default(),
-    );
-    
-    let product = Product::new(
-        config_map.get("product_manufacturer").map(|f| f.value()).unwrap_or_default(),
-        config_map.get("product_name").map(|f| f.value()).unwrap_or_default(),
-        config_map.get("product_part_number").map(|f| f.value()).unwrap_or_default(),
-        config_map.get("product_version").map(|f| f.value()).unwrap_or_default(),
-        config_map.get("product_serial_number").map(|f| f.value()).unwrap_or_default(),
-        config_map.get("product_asset_tag").map(|f| f.value()).unwrap_or_default(),
-        config_map.get("product_fruid").map(|f| f.value()).unwrap_or_default(),
-        config_map.get("product_extra").map(|f| f.value()).unwrap_or_default(),
-    );
-    
-    let get_configs = |range: std::ops::Range<usize>| -> Vec<FieldConfig> {
-        if let Some(lines) = ui_settings {
-            lines[range].iter().map(|l: &Line| FieldConfig {
+fn parse_image_format(input: &str) -> Result<ImageFormat, String> {
+    ImageFormat::parse(input)
+        .ok_or_else(|| format!("unknown format '{}' (expected one of: {})", input, ImageFormat::NAMES.join(", ")))
+}
+
+fn parse_address(input: &str) -> Result<u32, String> {
+    let trimmed = input.trim();
+    match trimmed.strip_prefix("0x").or_else(|| trimmed.strip_prefix("0X")) {
+        Some(hex) => u32::from_str_radix(hex, 16),
+        None => trimmed.parse(),
+    }
+    .map_err(|_| format!("invalid address '{}' (expected a 32-bit number such as 4096 or 0x1000)", input))
+}
+
+fn parse_assignment(input: &str) -> Result<(String, String), String> {
+    input
+        .split_once('=')
+        .map(|(key, value)| (key.trim().to_string(), value.to_string()))
+        .ok_or_else(|| format!("expected KEY=VALUE, found '{}'", input))
+}
+
+/// Report keys generation would ignore: warnings, or an error with `--strict`.
+fn check_config_keys(config_map: &HashMap<String, ConfigField>, strict: bool) -> Result<()> {
+    let unknown = unknown_config_keys(config_map);
+    if strict && !unknown.is_empty() {
+        return Err(anyhow::anyhow!("{}", unknown.join("\n")));
+    }
+    for message in &unknown {
+        eprintln!("Warning: {}; the key is ignored", message);
+    }
+    Ok(())
+}
+
+fn process_fru_data(config_path: &str, args: &ToolArgument, ui_settings: Option<&[Line]>) -> Result<Vec<u8>> {
+    let (size, debug, state) = (args.size, args.debug, args.state.as_path());
+    let mut config_map = load_yaml(config_path)?;
+    check_config_keys(&config_map, args.strict)?;
+    let mut allocator = SequenceAllocator::new(SequenceState::load(state)?, None);
+    let serials = allocator.expand_unit(&mut config_map, 0).map_err(anyhow::Error::msg)?;
+    let mut builder = FruBuilder::from_config(&config_map, load_multirecords(config_path)?)?
+        .internal_use(load_internal_use(config_path)?.as_ref())?
+        .layout(load_layout(config_path)?.as_ref())
+        .size(size);
+
+    if let Some(lines) = ui_settings {
+        // Custom field lines carry their settings in the saved config itself
+        let field_configs = lines
+            .iter()
+            .take(FIELD_KEYS.len())
+            .map(|l| FieldConfig {
                 enabled: l.enabled(),
                 reserved_bytes: l.reserved_bytes(),
-            }).collect()
-        } else {
-            let fields = vec![
-                "chassis_type", "chassis_part_number", "chassis_serial_number", "chassis_extra",
-                "board_mfg_date_time", "board_manufacturer", "board_product_name", "board_serial_number", "board_part_number", "board_fruid", "board_extra",
-                "product_manufacturer", "product_name", "product_part_number", "product_version", "product_serial_number", "product_asset_tag", "product_fruid", "product_extra"
-            ];
-            
-            range.map(|i| {
-                let key = fields[i];
-                let is_code = key.contains("type") || key.contains("mfg");
-                let default_reserve = if is_code { 0 } else { 32 };
-                
-                if let Some(field) = config_map.get(key) {
-                    FieldConfig {
-                        enabled: true,
-                        reserved_bytes: field.reserve_bytes().unwrap_or(default_reserve),
-                    }
-                } else {
-                    FieldConfig {
-                        enabled: false,
-                        reserved_bytes: default_reserve,
-                    }
-                }
-            }).collect()
-        }
-    };
-    
-    let internal_area_data  = internal.transfer_as_byte();
-    let chassis_area_data   = chassis.transfer_with_config(&get_configs(0..4));
-    let board_area_data     = board.transfer_with_config(&get_configs(4..11));
-    let product_area_data   = product.transfer_with_config(&get_configs(11..19));
+                encoding: l.encoding(),
+                padding: l.padding(),
+                ..Default::default()
+            })
+            .collect();
+        builder = builder.field_configs(field_configs);
+    }
 
-    if debug == true {
+    let image = builder.build().map_err(|e| match e {
+        FruError::ImageTooLarge { actual, .. } => match FruSize::smallest_part(actual) {
+            Some(part) => anyhow::anyhow!("{}\nHint: '--size {}' or '--size auto' holds the image", e, part),
+            None => anyhow::anyhow!("{}", e),
+        },
+        e => e.into(),
+    })?;
+    allocator.commit(&serials);
+    allocator.save()?;
+
+    if debug {
         println!("{:#?}", config_map);
-        println!("{:?}", internal_area_data);
-        println!("{:?}", chassis_area_data);
-        println!("{:?}", board_area_data);
-        println!("{:?}", product_area_data);
+        for area in &image.areas {
+            println!("{}: {:?}", area.name, image.area_data(&area.name).unwrap_or_default());
+        }
+    }
+
+    if size == FruSize::Auto {
+        println!("Fru Size: {} bytes (auto)", image.data.len());
+    } else {
+        println!("Fru Size: {}, {} bytes used, {} bytes free", size, image.used, image.headroom());
+        if let Some(warning) = image.headroom_warning() {
+            eprintln!("Warning: {}", warning);
+        }
+    }
+    Ok(image.into_bytes())
+}
+
+
+/// Write the generated FRU image to `file` in `--format`, or the format its extension names.
+fn write_encoded_data_to_bin_file(binary_data: &[u8], file: &str, args: &ToolArgument) -> Result<()> {
+    let format = args.format.unwrap_or_else(|| ImageFormat::from_path(Path::new(file)));
+    let encoded = encode_image(binary_data, format, args.base_address.unwrap_or(0))?;
+    let mut file = std::fs::File::create(file)?;
+    file.write_all(&encoded)?;
+    Ok(())
+}
+
+
+fn decode_binary_file(file: &Path, verbose: bool, json: bool, size: FruSize, state: &Path) -> Result<()> {
+    let fru = decode_fru(&read_image(file, size, state)?)?;
+
+    if json {
+        println!("{}", format_fru_json(&fru));
+        return Ok(());
     }
+    print!("{}", format_fru_print(&fru, verbose));
+    for violation in &fru.violations {
+        eprintln!("Warning: {}", violation);
+    }
+    Ok(())
+}
 
-    let mut current_offset = 1u8; // Start after Common Header (8 bytes)
 
-    // Internal Area
-    if !internal_area_data.is_empty() {
-        fru_data[1] = current_offset;
-        fru_data.extend(&internal_area_data);
-        current_offset += (internal_area_data.len() / 8) as u8;
+fn validate_file(file: &Path, size: FruSize, strict: bool, state: &Path) -> Result<()> {
+    let report = if is_config_path(file) {
+        let path = file.to_str().ok_or_else(|| anyhow::anyhow!("Could not convert path to a valid UTF-8 string"))?;
+        validate_config_file(path, size, strict, state)?
+    } else {
+        validate_image(&read_binary(file)?.data)
+    };
+
+    for finding in &report.findings {
+        println!("{}", finding);
     }
+    println!("{}: {} error(s), {} warning(s)", file.display(), report.errors(), report.warnings());
 
-    // Chassis Area
-    if !chassis_area_data.is_empty() {
-        fru_data[2] = current_offset;
-        fru_data.extend(&chassis_area_data);
-        current_offset += (chassis_area_data.len() / 8) as u8;
+    if !report.is_ok() {
+        return Err(anyhow::anyhow!("'{}' does not conform to the IPMI FRU specification", file.display()));
     }
+    Ok(())
+}
+
 
-    // Board Area
-    if !board_area_data.is_empty() {
-        fru_data[3] = current_offset;
-        fru_data.extend(&board_area_data);
-        current_offset += (board_area_data.len() / 8) as u8;
+/// Read a FRU image stored as raw binary, Intel HEX or S-record.
+fn read_binary(file: &Path) -> Result<LoadedImage> {
+    let bytes = std::fs::read(file)
+        .map_err(|e| anyhow::anyhow!("Could not read FRU binary '{}': {}", file.display(), e))?;
+    read_image_data(&bytes).map_err(|e| anyhow::anyhow!("Could not read FRU binary '{}': {}", file.display(), e))
+}
+
+
+/// Read a FRU binary (raw, Intel HEX or S-record), or build the image a config
+/// (.toml/.yaml/.yml/.json) describes, with serial patterns previewed from `state`.
+fn read_image(file: &Path, size: FruSize, state: &Path) -> Result<Vec<u8>> {
+    if is_config_path(file) {
+        let path = file.to_str().ok_or_else(|| anyhow::anyhow!("Could not convert path to a valid UTF-8 string"))?;
+        return Ok(FruBuilder::preview_config_file(path, state)?.size(size).build()?.into_bytes());
     }
+    Ok(read_binary(file)?.data)
+}
+
+
+fn export_file(file: &Path, output: Option<&PathBuf>) -> Result<()> {
+    let image = read_binary(file)?.data;
 
-    // Product Area
-    if !product_area_data.is_empty() {
-        fru_data[4] = current_offset;
-        fru_data.extend(&product_area_data);
+    match output {
+        Some(path) => {
+            std::fs::write(path, export_image(&image, ConfigFormat::from_path(path))?)?;
+            println!("Exported '{}' to '{}'", file.display(), path.display());
+        }
+        None => print!("{}", export_image(&image, ConfigFormat::Toml)?),
     }
+    Ok(())
+}
 
-    
-    // Calculate common Header checksum
-    fru_data[7] = ((0x100u16 - (fru_data.iter().take(7).map(|&b| b as u16).sum::<u16>() % 256)) % 256) as u8;
-    
 
-    // Check fru_data size.
-    if fru_data.len() > fru_size {
-        panic!("Error: fru data total size exceed limitation\nExp:[{}], Act:[{}]", fru_size, fru_data.len());
+fn diff_files(left: &Path, right: &Path, size: FruSize, state: &Path) -> Result<()> {
+    let diff = diff_images(&read_image(left, size, state)?, &read_image(right, size, state)?)?;
+
+    for difference in &diff.differences {
+        println!("{}", difference);
     }
+    if !diff.ranges.is_empty() {
+        println!("Differing bytes:");
+        for range in &diff.ranges {
+            println!("    {}", range);
+        }
+    }
+    println!("{} field difference(s), {} differing byte range(s)", diff.differences.len(), diff.ranges.len());
 
-    // If needed, extend size of fru_data to specified bytes
-    while fru_data.len() < fru_size {
-        fru_data.push(0x00);
+    if !diff.is_empty() {
+        return Err(anyhow::anyhow!("'{}' and '{}' differ", left.display(), right.display()));
     }
+    Ok(())
+}
+
+
+/// Patch `file`; the result keeps the input's format and base address unless
+/// `--format` / `--base-address` say otherwise.
+fn patch_file(file: &PathBuf, sets: &[(String, String)], output: Option<&PathBuf>, args: &ToolArgument) -> Result<()> {
+    let loaded = read_binary(file)?;
+    let image = loaded.data;
+    let patched = patch_image(&image, sets)?;
 
-    println!("Fru Size: {}", fru_size);
-    Ok(fru_data)
+    let target = output.unwrap_or(file);
+    let format = args.format.unwrap_or(loaded.format);
+    std::fs::write(target, encode_image(&patched, format, args.base_address.unwrap_or(loaded.base_address))?)?;
+    let changed = image.iter().zip(&patched).filter(|(a, b)| a != b).count();
+    println!("Patched {} field(s), {} byte(s) changed: '{}'", sets.len(), changed, target.display());
+    Ok(())
 }
 
 
-///
-fn write_encoded_data_to_bin_file(binary_data: &Vec<u8>, file: &str) -> io::Result<()>{
-    let mut file = std::fs::File::create(file)?;
-    file.write_all(&binary_data)?;
+fn batch_generate(args: &ToolArgument, manifest: Option<&Path>, count: usize, options: BatchOptions, report_path: Option<&PathBuf>) -> Result<()> {
+    let config_path_buf = args.path.clone()
+        .ok_or_else(|| anyhow::anyhow!("Batch mode needs a base config, given with '-r <PATH>'"))?;
+    let config_path = config_path_buf.to_str().ok_or_else(|| anyhow::anyhow!("Could not convert path to a valid UTF-8 string"))?;
+
+    let rows = match manifest {
+        Some(manifest) => read_manifest(manifest)?,
+        None => blank_rows(count),
+    };
+    let report = run_batch(config_path, &rows, &options)?;
+
+    for unit in &report.units {
+        match &unit.output {
+            Some(path) => println!("row {}: {}", unit.row, path.display()),
+            None => println!("row {}: FAILED", unit.row),
+        }
+        for error in &unit.errors {
+            println!("    error: {}", error);
+        }
+        for warning in &unit.warnings {
+            println!("    warning: {}", warning);
+        }
+    }
+    println!("Produced {} image(s), {} row(s) failed", report.produced, report.failed);
+
+    if let Some(path) = report_path {
+        std::fs::write(path, serde_json::to_string_pretty(&report)?)?;
+        println!("Report written to '{}'", path.display());
+    }
+    if report.failed > 0 {
+        return Err(anyhow::anyhow!("{} of {} manifest row(s) failed", report.failed, report.units.len()));
+    }
     Ok(())
 }
 
 
 fn dispatch_function(args: &ToolArgument) -> Result<()> {
+    if let Some(command) = &args.command {
+        match command {
+            Command::Decode { file, verbose, json } => decode_binary_file(file, *verbose, *json, args.size, &args.state)?,
+            Command::Validate { file } => validate_file(file, args.size, args.strict, &args.state)?,
+            Command::Export { file, output } => export_file(file, output.as_ref())?,
+            Command::Diff { left, right } => diff_files(left, right, args.size, &args.state)?,
+            Command::Patch { file, set, output } => patch_file(file, set, output.as_ref(), args)?,
+            Command::Batch { manifest, count, start, pattern, out_dir, report } => {
+                // The default pattern takes the extension of the requested format
+                let format = args.format.unwrap_or_else(|| ImageFormat::from_path(Path::new(pattern)));
+                let pattern = if pattern == DEFAULT_PATTERN {
+                    Path::new(pattern).with_extension(format.extension()).display().to_string()
+                } else {
+                    pattern.clone()
+                };
+                let options = BatchOptions {
+                    pattern,
+                    out_dir: out_dir.clone(),
+                    size: args.size,
+                    state_file: args.state.clone(),
+                    start: *start,
+                    strict: args.strict,
+                    format,
+                    base_address: args.base_address.unwrap_or(0),
+                };
+                batch_generate(args, manifest.as_deref(), count.unwrap_or(0), options, report.as_ref())?
+            }
+        }
+        return Ok(());
+    }
+
     if args.user_interface_mode {
         let initial_data = if let Some(path) = &args.path {
             let config_path = path.to_str().unwrap_or("output.yaml");
             if path.exists() {
-                Some(load_config(config_path)?)
+                let config_map = load_config(config_path)?;
+                check_config_keys(&config_map, args.strict)?;
+                Some(config_map)
             } else {
                 println!("Warning: Provided config file '{}' does not exist. Starting with empty fields.", config_path);
                 None
@@ -283,8 +545,8 @@ fn dispatch_function(args: &ToolArgument) -> Result<()> {
         let settings = fru_editor.run(temp_file_name, initial_data)?;
         
         if let Some(s) = settings {
-            let fru_data: Vec<u8> = process_fru_data(&temp_file_name, args.size, args.debug, Some(&s))?;
-            write_encoded_data_to_bin_file(&fru_data, &args.file)?;
+            let fru_data: Vec<u8> = process_fru_data(temp_file_name, args, Some(&s))?;
+            write_encoded_data_to_bin_file(&fru_data, &args.file, args)?;
             println!("Generate fru file: '{}'", &args.file);
         } else {
             println!("No changes saved. Exiting.");
@@ -303,11 +565,11 @@ fn dispatch_function(args: &ToolArgument) -> Result<()> {
 Hint: Use '-b' to generate a default template or '-u' to use the editor interface.", config_path_buf.display()));
         }
 
-        let config_path = config_path_buf.as_path().to_str().unwrap_or_else(|| panic!("Could not convert path to a valid UTF-8 string"));
-        let fru_data: Vec<u8> = process_fru_data(config_path, args.size, args.debug, None)?;
+        let config_path = config_path_buf.as_path().to_str().ok_or_else(|| anyhow::anyhow!("Could not convert path to a valid UTF-8 string"))?;
+        let fru_data: Vec<u8> = process_fru_data(config_path, args, None)?;
 
         // Write data
-        write_encoded_data_to_bin_file(&fru_data, &args.file)?;
+        write_encoded_data_to_bin_file(&fru_data, &args.file, args)?;
         println!("Generate fru file: '{}'", &args.file);
     }
 
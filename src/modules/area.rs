@@ -1,11 +1,42 @@
+use super::encoding::{Encoding, Padding};
+use crate::FruError;
+
+#[derive(Debug, Clone, Default)]
 pub struct FieldConfig {
     pub enabled: bool,
     pub reserved_bytes: usize,
+    pub encoding: Encoding,
+    /// How reserved bytes beyond the value are filled.
+    pub padding: Padding,
+    /// Language code of the containing area; 8-bit text is UNICODE when it is not English.
+    pub language: u8,
+}
+
+/// A custom type/length field written after an area's predefined fields and its `*_extra`.
+#[derive(Debug, Clone, Default)]
+pub struct CustomField {
+    pub value: String,
+    pub config: FieldConfig,
+}
+
+impl CustomField {
+    /// An enabled field with the default string settings (8-bit ASCII, 32 reserved bytes).
+    pub fn new(value: String) -> Self {
+        CustomField {
+            value,
+            config: FieldConfig { enabled: true, reserved_bytes: 32, ..Default::default() },
+        }
+    }
+}
+
+/// Checksum byte that makes `bytes` plus itself sum to zero (mod 256), as IPMI FRU requires.
+pub fn zero_checksum(bytes: &[u8]) -> u8 {
+    bytes.iter().fold(0u8, |acc, &b| acc.wrapping_add(b)).wrapping_neg()
 }
 
 pub trait Area {
-    fn transfer_as_byte(&self) -> Vec<u8>;
-    fn transfer_with_config(&self, field_configs: &[FieldConfig]) -> Vec<u8>;
-    fn check_area_length(&self, field_name: &str, field_value: &str);
-    fn validate(&self);
+    fn transfer_as_byte(&self) -> Result<Vec<u8>, FruError>;
+    fn transfer_with_config(&self, field_configs: &[FieldConfig]) -> Result<Vec<u8>, FruError>;
+    fn check_area_length(&self, field_name: &str, field_value: &str) -> Result<(), FruError>;
+    fn validate(&self) -> Result<(), FruError>;
 }
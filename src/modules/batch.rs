@@ -0,0 +1,341 @@
+use super::fru_image::{FruBuilder, FruSize};
+use super::image_format::{encode_image, ImageFormat};
+use super::sequence::{SequenceAllocator, SequenceState};
+use super::validate::{validate_config, Severity};
+use crate::{
+    load_config, load_internal_use_with_overrides, load_layout, load_multirecords_with_overrides, ConfigField, INTERNAL_USE_KEY,
+    MULTIRECORD_KEY,
+};
+use anyhow::{anyhow, bail, Context, Result};
+use serde::Serialize;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+/// Manifest column naming the output file of a row explicitly, overriding the pattern.
+pub const OUTPUT_COLUMN: &str = "output";
+
+/// Default output file name pattern.
+pub const DEFAULT_PATTERN: &str = "{board_serial_number}.bin";
+
+/// Per-unit overrides from one manifest row: (column, value) in column order.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ManifestRow {
+    /// 1-based row number (data rows, header excluded).
+    pub row: usize,
+    pub values: Vec<(String, String)>,
+}
+
+impl ManifestRow {
+    fn get(&self, key: &str) -> Option<&str> {
+        self.values
+            .iter()
+            .find(|(k, _)| k.eq_ignore_ascii_case(key))
+            .map(|(_, v)| v.as_str())
+    }
+}
+
+///
+/// Read a CSV (header row = config keys) or JSON (array of objects) manifest.
+///
+/// Empty cells keep the base config's value.
+pub fn read_manifest(path: &Path) -> Result<Vec<ManifestRow>> {
+    let ext = path.extension().and_then(|s| s.to_str()).unwrap_or("").to_lowercase();
+    let text = std::fs::read_to_string(path)
+        .with_context(|| format!("Could not read manifest '{}'", path.display()))?;
+
+    let rows = if ext == "json" {
+        let units: Vec<serde_json::Map<String, serde_json::Value>> = serde_json::from_str(&text)
+            .with_context(|| format!("Manifest '{}' must be a JSON array of objects", path.display()))?;
+        units
+            .into_iter()
+            .enumerate()
+            .map(|(i, unit)| {
+                let values = unit
+                    .into_iter()
+                    .map(|(k, v)| match v {
+                        serde_json::Value::String(s) => Ok((k, s)),
+                        serde_json::Value::Number(n) => Ok((k, n.to_string())),
+                        serde_json::Value::Bool(b) => Ok((k, b.to_string())),
+                        serde_json::Value::Null => Ok((k, String::new())),
+                        other => Err(anyhow!("row {}: '{}' must be a string or number, found {}", i + 1, k, other)),
+                    })
+                    .collect::<Result<Vec<_>>>()?;
+                Ok(ManifestRow { row: i + 1, values })
+            })
+            .collect::<Result<Vec<_>>>()?
+    } else {
+        let mut reader = csv::ReaderBuilder::new().trim(csv::Trim::All).from_reader(text.as_bytes());
+        let headers = reader.headers()?.clone();
+        reader
+            .records()
+            .enumerate()
+            .map(|(i, record)| {
+                let record = record.with_context(|| format!("Manifest '{}' row {}", path.display(), i + 1))?;
+                let values = headers
+                    .iter()
+                    .zip(record.iter())
+                    .map(|(k, v)| (k.to_string(), v.to_string()))
+                    .collect();
+                Ok(ManifestRow { row: i + 1, values })
+            })
+            .collect::<Result<Vec<_>>>()?
+    };
+
+    Ok(rows
+        .into_iter()
+        .map(|mut row| {
+            row.values.retain(|(_, v)| !v.is_empty());
+            row
+        })
+        .collect())
+}
+
+/// `count` manifest rows without overrides, for runs driven by serial patterns alone.
+pub fn blank_rows(count: usize) -> Vec<ManifestRow> {
+    (1..=count).map(|row| ManifestRow { row, values: Vec::new() }).collect()
+}
+
+/// Settings of one batch run.
+#[derive(Debug, Clone)]
+pub struct BatchOptions {
+    /// Output file name pattern; `{key}` is replaced by the unit's value of config key `key`,
+    /// `{row}` by the manifest row number.
+    pub pattern: String,
+    pub out_dir: PathBuf,
+    pub size: FruSize,
+    /// File persisting the last used sequence number of each serial pattern.
+    pub state_file: PathBuf,
+    /// First sequence number of the run; by default one past the last used number.
+    pub start: Option<u64>,
+    /// Fail units whose config or manifest columns carry unknown keys.
+    pub strict: bool,
+    /// File format of the written images.
+    pub format: ImageFormat,
+    /// Start address of the images in Intel HEX / S-record output.
+    pub base_address: u32,
+}
+
+/// Outcome of one manifest row.
+#[derive(Debug, Clone, Serialize)]
+pub struct UnitResult {
+    pub row: usize,
+    /// Written image, `None` if the row failed.
+    pub output: Option<PathBuf>,
+    /// Sequence number given to the unit's serial patterns, if any.
+    pub sequence: Option<u64>,
+    pub errors: Vec<String>,
+    pub warnings: Vec<String>,
+}
+
+/// Summary of a batch run, also written as the JSON report.
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct BatchReport {
+    pub produced: usize,
+    pub failed: usize,
+    pub units: Vec<UnitResult>,
+}
+
+/// Expand `{key}` placeholders of `pattern` from the unit's config values.
+fn output_name(pattern: &str, row: usize, config_map: &HashMap<String, ConfigField>) -> Result<String, String> {
+    let mut name = String::new();
+    let mut rest = pattern;
+    while let Some(start) = rest.find('{') {
+        let end = rest[start..]
+            .find('}')
+            .map(|e| start + e)
+            .ok_or_else(|| format!("unterminated '{{' in pattern '{}'", pattern))?;
+        name.push_str(&rest[..start]);
+        let key = rest[start + 1..end].trim().to_lowercase();
+        let value = if key == "row" {
+            row.to_string()
+        } else {
+            config_map
+                .get(&key)
+                .map(|f| f.value())
+                .filter(|v| !v.trim().is_empty())
+                .ok_or_else(|| format!("pattern key '{}' has no value for this unit", key))?
+        };
+        // Keep file names inside the output directory
+        name.extend(value.trim().chars().map(|c| if matches!(c, '/' | '\\' | ':') { '_' } else { c }));
+        rest = &rest[end + 1..];
+    }
+    name.push_str(rest);
+    Ok(name)
+}
+
+/// Check a manifest `output` value is a plain file name, kept inside the output directory.
+fn manifest_output(name: &str) -> Result<String, String> {
+    let name = name.trim();
+    if name.is_empty() || name == "." || name == ".." || name.contains(['/', '\\', ':']) {
+        return Err(format!("output '{}' must be a file name inside the output directory", name));
+    }
+    Ok(name.to_string())
+}
+
+/// Apply a row's flat overrides to a copy of the base config, keeping reserve/encoding settings.
+fn apply_overrides(base: &HashMap<String, ConfigField>, row: &ManifestRow) -> HashMap<String, ConfigField> {
+    let mut config_map = base.clone();
+    for (key, value) in &row.values {
+        let key = key.to_lowercase();
+        if key == OUTPUT_COLUMN || key.starts_with(MULTIRECORD_KEY) || key.starts_with(INTERNAL_USE_KEY) {
+            continue;
+        }
+        let field = match config_map.remove(&key) {
+            Some(field) => field.with_value(value.clone()),
+            None => ConfigField::Simple(value.clone()),
+        };
+        config_map.insert(key, field);
+    }
+    config_map
+}
+
+fn run_unit(
+    base_config: &str,
+    base: &HashMap<String, ConfigField>,
+    row: &ManifestRow,
+    unit: u64,
+    options: &BatchOptions,
+    allocator: &mut SequenceAllocator,
+    written: &mut HashMap<PathBuf, usize>,
+) -> UnitResult {
+    let mut result = UnitResult {
+        row: row.row,
+        output: None,
+        sequence: None,
+        errors: Vec::new(),
+        warnings: Vec::new(),
+    };
+
+    let mut config_map = apply_overrides(base, row);
+    let serials = match allocator.expand_unit(&mut config_map, unit) {
+        Ok(serials) => serials,
+        Err(e) => {
+            result.errors.push(e);
+            return result;
+        }
+    };
+    result.sequence = serials.first().map(|(_, seq)| *seq);
+    // Columns such as `multirecord[1].payload.hex` or `internal_use.payload.file` are config paths
+    let path_overrides = |prefix: &str| -> Vec<(String, String)> {
+        row.values
+            .iter()
+            .filter(|(k, _)| k.to_lowercase().starts_with(prefix))
+            .map(|(k, v)| (k.to_lowercase(), v.clone()))
+            .collect()
+    };
+    let records = match load_multirecords_with_overrides(base_config, &path_overrides(MULTIRECORD_KEY)) {
+        Ok(records) => records,
+        Err(e) => {
+            result.errors.push(e.to_string());
+            return result;
+        }
+    };
+    let internal_use = match load_internal_use_with_overrides(base_config, &path_overrides(INTERNAL_USE_KEY)) {
+        Ok(internal_use) => internal_use,
+        Err(e) => {
+            result.errors.push(e.to_string());
+            return result;
+        }
+    };
+
+    let layout = match load_layout(base_config) {
+        Ok(layout) => layout,
+        Err(e) => {
+            result.errors.push(e.to_string());
+            return result;
+        }
+    };
+
+    let report = validate_config(
+        &config_map,
+        records.clone(),
+        internal_use.as_ref(),
+        layout.as_ref(),
+        options.size,
+        options.strict,
+    );
+    for finding in report.findings {
+        match finding.severity {
+            Severity::Error => result.errors.push(format!("[{}] {}", finding.area, finding.message)),
+            Severity::Warning => result.warnings.push(format!("[{}] {}", finding.area, finding.message)),
+        }
+    }
+    if !result.errors.is_empty() {
+        return result;
+    }
+
+    let name = match row.get(OUTPUT_COLUMN) {
+        Some(name) => manifest_output(name),
+        None => output_name(&options.pattern, row.row, &config_map),
+    };
+    let name = match name {
+        Ok(name) => name,
+        Err(e) => {
+            result.errors.push(e);
+            return result;
+        }
+    };
+    let path = options.out_dir.join(name);
+    if let Some(previous) = written.get(&path) {
+        result.errors.push(format!("output '{}' was already written by row {}", path.display(), previous));
+        return result;
+    }
+
+    let image = FruBuilder::from_config(&config_map, records)
+        .and_then(|builder| builder.internal_use(internal_use.as_ref()))
+        .and_then(|builder| builder.layout(layout.as_ref()).size(options.size).build())
+        .and_then(|image| encode_image(image.as_bytes(), options.format, options.base_address));
+    let image = match image {
+        Ok(image) => image,
+        Err(e) => {
+            result.errors.push(e.to_string());
+            return result;
+        }
+    };
+    // Record the serials before the image exists, so an interrupted run leaves a gap
+    // instead of handing them out again
+    allocator.commit(&serials);
+    if let Err(e) = allocator.save() {
+        result.errors.push(format!("{:#}", e));
+        return result;
+    }
+    match std::fs::write(&path, image) {
+        Ok(()) => {
+            written.insert(path.clone(), row.row);
+            result.output = Some(path);
+        }
+        Err(e) => result.errors.push(format!("could not write '{}': {}", path.display(), e)),
+    }
+    result
+}
+
+///
+/// Generate one image per manifest row from `base_config` plus the row's overrides.
+///
+/// Rows failing validation are reported and skipped; the remaining rows are still built.
+/// Serial patterns (see [`super::sequence`]) advance by one per row, and the numbers of
+/// written images are persisted to `options.state_file` as each image is written.
+pub fn run_batch(base_config: &str, rows: &[ManifestRow], options: &BatchOptions) -> Result<BatchReport> {
+    let base = load_config(base_config)?;
+    if !options.out_dir.exists() {
+        std::fs::create_dir_all(&options.out_dir)
+            .with_context(|| format!("Could not create output directory '{}'", options.out_dir.display()))?;
+    }
+    if rows.is_empty() {
+        bail!("Manifest has no rows");
+    }
+
+    let mut allocator = SequenceAllocator::new(SequenceState::load(&options.state_file)?, options.start);
+    let mut report = BatchReport::default();
+    let mut written = HashMap::new();
+    for (index, row) in rows.iter().enumerate() {
+        let unit = run_unit(base_config, &base, row, index as u64, options, &mut allocator, &mut written);
+        if unit.output.is_some() {
+            report.produced += 1;
+        } else {
+            report.failed += 1;
+        }
+        report.units.push(unit);
+    }
+    Ok(report)
+}
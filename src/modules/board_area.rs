@@ -1,5 +1,7 @@
 use chrono::{TimeZone, Utc};
-use super::area::{Area, FieldConfig};
+use super::area::{zero_checksum, Area, CustomField, FieldConfig};
+use crate::FruError;
+use super::encoding;
 use serde::Deserialize;
 
 #[derive(Debug, Deserialize)]
@@ -11,6 +13,12 @@ pub struct Board {
     pub board_part_number: String,
     pub board_fru_file_id: String,
     pub board_extra: String,
+    /// Further custom fields after `board_extra`, each with its own settings.
+    #[serde(skip)]
+    pub board_custom: Vec<CustomField>,
+    /// IPMI language code of the area, see `LANGUAGE_CODE_TABLE` (0 = English).
+    #[serde(default)]
+    pub language_code: u8,
 }
 
 pub fn parse_mfg_time(input: &str) -> u32 {
@@ -45,6 +53,45 @@ pub fn parse_mfg_time(input: &str) -> u32 {
     }
 }
 
+/// Largest mfg date the 3-byte field can hold, in minutes since 1996-01-01 (late 2027).
+pub const MAX_MFG_MINUTES: u32 = 0xFF_FFFF;
+
+///
+/// Strict variant of [`parse_mfg_time`] used by validation.
+///
+/// Rejects unparsable input, dates before 1996 and values that do not fit in 24 bits,
+/// all of which `parse_mfg_time` silently turns into 0 or truncates.
+pub fn check_mfg_time(input: &str) -> Result<u32, String> {
+    let trimmed = input.trim();
+    if trimmed.is_empty() {
+        return Ok(0);
+    }
+
+    let minutes = if trimmed.len() == 14 && trimmed.chars().all(|c| c.is_ascii_digit()) {
+        let part = |range: std::ops::Range<usize>| trimmed[range].parse::<u32>().unwrap_or(0);
+        let dt = Utc
+            .with_ymd_and_hms(part(0..4) as i32, part(4..6), part(6..8), part(8..10), part(10..12), part(12..14))
+            .single()
+            .ok_or_else(|| format!("'{}' is not a valid YYYYMMDDHHMMSS date", trimmed))?;
+        let epoch = Utc.with_ymd_and_hms(1996, 1, 1, 0, 0, 0).single().unwrap();
+        if dt < epoch {
+            return Err(format!("'{}' is before 1996-01-01, the earliest date the field can hold", trimmed));
+        }
+        (dt - epoch).num_minutes() as u64
+    } else if trimmed.starts_with("0x") || trimmed.starts_with("0X") {
+        u64::from_str_radix(&trimmed[2..], 16).map_err(|_| format!("'{}' is not a valid hex value", trimmed))?
+    } else {
+        trimmed
+            .parse::<u64>()
+            .map_err(|_| format!("'{}' is neither YYYYMMDDHHMMSS nor a minute count", trimmed))?
+    };
+
+    if minutes > MAX_MFG_MINUTES as u64 {
+        return Err(format!("{} minutes since 1996 does not fit in 24 bits (max 0x{:X})", minutes, MAX_MFG_MINUTES));
+    }
+    Ok(minutes as u32)
+}
+
 impl Board {
     pub fn new(
         board_mfg_date_time: String,
@@ -63,9 +110,21 @@ impl Board {
             board_part_number,
             board_fru_file_id,
             board_extra,
+            board_custom: Vec::new(),
+            language_code: 0,
         }
     }
 
+    pub fn with_language_code(mut self, language_code: u8) -> Self {
+        self.language_code = language_code;
+        self
+    }
+
+    pub fn with_custom_fields(mut self, board_custom: Vec<CustomField>) -> Self {
+        self.board_custom = board_custom;
+        self
+    }
+
     pub fn print_all(&self) {
         println!("Board Mfg Date Time = {}", &self.board_mfg_date_time);
         println!("Board Manufacturer  = {}", &self.board_manufacturer);
@@ -74,49 +133,58 @@ impl Board {
         println!("Board Part Number   = {}", &self.board_part_number);
         println!("Board Fru ID        = {}", &self.board_fru_file_id);
         println!("Board Extra         = {}", &self.board_extra);
+        for (i, field) in self.board_custom.iter().enumerate() {
+            println!("Board Custom {:<6} = {}", i + 1, &field.value);
+        }
     }
 }
 
 impl Area for Board {
-    fn check_area_length(&self, field_name: &str, field_value: &str) {
-        if field_value.len() > 0x3F {
-            panic!(
-                "Error: String length of {} exceed limitation\nExp:[0x3F], Act:[0x{:02X}]",
-                field_name,
-                field_value.len()
-            );
+    fn check_area_length(&self, field_name: &str, field_value: &str) -> Result<(), FruError> {
+        let length = encoding::text_length(field_value, self.language_code);
+        if length > 0x3F {
+            return Err(FruError::FieldTooLong {
+                area: "Board Info Area".to_string(),
+                field: field_name.to_string(),
+                len: length,
+            });
         }
+        Ok(())
     }
 
-    fn validate(&self) {
-        self.check_area_length("Board Manufacturer", &self.board_manufacturer);
-        self.check_area_length("Board Product Name", &self.board_product_name);
-        self.check_area_length("Board Serial Number", &self.board_serial_number);
-        self.check_area_length("Board Part Number", &self.board_part_number);
-        self.check_area_length("Board Fru ID", &self.board_fru_file_id);
-        self.check_area_length("Board Extra", &self.board_extra);
+    fn validate(&self) -> Result<(), FruError> {
+        self.check_area_length("Board Manufacturer", &self.board_manufacturer)?;
+        self.check_area_length("Board Product Name", &self.board_product_name)?;
+        self.check_area_length("Board Serial Number", &self.board_serial_number)?;
+        self.check_area_length("Board Part Number", &self.board_part_number)?;
+        self.check_area_length("Board Fru ID", &self.board_fru_file_id)?;
+        self.check_area_length("Board Extra", &self.board_extra)?;
+        for (i, field) in self.board_custom.iter().enumerate() {
+            self.check_area_length(&format!("Board Custom {}", i + 1), &field.value)?;
+        }
+        Ok(())
     }
 
-    fn transfer_as_byte(&self) -> Vec<u8> {
+    fn transfer_as_byte(&self) -> Result<Vec<u8>, FruError> {
         let defaults = vec![
-            FieldConfig { enabled: true, reserved_bytes: 0 },
-            FieldConfig { enabled: true, reserved_bytes: 32 },
-            FieldConfig { enabled: true, reserved_bytes: 32 },
-            FieldConfig { enabled: true, reserved_bytes: 32 },
-            FieldConfig { enabled: true, reserved_bytes: 32 },
-            FieldConfig { enabled: true, reserved_bytes: 32 },
-            FieldConfig { enabled: true, reserved_bytes: 32 },
+            FieldConfig { enabled: true, reserved_bytes: 0, ..Default::default() },
+            FieldConfig { enabled: true, reserved_bytes: 32, ..Default::default() },
+            FieldConfig { enabled: true, reserved_bytes: 32, ..Default::default() },
+            FieldConfig { enabled: true, reserved_bytes: 32, ..Default::default() },
+            FieldConfig { enabled: true, reserved_bytes: 32, ..Default::default() },
+            FieldConfig { enabled: true, reserved_bytes: 32, ..Default::default() },
+            FieldConfig { enabled: true, reserved_bytes: 32, ..Default::default() },
         ];
         self.transfer_with_config(&defaults)
     }
 
-    fn transfer_with_config(&self, field_configs: &[FieldConfig]) -> Vec<u8> {
+    fn transfer_with_config(&self, field_configs: &[FieldConfig]) -> Result<Vec<u8>, FruError> {
         let mut board_area = Vec::new();
 
         // Board Area Header
         board_area.push(0x01); // Format version
         board_area.push(0x00); // Area lenght
-        board_area.push(0x00); // Language code ( 0 for English )
+        board_area.push(self.language_code); // Language code ( 0 for English )
 
         // Parse Mfg Date Time
         let mfg_time = if field_configs[0].enabled {
@@ -129,28 +197,28 @@ impl Area for Board {
         board_area.push(((mfg_time >> 8) & 0xFF) as u8);
         board_area.push(((mfg_time >> 16) & 0xFF) as u8);
 
-        let encode_field = |field: &str, config: &FieldConfig| -> Option<Vec<u8>> {
-            if !config.enabled {
-                return None;
+        let mut free_space = 0;
+        let mut encode_field = |name: &str, field: &str, config: &FieldConfig| -> Result<Option<Vec<u8>>, FruError> {
+            let config = FieldConfig { language: self.language_code, ..config.clone() };
+            let encoded = encoding::encode_area_field("Board Info Area", name, field, &config)?;
+            if let Some(f) = &encoded {
+                free_space += encoding::free_space(f, &config);
             }
-            let mut bytes = field.as_bytes().to_vec();
-            if config.reserved_bytes > 0 && bytes.len() < config.reserved_bytes {
-                bytes.resize(config.reserved_bytes, b' ');
-            }
-            let len = bytes.len().min(0x3F);
-            let mut res = vec![0xC0 | len as u8];
-            res.extend_from_slice(&bytes[..len]);
-            Some(res)
+            Ok(encoded)
         };
 
-        if let Some(f) = encode_field(&self.board_manufacturer, &field_configs[1]) { board_area.extend(f); }
-        if let Some(f) = encode_field(&self.board_product_name, &field_configs[2]) { board_area.extend(f); }
-        if let Some(f) = encode_field(&self.board_serial_number, &field_configs[3]) { board_area.extend(f); }
-        if let Some(f) = encode_field(&self.board_part_number, &field_configs[4]) { board_area.extend(f); }
-        if let Some(f) = encode_field(&self.board_fru_file_id, &field_configs[5]) { board_area.extend(f); }
-        if let Some(f) = encode_field(&self.board_extra, &field_configs[6]) { board_area.extend(f); }
+        if let Some(f) = encode_field("Board Manufacturer", &self.board_manufacturer, &field_configs[1])? { board_area.extend(f); }
+        if let Some(f) = encode_field("Board Product Name", &self.board_product_name, &field_configs[2])? { board_area.extend(f); }
+        if let Some(f) = encode_field("Board Serial Number", &self.board_serial_number, &field_configs[3])? { board_area.extend(f); }
+        if let Some(f) = encode_field("Board Part Number", &self.board_part_number, &field_configs[4])? { board_area.extend(f); }
+        if let Some(f) = encode_field("Board Fru ID", &self.board_fru_file_id, &field_configs[5])? { board_area.extend(f); }
+        if let Some(f) = encode_field("Board Extra", &self.board_extra, &field_configs[6])? { board_area.extend(f); }
+        for (i, field) in self.board_custom.iter().enumerate() {
+            if let Some(f) = encode_field(&format!("Board Custom {}", i + 1), &field.value, &field.config)? { board_area.extend(f); }
+        }
 
         board_area.push(0xC1);
+        board_area.resize(board_area.len() + free_space, 0x00); // Reservations kept after the end marker
         board_area.push(0x00); // Checksum placeholder
 
         // fill up the rest area space with 8 Byte
@@ -162,11 +230,11 @@ impl Area for Board {
         board_area[1] = (board_area.len() / 8) as u8;
 
         // Update checksum
-        let checksum = (0x100u16 - (board_area.iter().map(|&b| b as u16).sum::<u16>() % 256)) % 256; // Calculate checksum
+        let checksum = zero_checksum(&board_area); // Calculate checksum
         if let Some(last_byte) = board_area.last_mut() {
-            *last_byte = checksum as u8;
+            *last_byte = checksum;
         }
 
-        board_area
+        Ok(board_area)
     }
 }
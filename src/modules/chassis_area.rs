@@ -1,5 +1,7 @@
 use serde::Deserialize;
-use super::area::{Area, FieldConfig};
+use super::area::{zero_checksum, Area, CustomField, FieldConfig};
+use crate::FruError;
+use super::encoding;
 
 #[derive(Debug, Deserialize)]
 pub struct Chassis {
@@ -7,6 +9,9 @@ pub struct Chassis {
     pub chassis_part_number: String,
     pub chassis_serial_number: String,
     pub chassis_extra: String,
+    /// Further custom fields after `chassis_extra`, each with its own settings.
+    #[serde(skip)]
+    pub chassis_custom: Vec<CustomField>,
 }
 
 impl Chassis {
@@ -21,43 +26,59 @@ impl Chassis {
             chassis_part_number,
             chassis_serial_number,
             chassis_extra,
+            chassis_custom: Vec::new(),
         }
     }
+
+    pub fn with_custom_fields(mut self, chassis_custom: Vec<CustomField>) -> Self {
+        self.chassis_custom = chassis_custom;
+        self
+    }
+
     pub fn print_all(&self) {
         println!("Chassis Part Number   = {}", &self.chassis_part_number);
         println!("Chassis Serial Number = {}", &self.chassis_serial_number);
         println!("Chassis Extra         = {}", &self.chassis_extra);
+        for (i, field) in self.chassis_custom.iter().enumerate() {
+            println!("Chassis Custom {:<6} = {}", i + 1, &field.value);
+        }
     }
 }
 
 impl Area for Chassis {
-    fn check_area_length(&self, field_name: &str, field_value: &str) {
-        if field_value.len() > 0x3F {
-            panic!(
-                "Error: String length of {} exceed limitation\nExp:[0x3F], Act:[0x{:02X}]",
-                field_name,
-                field_value.len()
-            );
+    fn check_area_length(&self, field_name: &str, field_value: &str) -> Result<(), FruError> {
+        let length = encoding::text_length(field_value, 0);
+        if length > 0x3F {
+            return Err(FruError::FieldTooLong {
+                area: "Chassis Info Area".to_string(),
+                field: field_name.to_string(),
+                len: length,
+            });
         }
+        Ok(())
     }
 
-    fn validate(&self) {
-        self.check_area_length("Chassis Part Number", &self.chassis_part_number);
-        self.check_area_length("Chassis Serial Number", &self.chassis_serial_number);
-        self.check_area_length("Chassis Extra", &self.chassis_extra);
+    fn validate(&self) -> Result<(), FruError> {
+        self.check_area_length("Chassis Part Number", &self.chassis_part_number)?;
+        self.check_area_length("Chassis Serial Number", &self.chassis_serial_number)?;
+        self.check_area_length("Chassis Extra", &self.chassis_extra)?;
+        for (i, field) in self.chassis_custom.iter().enumerate() {
+            self.check_area_length(&format!("Chassis Custom {}", i + 1), &field.value)?;
+        }
+        Ok(())
     }
 
-    fn transfer_as_byte(&self) -> Vec<u8> {
+    fn transfer_as_byte(&self) -> Result<Vec<u8>, FruError> {
         let defaults = vec![
-            FieldConfig { enabled: true, reserved_bytes: 0 },
-            FieldConfig { enabled: true, reserved_bytes: 32 },
-            FieldConfig { enabled: true, reserved_bytes: 32 },
-            FieldConfig { enabled: true, reserved_bytes: 32 },
+            FieldConfig { enabled: true, reserved_bytes: 0, ..Default::default() },
+            FieldConfig { enabled: true, reserved_bytes: 32, ..Default::default() },
+            FieldConfig { enabled: true, reserved_bytes: 32, ..Default::default() },
+            FieldConfig { enabled: true, reserved_bytes: 32, ..Default::default() },
         ];
         self.transfer_with_config(&defaults)
     }
 
-    fn transfer_with_config(&self, field_configs: &[FieldConfig]) -> Vec<u8> {
+    fn transfer_with_config(&self, field_configs: &[FieldConfig]) -> Result<Vec<u8>, FruError> {
         let mut chassis_area = Vec::new();
 
         // Chassis area header
@@ -70,32 +91,33 @@ impl Area for Chassis {
             chassis_area[2] = self.chassis_type;
         }
 
-        let encode_field = |field: &str, config: &FieldConfig| -> Option<Vec<u8>> {
-            if !config.enabled {
-                return None;
-            }
-            let mut bytes = field.as_bytes().to_vec();
-            if config.reserved_bytes > 0 && bytes.len() < config.reserved_bytes {
-                bytes.resize(config.reserved_bytes, b' ');
+        let mut free_space = 0;
+        let mut encode_field = |name: &str, field: &str, config: &FieldConfig| -> Result<Option<Vec<u8>>, FruError> {
+            let encoded = encoding::encode_area_field("Chassis Info Area", name, field, config)?;
+            if let Some(f) = &encoded {
+                free_space += encoding::free_space(f, config);
             }
-            let len = bytes.len().min(0x3F);
-            let mut res = vec![0xC0 | len as u8];
-            res.extend_from_slice(&bytes[..len]);
-            Some(res)
+            Ok(encoded)
         };
 
-        if let Some(f) = encode_field(&self.chassis_part_number, &field_configs[1]) {
+        if let Some(f) = encode_field("Chassis Part Number", &self.chassis_part_number, &field_configs[1])? {
             chassis_area.extend(f);
         }
-        if let Some(f) = encode_field(&self.chassis_serial_number, &field_configs[2]) {
+        if let Some(f) = encode_field("Chassis Serial Number", &self.chassis_serial_number, &field_configs[2])? {
             chassis_area.extend(f);
         }
-        if let Some(f) = encode_field(&self.chassis_extra, &field_configs[3]) {
+        if let Some(f) = encode_field("Chassis Extra", &self.chassis_extra, &field_configs[3])? {
             chassis_area.extend(f);
         }
+        for (i, field) in self.chassis_custom.iter().enumerate() {
+            if let Some(f) = encode_field(&format!("Chassis Custom {}", i + 1), &field.value, &field.config)? {
+                chassis_area.extend(f);
+            }
+        }
 
         // End of Chassis area, 0xC1 as end Byte
         chassis_area.push(0xC1);
+        chassis_area.resize(chassis_area.len() + free_space, 0x00); // Reservations kept after the end marker
         chassis_area.push(0x00); // Checksum placeholder
 
         // fill up the rest area space with 8 Byte
@@ -107,12 +129,11 @@ impl Area for Chassis {
         chassis_area[1] = (chassis_area.len() / 8) as u8;
 
         // Update checksum
-        let checksum =
-            (0x100u16 - (chassis_area.iter().map(|&b| b as u16).sum::<u16>() % 256)) % 256; // Calculate checksum
+        let checksum = zero_checksum(&chassis_area); // Calculate checksum
         if let Some(last_byte) = chassis_area.last_mut() {
-            *last_byte = checksum as u8;
+            *last_byte = checksum;
         }
 
-        chassis_area
+        Ok(chassis_area)
     }
 }
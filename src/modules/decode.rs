@@ -0,0 +1,743 @@
+use super::board_area::Board;
+use super::chassis_area::Chassis;
+use super::encoding::{decode_text, is_english, Encoding};
+use super::internal_area::Internal;
+use super::multirecord_area::{MultiRecord, Record, RECORD_FORMAT_VERSION};
+use super::product_area::Product;
+use crate::{language_name, CHASSIS_TYPE_TABLE};
+use anyhow::{bail, Result};
+use serde::Serialize;
+use serde_json::{json, Value};
+use chrono::{Duration, TimeZone, Utc};
+use std::fmt;
+
+/// Predefined fields of the Chassis Info Area, in spec order: (config key, label).
+pub const CHASSIS_FIELDS: &[(&str, &str)] = &[
+    ("chassis_part_number", "Chassis Part Number"),
+    ("chassis_serial_number", "Chassis Serial Number"),
+];
+
+/// Predefined fields of the Board Info Area, in spec order: (config key, label).
+pub const BOARD_FIELDS: &[(&str, &str)] = &[
+    ("board_manufacturer", "Board Manufacturer"),
+    ("board_product_name", "Board Product Name"),
+    ("board_serial_number", "Board Serial Number"),
+    ("board_part_number", "Board Part Number"),
+    ("board_fruid", "Board Fru ID"),
+];
+
+/// Predefined fields of the Product Info Area, in spec order: (config key, label).
+pub const PRODUCT_FIELDS: &[(&str, &str)] = &[
+    ("product_manufacturer", "Product Manufacturer"),
+    ("product_name", "Product Name"),
+    ("product_part_number", "Product Part Number"),
+    ("product_version", "Product Version"),
+    ("product_serial_number", "Product Serial Number"),
+    ("product_asset_tag", "Product Asset Tag"),
+    ("product_fruid", "Product Fru ID"),
+];
+
+/// A deviation from the IPMI FRU specification found while decoding.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Violation {
+    pub area: String,
+    pub message: String,
+}
+
+impl fmt::Display for Violation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "[{}] {}", self.area, self.message)
+    }
+}
+
+fn serialize_hex<S: serde::Serializer>(data: &[u8], serializer: S) -> Result<S::Ok, S::Error> {
+    serializer.serialize_str(&data.iter().map(|b| format!("{:02X}", b)).collect::<String>())
+}
+
+/// One type/length encoded field as found in the image.
+#[derive(Debug, Clone, Serialize)]
+pub struct DecodedField {
+    pub key: String,
+    pub label: String,
+    /// Absolute offset of the type/length byte.
+    pub offset: usize,
+    pub type_length: u8,
+    #[serde(serialize_with = "serialize_hex")]
+    pub data: Vec<u8>,
+    pub value: String,
+}
+
+impl DecodedField {
+    /// Type code held in bits 7:6 of the type/length byte.
+    pub fn type_code(&self) -> u8 {
+        self.type_length >> 6
+    }
+}
+
+/// Location and raw contents of one area of the image.
+#[derive(Debug, Clone, Serialize)]
+pub struct DecodedArea {
+    pub name: String,
+    /// Absolute offset of the area in bytes.
+    pub offset: usize,
+    /// Area length in bytes (as declared, or as inferred for areas without a length byte).
+    pub length: usize,
+    pub checksum: u8,
+    pub fields: Vec<DecodedField>,
+}
+
+/// Result of decoding a FRU image.
+#[derive(Debug)]
+pub struct DecodedFru {
+    pub header: [u8; 8],
+    pub internal: Option<Internal>,
+    pub chassis: Option<Chassis>,
+    pub board: Option<Board>,
+    pub product: Option<Product>,
+    pub multirecord: Option<MultiRecord>,
+    pub areas: Vec<DecodedArea>,
+    pub violations: Vec<Violation>,
+}
+
+impl DecodedFru {
+    pub fn area(&self, name: &str) -> Option<&DecodedArea> {
+        self.areas.iter().find(|a| a.name == name)
+    }
+
+    pub fn is_compliant(&self) -> bool {
+        self.violations.is_empty()
+    }
+}
+
+fn zero_checksum_ok(bytes: &[u8]) -> bool {
+    bytes.iter().fold(0u8, |acc, &b| acc.wrapping_add(b)) == 0
+}
+
+struct Decoder<'a> {
+    data: &'a [u8],
+    violations: Vec<Violation>,
+}
+
+impl<'a> Decoder<'a> {
+    fn violation(&mut self, area: &str, message: String) {
+        self.violations.push(Violation {
+            area: area.to_string(),
+            message,
+        });
+    }
+
+    /// Slice out an area that carries a length byte, checking version, bounds and checksum.
+    fn info_area(&mut self, name: &str, offset: usize) -> Option<&'a [u8]> {
+        let data = self.data;
+        if offset + 2 > data.len() {
+            self.violation(name, format!("Area offset 0x{:X} is beyond end of image (0x{:X} bytes)", offset, data.len()));
+            return None;
+        }
+        if data[offset] != 0x01 {
+            self.violation(name, format!("Unsupported format version 0x{:02X}, expected 0x01", data[offset]));
+        }
+
+        let declared = data[offset + 1] as usize * 8;
+        if declared == 0 {
+            self.violation(name, "Area length is zero".to_string());
+            return None;
+        }
+        let end = if offset + declared > data.len() {
+            self.violation(name, format!(
+                "Area length 0x{:X} exceeds image size, truncated to 0x{:X}",
+                declared,
+                data.len() - offset
+            ));
+            data.len()
+        } else {
+            offset + declared
+        };
+
+        let area = &data[offset..end];
+        if !zero_checksum_ok(area) {
+            self.violation(name, format!("Checksum mismatch (stored 0x{:02X})", area[area.len() - 1]));
+        }
+        Some(area)
+    }
+
+    /// Parse type/length fields starting at `start` (relative to `area`) up to the 0xC1 marker.
+    #[allow(clippy::too_many_arguments)]
+    fn fields(
+        &mut self,
+        name: &str,
+        area: &[u8],
+        area_offset: usize,
+        start: usize,
+        language: u8,
+        predefined: &[(&str, &str)],
+        custom: (&str, &str),
+    ) -> Vec<DecodedField> {
+        let mut fields = Vec::new();
+        let limit = area.len().saturating_sub(1); // last byte is the checksum
+        let mut pos = start;
+        let mut terminated = false;
+
+        while pos < limit {
+            let type_length = area[pos];
+            if type_length == 0xC1 {
+                terminated = true;
+                break;
+            }
+            let len = (type_length & 0x3F) as usize;
+            if pos + 1 + len > limit {
+                self.violation(name, format!(
+                    "Field at offset 0x{:X} overruns the area (length 0x{:02X})",
+                    area_offset + pos,
+                    len
+                ));
+                break;
+            }
+            let bytes = area[pos + 1..pos + 1 + len].to_vec();
+            let (key, label) = predefined.get(fields.len()).copied().unwrap_or(custom);
+            if type_length >> 6 == 0b11 && !is_english(language) && !len.is_multiple_of(2) {
+                self.violation(name, format!(
+                    "UNICODE field at offset 0x{:X} has odd length 0x{:02X}",
+                    area_offset + pos,
+                    len
+                ));
+            }
+            fields.push(DecodedField {
+                key: key.to_string(),
+                label: label.to_string(),
+                offset: area_offset + pos,
+                type_length,
+                value: decode_text(type_length, &bytes, language),
+                data: bytes,
+            });
+            pos += 1 + len;
+        }
+
+        if !terminated {
+            self.violation(name, "Missing end-of-fields marker (0xC1)".to_string());
+        }
+        if fields.len() < predefined.len() {
+            self.violation(name, format!(
+                "Expected {} predefined fields, found {}",
+                predefined.len(),
+                fields.len()
+            ));
+        }
+        fields
+    }
+}
+
+impl Decoder<'_> {
+    /// Walk the record list starting at `offset` until the end-of-list bit.
+    fn records(&mut self, offset: usize) -> (Vec<Record>, usize) {
+        let name = "MultiRecord Area";
+        let data = self.data;
+        let mut records = Vec::new();
+        let mut pos = offset;
+
+        loop {
+            if pos + 5 > data.len() {
+                self.violation(name, format!("Record header at offset 0x{:X} is beyond end of image", pos));
+                break;
+            }
+            let header = &data[pos..pos + 5];
+            if !zero_checksum_ok(header) {
+                self.violation(name, format!("Header checksum mismatch in record at offset 0x{:X}", pos));
+            }
+            if header[1] & 0x0F != RECORD_FORMAT_VERSION {
+                self.violation(name, format!(
+                    "Record at offset 0x{:X} has format version 0x{:X}, expected 0x{:X}",
+                    pos,
+                    header[1] & 0x0F,
+                    RECORD_FORMAT_VERSION
+                ));
+            }
+
+            let len = header[2] as usize;
+            let start = pos + 5;
+            if start + len > data.len() {
+                self.violation(name, format!("Record at offset 0x{:X} overruns the image (length 0x{:02X})", pos, len));
+                break;
+            }
+            let payload = &data[start..start + len];
+            if payload.iter().fold(header[3], |acc, &b| acc.wrapping_add(b)) != 0 {
+                self.violation(name, format!("Record checksum mismatch in record at offset 0x{:X}", pos));
+            }
+
+            records.push(Record::from_bytes(header[0], payload));
+            pos = start + len;
+            if header[1] & 0x80 != 0 {
+                break;
+            }
+        }
+        (records, pos - offset)
+    }
+}
+
+fn field(fields: &[DecodedField], key: &str) -> String {
+    fields
+        .iter()
+        .find(|f| f.key == key)
+        .map(|f| f.value.clone())
+        .unwrap_or_default()
+}
+
+///
+/// Decode a FRU binary image into its areas.
+///
+/// Structural problems that still allow parsing (bad checksums, wrong lengths, missing
+/// markers) are collected in `violations`; only an image too short to hold a common
+/// header is rejected outright.
+///
+/// # Example
+/// ```no_run
+/// use fru_gen::modules::decode::decode_fru;
+///
+/// let image = std::fs::read("fru_gen.bin")?;
+/// let fru = decode_fru(&image)?;
+/// for v in &fru.violations {
+///     println!("{}", v);
+/// }
+/// # Ok::<(), anyhow::Error>(())
+/// ```
+pub fn decode_fru(data: &[u8]) -> Result<DecodedFru> {
+    if data.len() < 8 {
+        bail!("Image too short for a common header: {} bytes", data.len());
+    }
+
+    let mut header = [0u8; 8];
+    header.copy_from_slice(&data[..8]);
+
+    let mut decoder = Decoder { data, violations: Vec::new() };
+    if header[0] != 0x01 {
+        decoder.violation("Common Header", format!("Unsupported format version 0x{:02X}, expected 0x01", header[0]));
+    }
+    if !zero_checksum_ok(&header) {
+        decoder.violation("Common Header", format!("Checksum mismatch (stored 0x{:02X})", header[7]));
+    }
+    if header[6] != 0x00 {
+        decoder.violation("Common Header", format!("Pad byte is 0x{:02X}, expected 0x00", header[6]));
+    }
+
+    let offsets: Vec<usize> = header[1..6].iter().map(|&o| o as usize * 8).collect();
+    let mut areas = Vec::new();
+
+    // Internal Use Area: no length byte, runs until the next area or end of image.
+    let mut internal = None;
+    if offsets[0] != 0 {
+        let start = offsets[0];
+        let end = offsets[1..]
+            .iter()
+            .copied()
+            .filter(|&o| o > start)
+            .min()
+            .unwrap_or(data.len())
+            .min(data.len());
+        if start >= data.len() {
+            decoder.violation("Internal Use Area", format!("Area offset 0x{:X} is beyond end of image (0x{:X} bytes)", start, data.len()));
+        } else {
+            if data[start] != 0x01 {
+                decoder.violation("Internal Use Area", format!("Unsupported format version 0x{:02X}, expected 0x01", data[start]));
+            }
+            let payload = &data[start + 1..end];
+            internal = Some(Internal::new(
+                payload.iter().map(|b| format!("{:02X}", b)).collect::<String>(),
+            ));
+            areas.push(DecodedArea {
+                name: "Internal Use Area".to_string(),
+                offset: start,
+                length: end - start,
+                checksum: 0,
+                fields: Vec::new(),
+            });
+        }
+    }
+
+    // Chassis Info Area
+    let mut chassis = None;
+    if offsets[1] != 0 {
+        let name = "Chassis Info Area";
+        if let Some(area) = decoder.info_area(name, offsets[1]) {
+            let chassis_type = area.get(2).copied().unwrap_or(0);
+            let fields = decoder.fields(name, area, offsets[1], 3, 0, CHASSIS_FIELDS, ("chassis_extra", "Chassis Extra"));
+            chassis = Some(Chassis::new(
+                chassis_type,
+                field(&fields, "chassis_part_number"),
+                field(&fields, "chassis_serial_number"),
+                field(&fields, "chassis_extra"),
+            ));
+            areas.push(DecodedArea {
+                name: name.to_string(),
+                offset: offsets[1],
+                length: area.len(),
+                checksum: area[area.len() - 1],
+                fields,
+            });
+        }
+    }
+
+    // Board Info Area
+    let mut board = None;
+    if offsets[2] != 0 {
+        let name = "Board Info Area";
+        if let Some(area) = decoder.info_area(name, offsets[2]) {
+            let mfg_time = if area.len() >= 6 {
+                area[3] as u32 | (area[4] as u32) << 8 | (area[5] as u32) << 16
+            } else {
+                0
+            };
+            let language = area.get(2).copied().unwrap_or(0);
+            let fields = decoder.fields(name, area, offsets[2], 6, language, BOARD_FIELDS, ("board_extra", "Board Extra"));
+            board = Some(Board::new(
+                mfg_time.to_string(),
+                field(&fields, "board_manufacturer"),
+                field(&fields, "board_product_name"),
+                field(&fields, "board_serial_number"),
+                field(&fields, "board_part_number"),
+                field(&fields, "board_fruid"),
+                field(&fields, "board_extra"),
+            ).with_language_code(language));
+            areas.push(DecodedArea {
+                name: name.to_string(),
+                offset: offsets[2],
+                length: area.len(),
+                checksum: area[area.len() - 1],
+                fields,
+            });
+        }
+    }
+
+    // Product Info Area
+    let mut product = None;
+    if offsets[3] != 0 {
+        let name = "Product Info Area";
+        if let Some(area) = decoder.info_area(name, offsets[3]) {
+            let language = area.get(2).copied().unwrap_or(0);
+            let fields = decoder.fields(name, area, offsets[3], 3, language, PRODUCT_FIELDS, ("product_extra", "Product Extra"));
+            product = Some(Product::new(
+                field(&fields, "product_manufacturer"),
+                field(&fields, "product_name"),
+                field(&fields, "product_part_number"),
+                field(&fields, "product_version"),
+                field(&fields, "product_serial_number"),
+                field(&fields, "product_asset_tag"),
+                field(&fields, "product_fruid"),
+                field(&fields, "product_extra"),
+            ).with_language_code(language));
+            areas.push(DecodedArea {
+                name: name.to_string(),
+                offset: offsets[3],
+                length: area.len(),
+                checksum: area[area.len() - 1],
+                fields,
+            });
+        }
+    }
+
+    // MultiRecord Area
+    let mut multirecord = None;
+    if offsets[4] != 0 {
+        let (records, length) = decoder.records(offsets[4]);
+        multirecord = Some(MultiRecord::new(records));
+        areas.push(DecodedArea {
+            name: "MultiRecord Area".to_string(),
+            offset: offsets[4],
+            length,
+            checksum: 0,
+            fields: Vec::new(),
+        });
+    }
+
+    Ok(DecodedFru {
+        header,
+        internal,
+        chassis,
+        board,
+        product,
+        multirecord,
+        areas,
+        violations: decoder.violations,
+    })
+}
+
+/// Render a Board Mfg Date (minutes since 1996-01-01 00:00 UTC) the way `ipmitool` does.
+pub fn format_mfg_time(minutes: u32) -> String {
+    if minutes == 0 {
+        return "Unspecified".to_string();
+    }
+    let epoch = Utc.with_ymd_and_hms(1996, 1, 1, 0, 0, 0).single().unwrap();
+    let date = epoch + Duration::minutes(minutes as i64);
+    date.format("%a %b %e %H:%M:%S %Y").to_string()
+}
+
+///
+/// Format decoded FRU contents in the layout of `ipmitool fru print`.
+///
+/// Empty fields are omitted as ipmitool does; FRU file IDs are only shown when
+/// `verbose` is set, matching `ipmitool -v fru print`.
+///
+pub fn format_fru_print(fru: &DecodedFru, verbose: bool) -> String {
+    let mut out = String::new();
+    let line = |out: &mut String, label: &str, value: &str| {
+        out.push_str(&format!(" {:<22}: {}\n", label, value));
+    };
+
+    let labels = |key: &str| -> Option<&'static str> {
+        match key {
+            "chassis_part_number" => Some("Chassis Part Number"),
+            "chassis_serial_number" => Some("Chassis Serial"),
+            "chassis_extra" => Some("Chassis Extra"),
+            "board_manufacturer" => Some("Board Mfg"),
+            "board_product_name" => Some("Board Product"),
+            "board_serial_number" => Some("Board Serial"),
+            "board_part_number" => Some("Board Part Number"),
+            "board_fruid" if verbose => Some("Board FRU ID"),
+            "board_extra" => Some("Board Extra"),
+            "product_manufacturer" => Some("Product Manufacturer"),
+            "product_name" => Some("Product Name"),
+            "product_part_number" => Some("Product Part Number"),
+            "product_version" => Some("Product Version"),
+            "product_serial_number" => Some("Product Serial"),
+            "product_asset_tag" => Some("Product Asset Tag"),
+            "product_fruid" if verbose => Some("Product FRU ID"),
+            "product_extra" => Some("Product Extra"),
+            _ => None,
+        }
+    };
+
+    for area in &fru.areas {
+        match area.name.as_str() {
+            "Chassis Info Area" => {
+                if let Some(chassis) = &fru.chassis {
+                    let type_name = CHASSIS_TYPE_TABLE
+                        .get(chassis.chassis_type as usize)
+                        .copied()
+                        .unwrap_or("Unknown");
+                    line(&mut out, "Chassis Type", type_name);
+                }
+            }
+            "Board Info Area" => {
+                if let Some(board) = &fru.board {
+                    let minutes = board.board_mfg_date_time.parse::<u32>().unwrap_or(0);
+                    line(&mut out, "Board Mfg Date", &format_mfg_time(minutes));
+                }
+            }
+            _ => {}
+        }
+        if area.name == "MultiRecord Area" {
+            if let Some(multirecord) = &fru.multirecord {
+                for record in &multirecord.records {
+                    format_record(&mut out, record);
+                }
+            }
+        }
+        for field in &area.fields {
+            if field.data.is_empty() {
+                continue;
+            }
+            if let Some(label) = labels(&field.key) {
+                line(&mut out, label, &field.value);
+            }
+        }
+    }
+    out
+}
+
+///
+/// Render decoded FRU contents as JSON for other tools.
+///
+/// Every area is listed with offset, length and checksum; fields carry their offset,
+/// type/length byte, encoding, raw data (hex) and decoded value. Records carry their
+/// raw payload and, where the type is understood, their config form.
+pub fn format_fru_json(fru: &DecodedFru) -> String {
+    let offsets: Vec<usize> = fru.header[1..6].iter().map(|&o| o as usize * 8).collect();
+    let areas: Vec<Value> = fru
+        .areas
+        .iter()
+        .map(|area| {
+            let mut value = json!({
+                "name": area.name,
+                "offset": area.offset,
+                "length": area.length,
+                "checksum": area.checksum,
+            });
+            if area.name.ends_with("Info Area") {
+                value["fields"] = area
+                    .fields
+                    .iter()
+                    .map(|field| {
+                        let mut entry = serde_json::to_value(field).unwrap_or_default();
+                        entry["encoding"] = json!(Encoding::from_type_code(field.type_code()).name());
+                        entry["length"] = json!(field.data.len());
+                        entry
+                    })
+                    .collect();
+            }
+            match area.name.as_str() {
+                "Chassis Info Area" => {
+                    if let Some(chassis) = &fru.chassis {
+                        value["chassis_type"] = json!(chassis.chassis_type);
+                        value["chassis_type_name"] = json!(CHASSIS_TYPE_TABLE.get(chassis.chassis_type as usize));
+                    }
+                }
+                "Board Info Area" => {
+                    if let Some(board) = &fru.board {
+                        let minutes = board.board_mfg_date_time.parse::<u32>().unwrap_or(0);
+                        value["language"] = json!(board.language_code);
+                        value["language_name"] = json!(language_name(board.language_code));
+                        value["mfg_date_time"] = json!(minutes);
+                        value["mfg_date"] = json!(format_mfg_time(minutes));
+                    }
+                }
+                "Product Info Area" => {
+                    if let Some(product) = &fru.product {
+                        value["language"] = json!(product.language_code);
+                        value["language_name"] = json!(language_name(product.language_code));
+                    }
+                }
+                "Internal Use Area" => {
+                    if let Some(internal) = &fru.internal {
+                        value["payload"] = json!(internal.internal_info);
+                    }
+                }
+                "MultiRecord Area" => {
+                    value["records"] = fru
+                        .multirecord
+                        .iter()
+                        .flat_map(|m| &m.records)
+                        .map(|record| {
+                            json!({
+                                "type_id": record.type_id(),
+                                "name": record.name(),
+                                "payload": record.payload().iter().map(|b| format!("{:02X}", b)).collect::<String>(),
+                                "config": serde_json::to_value(record).ok(),
+                            })
+                        })
+                        .collect();
+                }
+                _ => {}
+            }
+            value
+        })
+        .collect();
+
+    let dump = json!({
+        "header": {
+            "format_version": fru.header[0],
+            "internal_use_offset": offsets[0],
+            "chassis_info_offset": offsets[1],
+            "board_info_offset": offsets[2],
+            "product_info_offset": offsets[3],
+            "multirecord_offset": offsets[4],
+            "checksum": fru.header[7],
+        },
+        "areas": areas,
+        "compliant": fru.is_compliant(),
+        "violations": fru.violations,
+    });
+    serde_json::to_string_pretty(&dump).unwrap_or_default()
+}
+
+fn format_record(out: &mut String, record: &Record) {
+    let title = match record {
+        Record::PowerSupply(_) => "Power Supply Record".to_string(),
+        Record::DcOutput(_) => "DC Output Record".to_string(),
+        Record::DcLoad(_) => "DC Load Record".to_string(),
+        Record::ManagementAccess(_) => "Management Access Record".to_string(),
+        Record::BaseCompatibility(_) => "Base Compatibility Record".to_string(),
+        Record::ExtendedCompatibility(_) => "Extended Compatibility Record".to_string(),
+        Record::Oem(oem) => format!("OEM Record (type 0x{:02X})", oem.type_id),
+        Record::Unknown { type_id, .. } => format!("Unknown Record (type 0x{:02X})", type_id),
+    };
+    out.push_str(&format!(" {}\n", title));
+
+    let mut line = |label: &str, value: String| {
+        out.push_str(&format!("  {:<27}: {}\n", label, value));
+    };
+    let volts = |v: i16| format!("{:.2} V", v as f64 / 100.0);
+    let amps = |a: u16| format!("{:.3} A", a as f64 / 1000.0);
+
+    match record {
+        Record::PowerSupply(ps) => {
+            let voltage = |code: u8| match code {
+                0 => "12 V",
+                1 => "-12 V",
+                2 => "5 V",
+                3 => "3.3 V",
+                _ => "unknown",
+            };
+            let flags: Vec<&str> = [
+                (ps.predictive_fail_support, "'Predictive fail'"),
+                (ps.power_factor_correction, "'PFC'"),
+                (ps.autoswitch, "'Autoswitch'"),
+                (ps.hot_swap, "'Hot swap'"),
+                (ps.predictive_fail_polarity, "'Polarity'"),
+            ]
+            .iter()
+            .filter(|(set, _)| *set)
+            .map(|(_, name)| *name)
+            .collect();
+
+            line("Capacity", format!("{} W", ps.overall_capacity));
+            line("Peak VA", format!("{} VA", ps.peak_va));
+            line("Inrush Current", format!("{} A", ps.inrush_current));
+            line("Inrush Interval", format!("{} ms", ps.inrush_interval));
+            line("Input Voltage Range 1", format!("{}-{} V", ps.low_input_voltage_1 / 100, ps.high_input_voltage_1 / 100));
+            line("Input Voltage Range 2", format!("{}-{} V", ps.low_input_voltage_2 / 100, ps.high_input_voltage_2 / 100));
+            line("Input Frequency Range", format!("{}-{} Hz", ps.low_input_frequency, ps.high_input_frequency));
+            line("A/C Dropout Tolerance", format!("{} ms", ps.dropout_tolerance));
+            line("Flags", flags.join(" "));
+            line("Peak capacity", format!("{} W", ps.peak_capacity));
+            line("Peak capacity holdup", format!("{} s", ps.hold_up_time));
+            if ps.total_combined_wattage == 0 {
+                line("Combined capacity", "not specified".to_string());
+            } else {
+                line("Combined capacity", format!(
+                    "{} W ({} and {})",
+                    ps.total_combined_wattage,
+                    voltage(ps.combined_voltage_1),
+                    voltage(ps.combined_voltage_2)
+                ));
+            }
+            if ps.predictive_fail_support {
+                line("Fan lower threshold", format!("{} RPS", ps.tach_lower_threshold));
+            }
+        }
+        Record::DcOutput(dc) => {
+            line("Output Number", dc.output_number.to_string());
+            line("Standby power", if dc.standby { "Yes" } else { "No" }.to_string());
+            line("Nominal voltage", volts(dc.nominal_voltage));
+            line("Max negative deviation", volts(dc.max_negative_deviation));
+            line("Max positive deviation", volts(dc.max_positive_deviation));
+            line("Ripple and noise pk-pk", format!("{} mV", dc.ripple_noise));
+            line("Minimum current draw", amps(dc.min_current));
+            line("Maximum current draw", amps(dc.max_current));
+        }
+        Record::DcLoad(dl) => {
+            line("Output Number", dl.output_number.to_string());
+            line("Nominal voltage", volts(dl.nominal_voltage));
+            line("Min voltage allowed", volts(dl.min_voltage));
+            line("Max voltage allowed", volts(dl.max_voltage));
+            line("Ripple and noise pk-pk", format!("{} mV", dl.ripple_noise));
+            line("Minimum current load", amps(dl.min_current));
+            line("Maximum current load", amps(dl.max_current));
+        }
+        Record::ManagementAccess(ma) => {
+            line("Sub-record", format!("{} (0x{:02X})", ma.label(), ma.sub_record));
+            line("Value", ma.value.clone());
+        }
+        Record::BaseCompatibility(c) | Record::ExtendedCompatibility(c) => {
+            line("Manufacturer ID", format!("0x{:06X}", c.manufacturer_id));
+            line("Entity ID", format!("0x{:02X}", c.entity_id));
+            line("Compatibility Base", format!("0x{:02X}", c.compatibility_base));
+            line("Code Start", format!("0x{:02X}", c.code_start));
+            line("Code Range Mask", c.code_range_mask.iter().map(|b| format!("{:02X}", b)).collect::<Vec<_>>().join(" "));
+        }
+        Record::Oem(oem) => {
+            line("Manufacturer ID", format!("0x{:06X}", oem.manufacturer_id));
+            line("Data", oem.data.iter().map(|b| format!("{:02X}", b)).collect::<Vec<_>>().join(" "));
+        }
+        Record::Unknown { data, .. } => {
+            line("Data", data.iter().map(|b| format!("{:02X}", b)).collect::<Vec<_>>().join(" "));
+        }
+    }
+}
@@ -0,0 +1,244 @@
+use super::decode::{decode_fru, format_mfg_time, DecodedArea, DecodedField, DecodedFru};
+use super::encoding::Encoding;
+use super::multirecord_area::Record;
+use crate::{language_name, CHASSIS_TYPE_TABLE};
+use anyhow::Result;
+use std::fmt;
+
+/// One difference between two images, attributed to an area and field.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Difference {
+    pub area: String,
+    /// Field label, or an area property such as `Checksum` or `Offset`.
+    pub field: String,
+    pub left: String,
+    pub right: String,
+}
+
+impl fmt::Display for Difference {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "[{}] {}: {} -> {}", self.area, self.field, self.left, self.right)
+    }
+}
+
+/// A run of bytes that differ, `start..end` in image offsets.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ByteRange {
+    pub start: usize,
+    pub end: usize,
+    /// Area of the left image the range starts in, if any.
+    pub area: Option<String>,
+}
+
+impl fmt::Display for ByteRange {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "0x{:04X}-0x{:04X} ({} bytes)", self.start, self.end - 1, self.end - self.start)?;
+        if let Some(area) = &self.area {
+            write!(f, " {}", area)?;
+        }
+        Ok(())
+    }
+}
+
+/// Field-level and byte-level comparison of two FRU images.
+#[derive(Debug, Default)]
+pub struct FruDiff {
+    pub differences: Vec<Difference>,
+    pub ranges: Vec<ByteRange>,
+}
+
+impl FruDiff {
+    pub fn is_empty(&self) -> bool {
+        self.differences.is_empty() && self.ranges.is_empty()
+    }
+
+    fn push(&mut self, area: &str, field: &str, left: String, right: String) {
+        if left != right {
+            self.differences.push(Difference {
+                area: area.to_string(),
+                field: field.to_string(),
+                left,
+                right,
+            });
+        }
+    }
+}
+
+const ABSENT: &str = "(absent)";
+
+fn quoted(value: &str) -> String {
+    format!("\"{}\"", value)
+}
+
+fn compare_fields(diff: &mut FruDiff, area: &str, left: &[DecodedField], right: &[DecodedField]) {
+    for i in 0..left.len().max(right.len()) {
+        let (l, r) = (left.get(i), right.get(i));
+        let label = l.or(r).map(|f| f.label.clone()).unwrap_or_default();
+        let (l, r) = match (l, r) {
+            (Some(l), Some(r)) => (l, r),
+            (l, r) => {
+                let show = |f: Option<&DecodedField>| f.map_or(ABSENT.to_string(), |f| quoted(f.value.trim_end()));
+                diff.push(area, &label, show(l), show(r));
+                continue;
+            }
+        };
+
+        // Trailing pad characters are reserved space, reported separately from the value
+        diff.push(area, &label, quoted(l.value.trim_end()), quoted(r.value.trim_end()));
+        diff.push(
+            area,
+            &format!("{} encoding", label),
+            Encoding::from_type_code(l.type_code()).name().to_string(),
+            Encoding::from_type_code(r.type_code()).name().to_string(),
+        );
+        if l.value.trim_end() == r.value.trim_end() {
+            diff.push(
+                area,
+                &format!("{} length", label),
+                format!("{} bytes", l.data.len()),
+                format!("{} bytes", r.data.len()),
+            );
+        }
+    }
+}
+
+fn compare_area(diff: &mut FruDiff, name: &str, left: Option<&DecodedArea>, right: Option<&DecodedArea>) {
+    let (l, r) = match (left, right) {
+        (None, None) => return,
+        (Some(l), Some(r)) => (l, r),
+        (l, r) => {
+            let show = |a: Option<&DecodedArea>| a.map_or(ABSENT.to_string(), |a| format!("present at 0x{:X}", a.offset));
+            diff.push(name, "Area", show(l), show(r));
+            return;
+        }
+    };
+    diff.push(name, "Offset", format!("0x{:X}", l.offset), format!("0x{:X}", r.offset));
+    diff.push(name, "Length", format!("{} bytes", l.length), format!("{} bytes", r.length));
+    if name != "Internal Use Area" && name != "MultiRecord Area" {
+        diff.push(name, "Checksum", format!("0x{:02X}", l.checksum), format!("0x{:02X}", r.checksum));
+    }
+    compare_fields(diff, name, &l.fields, &r.fields);
+}
+
+fn compare_decoded(diff: &mut FruDiff, left: &DecodedFru, right: &DecodedFru) {
+    let chassis_type = |fru: &DecodedFru| {
+        fru.chassis.as_ref().map_or(ABSENT.to_string(), |c| {
+            let name = CHASSIS_TYPE_TABLE.get(c.chassis_type as usize).copied().unwrap_or("Unknown");
+            format!("{} (0x{:02X})", name, c.chassis_type)
+        })
+    };
+    let mfg_date = |fru: &DecodedFru| {
+        fru.board.as_ref().map_or(ABSENT.to_string(), |b| {
+            format_mfg_time(b.board_mfg_date_time.parse::<u32>().unwrap_or(0))
+        })
+    };
+    let language = |code: Option<u8>| code.map_or(ABSENT.to_string(), |c| format!("{} ({})", language_name(c), c));
+
+    for name in ["Internal Use Area", "Chassis Info Area", "Board Info Area", "Product Info Area", "MultiRecord Area"] {
+        let (l, r) = (left.area(name), right.area(name));
+        match name {
+            "Chassis Info Area" if l.is_some() && r.is_some() => {
+                diff.push(name, "Chassis Type", chassis_type(left), chassis_type(right))
+            }
+            "Board Info Area" if l.is_some() && r.is_some() => {
+                diff.push(name, "Mfg Date", mfg_date(left), mfg_date(right));
+                diff.push(
+                    name,
+                    "Language",
+                    language(left.board.as_ref().map(|b| b.language_code)),
+                    language(right.board.as_ref().map(|b| b.language_code)),
+                );
+            }
+            "Product Info Area" if l.is_some() && r.is_some() => diff.push(
+                name,
+                "Language",
+                language(left.product.as_ref().map(|p| p.language_code)),
+                language(right.product.as_ref().map(|p| p.language_code)),
+            ),
+            _ => {}
+        }
+        compare_area(diff, name, l, r);
+    }
+
+    let records = |fru: &DecodedFru| fru.multirecord.as_ref().map(|m| m.records.clone()).unwrap_or_default();
+    let (l, r) = (records(left), records(right));
+    for i in 0..l.len().max(r.len()) {
+        let show = |record: Option<&Record>| {
+            record.map_or(ABSENT.to_string(), |rec| {
+                let payload: String = rec.payload().iter().map(|b| format!("{:02X}", b)).collect();
+                format!("{} [{}]", rec.name(), payload)
+            })
+        };
+        diff.push("MultiRecord Area", &format!("Record {}", i), show(l.get(i)), show(r.get(i)));
+    }
+
+    let violations = |fru: &DecodedFru, area: &str| {
+        fru.violations
+            .iter()
+            .filter(|v| v.area == area)
+            .map(|v| v.message.clone())
+            .collect::<Vec<_>>()
+            .join("; ")
+    };
+    let mut areas: Vec<&str> = Vec::new();
+    for violation in left.violations.iter().chain(&right.violations) {
+        if !areas.contains(&violation.area.as_str()) {
+            areas.push(&violation.area);
+        }
+    }
+    for area in areas {
+        let show = |s: String| if s.is_empty() { "compliant".to_string() } else { s };
+        diff.push(area, "Violations", show(violations(left, area)), show(violations(right, area)));
+    }
+}
+
+/// Runs of differing bytes; bytes past the end of the shorter image count as differing.
+fn byte_ranges(left: &[u8], right: &[u8], areas: &[DecodedArea]) -> Vec<ByteRange> {
+    let len = left.len().max(right.len());
+    let mut ranges = Vec::new();
+    let mut start = None;
+    for i in 0..=len {
+        let differs = i < len && left.get(i) != right.get(i);
+        match (differs, start) {
+            (true, None) => start = Some(i),
+            (false, Some(s)) => {
+                let area = areas
+                    .iter()
+                    .find(|a| (a.offset..a.offset + a.length).contains(&s))
+                    .map(|a| a.name.clone())
+                    .or_else(|| (s < 8).then(|| "Common Header".to_string()));
+                ranges.push(ByteRange { start: s, end: i, area });
+                start = None;
+            }
+            _ => {}
+        }
+    }
+    ranges
+}
+
+///
+/// Compare two FRU images area by area and field by field.
+///
+/// Field values are compared without their trailing pad characters; a difference that
+/// is only reserved padding shows up as a `length` difference instead.
+///
+/// # Example
+/// ```no_run
+/// use fru_gen::modules::diff::diff_images;
+///
+/// let golden = std::fs::read("golden.bin")?;
+/// let readback = std::fs::read("readback.bin")?;
+/// for difference in &diff_images(&golden, &readback)?.differences {
+///     println!("{}", difference);
+/// }
+/// # Ok::<(), anyhow::Error>(())
+/// ```
+pub fn diff_images(left: &[u8], right: &[u8]) -> Result<FruDiff> {
+    let left_fru = decode_fru(left)?;
+    let right_fru = decode_fru(right)?;
+
+    let mut diff = FruDiff::default();
+    compare_decoded(&mut diff, &left_fru, &right_fru);
+    diff.ranges = byte_ranges(left, right, &left_fru.areas);
+    Ok(diff)
+}
@@ -0,0 +1,385 @@
+use super::area::FieldConfig;
+use crate::FruError;
+use std::fmt;
+
+/// Largest data length a type/length byte can express.
+pub const MAX_FIELD_LENGTH: usize = 0x3F;
+
+/// Characters of the BCD plus encoding, indexed by nibble value (0xD-0xF are reserved).
+const BCD_PLUS_TABLE: &[char] = &['0', '1', '2', '3', '4', '5', '6', '7', '8', '9', ' ', '-', '.'];
+
+/// Type code of an IPMI type/length field (bits 7:6 of the type/length byte).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Encoding {
+    /// 00b: binary or unspecified; given in config as a hex string.
+    Binary,
+    /// 01b: BCD plus, two characters from "0-9 -." per byte.
+    BcdPlus,
+    /// 10b: 6-bit packed ASCII, four characters from 0x20-0x5F per three bytes.
+    SixBitAscii,
+    /// 11b: 8-bit ASCII + Latin 1.
+    #[default]
+    Ascii8,
+    /// Pick the smallest of BCD plus, 6-bit ASCII and 8-bit ASCII that can hold the value.
+    Auto,
+}
+
+/// Encodings in the order the TUI cycles through them.
+pub const ENCODING_CYCLE: &[Encoding] = &[
+    Encoding::Ascii8,
+    Encoding::Auto,
+    Encoding::SixBitAscii,
+    Encoding::BcdPlus,
+    Encoding::Binary,
+];
+
+impl Encoding {
+    ///
+    /// Parse an encoding name as written in config files.
+    ///
+    /// Accepts `binary`, `bcd` / `bcd_plus`, `6bit` / `six_bit_ascii`, `ascii` / `8bit` and `auto`.
+    pub fn parse(input: &str) -> Option<Encoding> {
+        match input.trim().to_lowercase().replace(['-', ' '], "_").as_str() {
+            "binary" | "bin" => Some(Encoding::Binary),
+            "bcd" | "bcd_plus" | "bcdplus" => Some(Encoding::BcdPlus),
+            "6bit" | "6_bit" | "sixbit" | "six_bit_ascii" | "6bit_ascii" => Some(Encoding::SixBitAscii),
+            "ascii" | "8bit" | "8_bit" | "8bit_ascii" | "text" => Some(Encoding::Ascii8),
+            "auto" => Some(Encoding::Auto),
+            _ => None,
+        }
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Encoding::Binary => "binary",
+            Encoding::BcdPlus => "bcd_plus",
+            Encoding::SixBitAscii => "6bit_ascii",
+            Encoding::Ascii8 => "8bit_ascii",
+            Encoding::Auto => "auto",
+        }
+    }
+
+    /// The type code for bits 7:6, or `None` for `Auto` which is resolved per value.
+    pub fn type_code(self) -> Option<u8> {
+        match self {
+            Encoding::Binary => Some(0b00),
+            Encoding::BcdPlus => Some(0b01),
+            Encoding::SixBitAscii => Some(0b10),
+            Encoding::Ascii8 => Some(0b11),
+            Encoding::Auto => None,
+        }
+    }
+
+    pub fn from_type_code(code: u8) -> Encoding {
+        match code & 0b11 {
+            0b00 => Encoding::Binary,
+            0b01 => Encoding::BcdPlus,
+            0b10 => Encoding::SixBitAscii,
+            _ => Encoding::Ascii8,
+        }
+    }
+
+    /// Byte used to fill reserved space: an encoded space where the encoding has one.
+    pub fn pad_byte(self) -> u8 {
+        match self {
+            Encoding::BcdPlus => 0xAA,
+            Encoding::SixBitAscii | Encoding::Binary => 0x00,
+            Encoding::Ascii8 | Encoding::Auto => b' ',
+        }
+    }
+
+    pub fn next(self) -> Encoding {
+        let i = ENCODING_CYCLE.iter().position(|&e| e == self).unwrap_or(0);
+        ENCODING_CYCLE[(i + 1) % ENCODING_CYCLE.len()]
+    }
+}
+
+/// How a field fills the bytes it reserves beyond its value.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Padding {
+    /// The encoding's space character (0x20 for 8-bit ASCII).
+    #[default]
+    Space,
+    /// A fixed byte such as 0x00 or 0xFF.
+    Byte(u8),
+    /// No padding in the field; the reservation is kept as free space after the area's
+    /// 0xC1 end marker, where a later update can grow the field into.
+    AfterEnd,
+}
+
+/// Paddings in the order the TUI cycles through them.
+pub const PADDING_CYCLE: &[Padding] = &[Padding::Space, Padding::Byte(0x00), Padding::Byte(0xFF), Padding::AfterEnd];
+
+impl Padding {
+    ///
+    /// Parse a padding name as written in config files.
+    ///
+    /// Accepts `space`, `nul`, `ff`, any byte as `0xNN`, and `after_end` / `free`.
+    pub fn parse(input: &str) -> Option<Padding> {
+        let name = input.trim().to_lowercase().replace(['-', ' '], "_");
+        match name.as_str() {
+            "space" | "spaces" => Some(Padding::Space),
+            "nul" | "null" | "zero" => Some(Padding::Byte(0x00)),
+            "ff" | "erased" => Some(Padding::Byte(0xFF)),
+            "after_end" | "free" | "free_space" => Some(Padding::AfterEnd),
+            _ => name
+                .strip_prefix("0x")
+                .and_then(|hex| u8::from_str_radix(hex, 16).ok())
+                .map(Padding::Byte),
+        }
+    }
+
+    pub fn name(self) -> String {
+        match self {
+            Padding::Space => "space".to_string(),
+            Padding::Byte(0x00) => "nul".to_string(),
+            Padding::Byte(0xFF) => "ff".to_string(),
+            Padding::Byte(byte) => format!("0x{:02X}", byte),
+            Padding::AfterEnd => "after_end".to_string(),
+        }
+    }
+
+    pub fn next(self) -> Padding {
+        let i = PADDING_CYCLE.iter().position(|&p| p == self).unwrap_or(0);
+        PADDING_CYCLE[(i + 1) % PADDING_CYCLE.len()]
+    }
+}
+
+/// Whether an IPMI language code means English (codes 0 and 25).
+pub fn is_english(language: u8) -> bool {
+    language == 0 || language == 25
+}
+
+/// Length in bytes of `value` stored as 8-bit text in an area with the given language.
+pub fn text_length(value: &str, language: u8) -> usize {
+    if is_english(language) {
+        value.chars().count()
+    } else {
+        value.encode_utf16().count() * 2
+    }
+}
+
+fn is_bcd_plus(value: &str) -> bool {
+    value.chars().all(|c| BCD_PLUS_TABLE.contains(&c))
+}
+
+fn is_six_bit(value: &str) -> bool {
+    value.chars().all(|c| (' '..='_').contains(&c))
+}
+
+/// Resolve `Auto` to the smallest encoding able to represent `value`.
+pub fn resolve_encoding(value: &str, encoding: Encoding) -> Encoding {
+    if encoding != Encoding::Auto {
+        return encoding;
+    }
+    if value.is_empty() {
+        Encoding::Ascii8
+    } else if is_bcd_plus(value) {
+        Encoding::BcdPlus
+    } else if is_six_bit(value) {
+        Encoding::SixBitAscii
+    } else {
+        Encoding::Ascii8
+    }
+}
+
+///
+/// Encode `value` into field data bytes (without the type/length byte).
+///
+/// Returns the concrete encoding used (relevant for `Auto`) and the data.
+pub fn encode_value(value: &str, encoding: Encoding) -> Result<(Encoding, Vec<u8>), String> {
+    encode_text(value, encoding, 0)
+}
+
+///
+/// Encode `value` for an area with the given language code.
+///
+/// In non-English areas type 11b holds 2-byte UNICODE, least significant byte first.
+pub fn encode_text(value: &str, encoding: Encoding, language: u8) -> Result<(Encoding, Vec<u8>), String> {
+    let encoding = resolve_encoding(value, encoding);
+    if encoding == Encoding::Ascii8 && !is_english(language) {
+        let bytes = value.encode_utf16().flat_map(u16::to_le_bytes).collect();
+        return Ok((encoding, bytes));
+    }
+    let bytes = match encoding {
+        Encoding::Binary => crate::parse_hex_bytes(value)?,
+        Encoding::BcdPlus => {
+            let nibbles = value
+                .chars()
+                .map(|c| {
+                    BCD_PLUS_TABLE
+                        .iter()
+                        .position(|&b| b == c)
+                        .map(|n| n as u8)
+                        .ok_or_else(|| format!("'{}' cannot be encoded as BCD plus (allowed: 0-9, space, '-', '.')", c))
+                })
+                .collect::<Result<Vec<u8>, String>>()?;
+            nibbles
+                .chunks(2)
+                .map(|pair| pair[0] << 4 | pair.get(1).copied().unwrap_or(0x0A))
+                .collect()
+        }
+        Encoding::SixBitAscii => {
+            let codes = value
+                .chars()
+                .map(|c| {
+                    if (' '..='_').contains(&c) {
+                        Ok(c as u8 - 0x20)
+                    } else {
+                        Err(format!("'{}' cannot be encoded as 6-bit ASCII (allowed: 0x20-0x5F, upper case)", c))
+                    }
+                })
+                .collect::<Result<Vec<u8>, String>>()?;
+            let mut bytes = Vec::new();
+            for group in codes.chunks(4) {
+                let packed = group
+                    .iter()
+                    .enumerate()
+                    .fold(0u32, |acc, (i, &code)| acc | (code as u32) << (6 * i));
+                let len = (group.len() * 6).div_ceil(8);
+                bytes.extend(&packed.to_le_bytes()[..len]);
+            }
+            bytes
+        }
+        Encoding::Ascii8 | Encoding::Auto => value
+            .chars()
+            .map(|c| {
+                u8::try_from(c as u32)
+                    .map_err(|_| format!("'{}' cannot be encoded as 8-bit ASCII/Latin-1", c))
+            })
+            .collect::<Result<Vec<u8>, String>>()?,
+    };
+    Ok((encoding, bytes))
+}
+
+///
+/// Encode one type/length field according to its settings.
+///
+/// Disabled fields yield `None`. Data shorter than `reserved_bytes` is padded as the
+/// field's [`Padding`] says (see [`free_space`] for `AfterEnd`), and single-byte 8-bit
+/// text to two bytes, since type/length 0xC1 is the end-of-fields marker. Data or a
+/// reservation beyond 0x3F bytes (0x3E, whole characters, for UNICODE) is an error.
+pub fn encode_field(value: &str, config: &FieldConfig) -> Result<Option<Vec<u8>>, String> {
+    encode_checked(value, config).map_err(|e| e.to_string())
+}
+
+///
+/// [`encode_field`] for a field of an info area, failing with a [`FruError`]: data longer
+/// than a field can hold is `FieldTooLong`, any other failure `FieldEncoding`.
+pub fn encode_area_field(area: &str, field: &str, value: &str, config: &FieldConfig) -> Result<Option<Vec<u8>>, FruError> {
+    encode_checked(value, config).map_err(|e| match e {
+        FieldFailure::TooLong { len, .. } => FruError::FieldTooLong { area: area.to_string(), field: field.to_string(), len },
+        FieldFailure::Other(message) => FruError::FieldEncoding { area: area.to_string(), field: field.to_string(), message },
+    })
+}
+
+/// Why a field could not be encoded, kept apart so areas can report lengths as `FieldTooLong`.
+enum FieldFailure {
+    TooLong { len: usize, max: usize },
+    Other(String),
+}
+
+impl fmt::Display for FieldFailure {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            FieldFailure::TooLong { len, max } => {
+                write!(f, "value encodes to {} bytes, more than the {} a field can hold", len, max)
+            }
+            FieldFailure::Other(message) => f.write_str(message),
+        }
+    }
+}
+
+fn encode_checked(value: &str, config: &FieldConfig) -> Result<Option<Vec<u8>>, FieldFailure> {
+    if !config.enabled {
+        return Ok(None);
+    }
+    let (encoding, mut bytes) = encode_text(value, config.encoding, config.language).map_err(FieldFailure::Other)?;
+    let unicode = encoding == Encoding::Ascii8 && !is_english(config.language);
+    let max = if unicode { MAX_FIELD_LENGTH & !1 } else { MAX_FIELD_LENGTH };
+    if bytes.len() > max {
+        return Err(FieldFailure::TooLong { len: bytes.len(), max });
+    }
+    if config.reserved_bytes > max {
+        return Err(FieldFailure::Other(format!(
+            "reserve_bytes of {} exceeds the {} bytes a field can hold",
+            config.reserved_bytes, max
+        )));
+    }
+    let reserved = if config.padding == Padding::AfterEnd { 0 } else { config.reserved_bytes };
+    if reserved > 0 && bytes.len() < reserved {
+        let pad = match config.padding {
+            Padding::Byte(byte) => [byte, byte],
+            _ if unicode => [b' ', 0x00],
+            _ => [encoding.pad_byte(); 2],
+        };
+        if unicode {
+            while bytes.len() < reserved {
+                bytes.extend_from_slice(&pad);
+            }
+        } else {
+            bytes.resize(reserved, pad[0]);
+        }
+    }
+    let type_code = encoding.type_code().unwrap_or(0b11);
+    if type_code == 0b11 && bytes.len() == 1 {
+        // 0xC1 would read as the end-of-fields marker
+        bytes.push(match config.padding {
+            Padding::Byte(byte) => byte,
+            _ => encoding.pad_byte(),
+        });
+    }
+    let len = bytes.len().min(max);
+    let mut res = vec![type_code << 6 | len as u8];
+    res.extend_from_slice(&bytes[..len]);
+    Ok(Some(res))
+}
+
+/// Bytes an encoded field (type/length byte included) keeps free after the area's end
+/// marker: the rest of its reservation when its padding is [`Padding::AfterEnd`].
+pub fn free_space(field: &[u8], config: &FieldConfig) -> usize {
+    match config.padding {
+        Padding::AfterEnd => config.reserved_bytes.saturating_sub(field.len().saturating_sub(1)),
+        _ => 0,
+    }
+}
+
+/// Decode field data according to the type code in its type/length byte.
+pub fn decode_value(type_length: u8, data: &[u8]) -> String {
+    decode_text(type_length, data, 0)
+}
+
+/// Decode field data of an area with the given language code.
+pub fn decode_text(type_length: u8, data: &[u8], language: u8) -> String {
+    match Encoding::from_type_code(type_length >> 6) {
+        Encoding::Ascii8 if !is_english(language) => {
+            let units: Vec<u16> = data
+                .chunks_exact(2)
+                .map(|pair| u16::from_le_bytes([pair[0], pair[1]]))
+                .collect();
+            String::from_utf16_lossy(&units)
+        }
+        Encoding::Binary => data
+            .iter()
+            .map(|b| format!("{:02X}", b))
+            .collect::<Vec<_>>()
+            .join(" "),
+        Encoding::BcdPlus => data
+            .iter()
+            .flat_map(|&b| [b >> 4, b & 0x0F])
+            .map(|n| BCD_PLUS_TABLE.get(n as usize).copied().unwrap_or('?'))
+            .collect(),
+        Encoding::SixBitAscii => {
+            let count = data.len() * 8 / 6;
+            (0..count)
+                .map(|i| {
+                    let bit = i * 6;
+                    let word = data[bit / 8] as u16
+                        | (data.get(bit / 8 + 1).copied().unwrap_or(0) as u16) << 8;
+                    (((word >> (bit % 8)) & 0x3F) as u8 + 0x20) as char
+                })
+                .collect()
+        }
+        _ => data.iter().map(|&b| b as char).collect(),
+    }
+}
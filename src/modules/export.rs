@@ -0,0 +1,325 @@
+use super::decode::{decode_fru, DecodedArea, DecodedField, DecodedFru};
+use super::encoding::{encode_text, is_english, Encoding, Padding};
+use super::multirecord_area::Record;
+use crate::{
+    parse_hex_bytes, parse_language_code, InternalUseConfig, PayloadSource, CHASSIS_TYPE_TABLE, INTERNAL_USE_KEY,
+    LANGUAGE_CODE_TABLE, MULTIRECORD_KEY,
+};
+use anyhow::Result;
+use chrono::{Duration, TimeZone, Utc};
+use serde_json::Value;
+use std::path::Path;
+
+/// Reserved bytes a string field gets when its config entry is a plain string.
+const DEFAULT_RESERVE: usize = 32;
+
+/// Key carrying the export notes in JSON configs, which have no comments.
+pub const COMMENT_KEY: &str = "_comment";
+
+/// Config keys as `build_config_template` writes them, for each decoded field key.
+const TEMPLATE_KEYS: &[(&str, &str)] = &[
+    ("chassis_part_number", "Chassis_Part_Number"),
+    ("chassis_serial_number", "Chassis_Serial_Number"),
+    ("chassis_extra", "Chassis_Extra"),
+    ("board_manufacturer", "Board_Manufacturer"),
+    ("board_product_name", "Board_Product_Name"),
+    ("board_serial_number", "Board_Serial_Number"),
+    ("board_part_number", "Board_Part_Number"),
+    ("board_fruid", "Board_Fruid"),
+    ("board_extra", "Board_Extra"),
+    ("product_manufacturer", "Product_Manufacturer"),
+    ("product_name", "Product_Name"),
+    ("product_part_number", "Product_Part_Number"),
+    ("product_version", "Product_Version"),
+    ("product_serial_number", "Product_Serial_Number"),
+    ("product_asset_tag", "Product_Asset_Tag"),
+    ("product_fruid", "Product_Fruid"),
+    ("product_extra", "Product_Extra"),
+];
+
+/// Config file flavour to export to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ConfigFormat {
+    Toml,
+    Yaml,
+    Json,
+}
+
+impl ConfigFormat {
+    /// YAML for `.yaml` / `.yml`, JSON for `.json`, TOML otherwise.
+    pub fn from_path(path: &Path) -> ConfigFormat {
+        match path.extension().and_then(|s| s.to_str()).map(|s| s.to_lowercase()).as_deref() {
+            Some("yaml") | Some("yml") => ConfigFormat::Yaml,
+            Some("json") => ConfigFormat::Json,
+            _ => ConfigFormat::Toml,
+        }
+    }
+
+    fn assign(self) -> &'static str {
+        match self {
+            ConfigFormat::Toml => " = ",
+            ConfigFormat::Yaml | ConfigFormat::Json => ": ",
+        }
+    }
+}
+
+/// A scalar, list or inline table in the notation of `format`.
+fn inline(value: &Value, format: ConfigFormat) -> String {
+    match value {
+        Value::Array(items) => format!(
+            "[{}]",
+            items.iter().map(|v| inline(v, format)).collect::<Vec<_>>().join(", ")
+        ),
+        Value::Object(map) => format!(
+            "{{ {} }}",
+            map.iter()
+                .map(|(k, v)| format!("{}{}{}", k, format.assign(), inline(v, format)))
+                .collect::<Vec<_>>()
+                .join(", ")
+        ),
+        Value::Null => "\"\"".to_string(),
+        // JSON string escapes are valid in TOML basic strings and YAML double-quoted strings
+        other => other.to_string(),
+    }
+}
+
+///
+/// Config entry for one decoded field.
+///
+/// Trailing pad characters are taken as reserved space. A plain string is written when
+/// the default settings (8-bit ASCII, 32 reserved bytes) reproduce the field exactly.
+fn field_entry(field: &DecodedField, language: u8) -> Value {
+    let encoding = Encoding::from_type_code(field.type_code());
+    // 8-bit English text padded with a byte other than space keeps that padding
+    let pad = match field.data.last() {
+        Some(&byte) if encoding == Encoding::Ascii8 && is_english(language) && (byte == 0x00 || byte == 0xFF) => {
+            Some(Padding::Byte(byte))
+        }
+        _ => None,
+    };
+    let value = match pad {
+        _ if encoding == Encoding::Binary => field.value.clone(),
+        Some(Padding::Byte(byte)) => field.value.trim_end_matches(byte as char).to_string(),
+        _ => field.value.trim_end_matches(' ').to_string(),
+    };
+    let encoded_len = encode_text(&value, encoding, language)
+        .map(|(_, bytes)| bytes.len())
+        .unwrap_or(field.data.len());
+
+    let padded = field.data.len() > encoded_len;
+    if encoding == Encoding::Ascii8 && pad.is_none() && field.data.len() == encoded_len.max(DEFAULT_RESERVE) {
+        return Value::String(value);
+    }
+    let mut entry = serde_json::Map::new();
+    entry.insert("value".to_string(), Value::String(value));
+    entry.insert("reserve_bytes".to_string(), Value::from(if padded { field.data.len() } else { 0 }));
+    if encoding != Encoding::Ascii8 {
+        entry.insert("encoding".to_string(), Value::String(encoding.name().to_string()));
+    }
+    if let Some(pad) = pad.filter(|_| padded) {
+        entry.insert("pad".to_string(), Value::String(pad.name()));
+    }
+    Value::Object(entry)
+}
+
+/// Language as its ISO 639 code where that reads back to the same IPMI code.
+fn language_entry(code: u8) -> String {
+    match LANGUAGE_CODE_TABLE.get(code as usize) {
+        Some((iso, _)) if parse_language_code(iso) == Some(code) => iso.to_string(),
+        _ => format!("0x{:02X}", code),
+    }
+}
+
+fn mfg_date_entry(minutes: &str) -> String {
+    match minutes.parse::<i64>() {
+        Ok(minutes) if minutes > 0 => {
+            let epoch = Utc.with_ymd_and_hms(1996, 1, 1, 0, 0, 0).single().unwrap();
+            (epoch + Duration::minutes(minutes)).format("%Y%m%d%H%M%S").to_string()
+        }
+        _ => "0".to_string(),
+    }
+}
+
+/// Predefined fields under their own keys; the first custom field as `*_Extra`, the rest
+/// as the `*_Custom` list.
+fn push_fields(lines: &mut Vec<(String, Value)>, area: &DecodedArea, custom_key: &str, language: u8) {
+    let mut seen_extra = false;
+    let mut custom = Vec::new();
+    for field in &area.fields {
+        if field.key.ends_with("_extra") {
+            if seen_extra {
+                custom.push(field_entry(field, language));
+                continue;
+            }
+            seen_extra = true;
+        }
+        if let Some((_, key)) = TEMPLATE_KEYS.iter().find(|(k, _)| *k == field.key) {
+            lines.push((key.to_string(), field_entry(field, language)));
+        }
+    }
+    if !custom.is_empty() {
+        lines.push((custom_key.to_string(), Value::Array(custom)));
+    }
+}
+
+/// Payload of the empty internal use area the builder writes by default.
+const EMPTY_INTERNAL_USE: &str = "000000000000FF";
+
+/// The `[internal_use]` table for a decoded internal use area; trailing 0x00 bytes are
+/// taken as reserved space. `None` for the default empty area.
+fn internal_use_entry(fru: &DecodedFru) -> Option<InternalUseConfig> {
+    let internal = fru.internal.as_ref()?;
+    if internal.internal_info == EMPTY_INTERNAL_USE {
+        return None;
+    }
+    let mut payload = parse_hex_bytes(&internal.internal_info).ok()?;
+    let length = payload.len();
+    while payload.last() == Some(&0x00) {
+        payload.pop();
+    }
+    let hex = payload.iter().map(|b| format!("{:02X}", b)).collect::<Vec<_>>().join(":");
+    Some(InternalUseConfig {
+        payload: (!payload.is_empty()).then(|| PayloadSource { hex: Some(hex), ..Default::default() }),
+        reserve_bytes: (payload.len() < length).then_some(length),
+    })
+}
+
+///
+/// Render decoded FRU contents as a config that regenerates the same areas.
+///
+/// Keys follow `build_config_template`. Parts a config cannot express (absent info areas,
+/// unknown multi-record types) are listed as comments at the top, or under [`COMMENT_KEY`]
+/// in JSON.
+pub fn export_config(fru: &DecodedFru, format: ConfigFormat) -> Result<String> {
+    let mut lines: Vec<(String, Value)> = Vec::new();
+    let mut notes = Vec::new();
+
+    match (&fru.chassis, fru.area("Chassis Info Area")) {
+        (Some(chassis), Some(area)) => {
+            let chassis_type = CHASSIS_TYPE_TABLE
+                .get(chassis.chassis_type as usize)
+                .map(|name| name.to_string())
+                .unwrap_or_else(|| format!("0x{:02X}", chassis.chassis_type));
+            lines.push(("Chassis_type".to_string(), Value::String(chassis_type)));
+            push_fields(&mut lines, area, "Chassis_Custom", 0);
+        }
+        _ => notes.push("Chassis Info Area not present in the image".to_string()),
+    }
+
+    match (&fru.board, fru.area("Board Info Area")) {
+        (Some(board), Some(area)) => {
+            if board.language_code != 0 {
+                lines.push(("Board_Language".to_string(), Value::String(language_entry(board.language_code))));
+            }
+            lines.push(("Board_mfg_date_time".to_string(), Value::String(mfg_date_entry(&board.board_mfg_date_time))));
+            push_fields(&mut lines, area, "Board_Custom", board.language_code);
+        }
+        _ => notes.push("Board Info Area not present in the image".to_string()),
+    }
+
+    match (&fru.product, fru.area("Product Info Area")) {
+        (Some(product), Some(area)) => {
+            if product.language_code != 0 {
+                lines.push(("Product_Language".to_string(), Value::String(language_entry(product.language_code))));
+            }
+            push_fields(&mut lines, area, "Product_Custom", product.language_code);
+        }
+        _ => notes.push("Product Info Area not present in the image".to_string()),
+    }
+
+    let internal_use = internal_use_entry(fru).map(serde_json::to_value).transpose()?;
+
+    let mut areas: Vec<&DecodedArea> = fru.areas.iter().collect();
+    areas.sort_by_key(|a| a.offset);
+    let packed = areas.first().is_none_or(|a| a.offset == 8)
+        && areas.windows(2).all(|pair| pair[0].offset + pair[0].length == pair[1].offset);
+    if !packed {
+        notes.push("Areas are not packed after the header; their offsets need a [layout] table, not exported".to_string());
+    }
+
+    let mut records = Vec::new();
+    for record in fru.multirecord.iter().flat_map(|m| &m.records) {
+        match record {
+            Record::Unknown { type_id, .. } => {
+                notes.push(format!("MultiRecord Area: record type 0x{:02X} has no config form, not exported", type_id))
+            }
+            record => records.push(serde_json::to_value(record)?),
+        }
+    }
+
+    if format == ConfigFormat::Json {
+        // JSON has no comments; the notes go into a string key no area reads
+        let mut map = serde_json::Map::new();
+        if !notes.is_empty() {
+            map.insert(COMMENT_KEY.to_string(), Value::String(notes.join("; ")));
+        }
+        map.extend(lines);
+        if let Some(internal_use) = internal_use {
+            map.insert(INTERNAL_USE_KEY.to_string(), internal_use);
+        }
+        if !records.is_empty() {
+            map.insert(MULTIRECORD_KEY.to_string(), Value::Array(records));
+        }
+        let mut out = serde_json::to_string_pretty(&map)?;
+        out.push('\n');
+        return Ok(out);
+    }
+
+    let mut out = String::new();
+    for note in &notes {
+        out.push_str(&format!("# {}\n", note));
+    }
+    if !notes.is_empty() {
+        out.push('\n');
+    }
+    for (key, value) in &lines {
+        out.push_str(&format!("{}{}{}\n", key, format.assign(), inline(value, format)));
+    }
+
+    if let Some(Value::Object(entries)) = &internal_use {
+        match format {
+            ConfigFormat::Toml => out.push_str(&format!("\n[{}]\n", INTERNAL_USE_KEY)),
+            ConfigFormat::Yaml | ConfigFormat::Json => out.push_str(&format!("\n{}:\n", INTERNAL_USE_KEY)),
+        }
+        let indent = if format == ConfigFormat::Toml { "" } else { "  " };
+        for (key, value) in entries {
+            out.push_str(&format!("{}{}{}{}\n", indent, key, format.assign(), inline(value, format)));
+        }
+    }
+
+    if format == ConfigFormat::Yaml && !records.is_empty() {
+        out.push_str(&format!("\n{}:\n", MULTIRECORD_KEY));
+    }
+    for record in &records {
+        let Value::Object(entries) = record else { continue };
+        match format {
+            ConfigFormat::Toml => {
+                out.push_str(&format!("\n[[{}]]\n", MULTIRECORD_KEY));
+                for (key, value) in entries {
+                    out.push_str(&format!("{} = {}\n", key, inline(value, format)));
+                }
+            }
+            ConfigFormat::Yaml | ConfigFormat::Json => {
+                for (i, (key, value)) in entries.iter().enumerate() {
+                    let bullet = if i == 0 { "  - " } else { "    " };
+                    out.push_str(&format!("{}{}: {}\n", bullet, key, inline(value, format)));
+                }
+            }
+        }
+    }
+    Ok(out)
+}
+
+/// Decode a FRU binary and render it as a config; see [`export_config`].
+///
+/// # Example
+/// ```no_run
+/// use fru_gen::modules::export::{export_image, ConfigFormat};
+///
+/// let image = std::fs::read("vendor.bin")?;
+/// std::fs::write("recovered.toml", export_image(&image, ConfigFormat::Toml)?)?;
+/// # Ok::<(), anyhow::Error>(())
+/// ```
+pub fn export_image(data: &[u8], format: ConfigFormat) -> Result<String> {
+    export_config(&decode_fru(data)?, format)
+}
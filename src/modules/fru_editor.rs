@@ -1,4 +1,5 @@
-use super::{area::Area, board_area::{Board, parse_mfg_time}, chassis_area::Chassis, product_area::Product};
+use super::{area::{zero_checksum, Area, CustomField}, board_area::{Board, parse_mfg_time}, chassis_area::Chassis, product_area::Product};
+use super::encoding::{encode_field, encode_value, Encoding, Padding, MAX_FIELD_LENGTH};
 use crate::{parse_chassis_type, CHASSIS_TYPE_TABLE, ConfigField};
 use chrono::{Duration, TimeZone, Utc};
 use crossterm::{
@@ -36,11 +37,110 @@ pub struct Line {
     // Settings
     enabled: bool,
     reserved_bytes: usize,
+    encoding: Encoding,
+    padding: Padding,
 }
 
 impl Line {
     pub fn enabled(&self) -> bool { self.enabled }
     pub fn reserved_bytes(&self) -> usize { self.reserved_bytes }
+    pub fn encoding(&self) -> Encoding { self.encoding }
+    pub fn padding(&self) -> Padding { self.padding }
+
+    /// Whether the field reserves more bytes than its type/length byte can hold.
+    fn reserve_exceeds(&self) -> bool {
+        self.enabled && self.reserved_bytes > MAX_FIELD_LENGTH
+    }
+
+    fn reserve_info(&self) -> String {
+        if self.reserve_exceeds() {
+            format!("{} (EXCEEDS 0x3F! '-' reduces it)", self.reserved_bytes)
+        } else {
+            self.reserved_bytes.to_string()
+        }
+    }
+
+    fn field_config(&self) -> super::area::FieldConfig {
+        super::area::FieldConfig {
+            enabled: self.enabled,
+            reserved_bytes: self.reserved_bytes,
+            encoding: self.encoding,
+            padding: self.padding,
+            ..Default::default()
+        }
+    }
+}
+
+/// Line indices holding codes rather than type/length encoded strings.
+const CODE_LINES: &[usize] = &[0, 4];
+
+/// Lines of the predefined fields; custom field lines follow them.
+const FIXED_LINES: usize = 19;
+
+/// Config keys of the custom field lists, in area order.
+const CUSTOM_KEYS: &[&str] = &["Chassis_Custom", "Board_Custom", "Product_Custom"];
+
+/// Custom list key of a `Board_Custom[2]: ` line.
+fn custom_key(line: &Line) -> Option<&str> {
+    line.immutable.split_once('[').map(|(key, _)| key).filter(|key| CUSTOM_KEYS.contains(key))
+}
+
+/// Custom list key of the area the line at `index` belongs to.
+fn area_custom_key(lines: &[Line], index: usize) -> &'static str {
+    match index {
+        0..=3 => CUSTOM_KEYS[0],
+        4..=10 => CUSTOM_KEYS[1],
+        11..=18 => CUSTOM_KEYS[2],
+        _ => {
+            let key = custom_key(&lines[index]).unwrap_or_default();
+            CUSTOM_KEYS.iter().find(|k| **k == key).copied().unwrap_or(CUSTOM_KEYS[2])
+        }
+    }
+}
+
+fn custom_line(key: &str, editable: String, reserved_bytes: usize, encoding: Encoding, padding: Padding) -> Line {
+    Line {
+        immutable: format!("{}[]: ", key),
+        editable,
+        selected: false,
+        enabled: true,
+        reserved_bytes,
+        encoding,
+        padding,
+    }
+}
+
+/// Padding of the field under `key`: its own `pad`, else its area's `*_Pad`.
+fn padding_of(data: &HashMap<String, ConfigField>, key: &str) -> Padding {
+    let area = key.split('_').next().unwrap_or_default();
+    data.get(key)
+        .and_then(|field| field.pad())
+        .or_else(|| data.get(&format!("{}_pad", area)).map(|field| field.value()))
+        .and_then(|name| Padding::parse(&name))
+        .unwrap_or_default()
+}
+
+/// Number custom lines per area from 1, in line order.
+fn renumber_custom_lines(lines: &mut [Line]) {
+    for key in CUSTOM_KEYS {
+        let mut number = 0;
+        for line in lines.iter_mut().skip(FIXED_LINES) {
+            if custom_key(line) == Some(key) {
+                number += 1;
+                line.immutable = format!("{}[{}]: ", key, number);
+            }
+        }
+    }
+}
+
+/// Enabled custom fields of the list `key`, as the area structs take them.
+fn custom_fields(lines: &[Line], key: &str) -> Vec<CustomField> {
+    lines
+        .iter()
+        .skip(FIXED_LINES)
+        .filter(|line| line.enabled && custom_key(line) == Some(key))
+        .map(|line| CustomField { value: line.editable.clone(), config: line.field_config() })
+        .collect()
 }
 
 #[derive(PartialEq, Debug)]
@@ -112,6 +212,11 @@ impl FRUEditor {
                 "{}\nHint: Enter timestamp (YYYYMMDDHHMMSS) or minutes since 1996.",
                 length_info
             )
+        } else if cursor_y >= FIXED_LINES {
+            format!(
+                "{}\nHint: Custom field, stored after the area's predefined fields.\nAdd ('a') or remove ('x') custom fields on the Settings page.",
+                length_info
+            )
         } else {
             format!(
                 "{}\nHint: Stored as plain text string.",
@@ -178,21 +283,17 @@ impl FRUEditor {
                 .join(" ")
         };
 
-        let mut bytes = value.as_bytes().to_vec();
-        if line.reserved_bytes > 0 && bytes.len() < line.reserved_bytes {
-            bytes.resize(line.reserved_bytes, b' ');
-        }
-        let len = bytes.len().min(0x3F);
-        let mut encoded = vec![0xC0 | len as u8];
-        encoded.extend_from_slice(&bytes[..len]);
-
-        let fru_hex = encoded
-            .iter()
-            .map(|byte| format!("{:02X}", byte))
-            .collect::<Vec<_>>()
-            .join(" ");
+        let fru_hex = match encode_field(value, &line.field_config()) {
+            Ok(encoded) => encoded
+                .unwrap_or_default()
+                .iter()
+                .map(|byte| format!("{:02X}", byte))
+                .collect::<Vec<_>>()
+                .join(" "),
+            Err(e) => format!("<{}>", e),
+        };
 
-        format!("Selected Field: {label}\nText bytes  : {text_hex}\nFRU bytes   : {fru_hex}")
+        format!("Selected Field: {label}\nText bytes  : {text_hex}\nEncoding    : {}\nFRU bytes   : {fru_hex}", line.encoding.name())
     }
 
     fn build_hex_view(lines: &[Line], cursor_y: usize) -> String {
@@ -206,13 +307,35 @@ impl FRUEditor {
             return format!("{field_preview}\n\nFull FRU preview unavailable: one or more fields exceed 0x3F bytes.");
         }
 
+        if let Some(line) = lines.iter().find(|line| line.reserve_exceeds()) {
+            return format!(
+                "{field_preview}\n\nFull FRU preview unavailable: {} reserves {} bytes, more than 0x3F.",
+                Self::sanitize_label(&line.immutable),
+                line.reserved_bytes
+            );
+        }
+
+        if let Some((line, e)) = lines
+            .iter()
+            .enumerate()
+            .filter(|(i, line)| line.enabled && !CODE_LINES.contains(i))
+            .find_map(|(_, line)| encode_value(&line.editable, line.encoding).err().map(|e| (line, e)))
+        {
+            return format!(
+                "{field_preview}\n\nFull FRU preview unavailable: {} {}",
+                Self::sanitize_label(&line.immutable),
+                e
+            );
+        }
+
         let chassis_type_code = parse_chassis_type(&lines[0].editable);
         let chassis = Chassis::new(
             chassis_type_code,
             lines[1].editable.clone(),
             lines[2].editable.clone(),
             lines[3].editable.clone(),
-        );
+        )
+        .with_custom_fields(custom_fields(lines, CUSTOM_KEYS[0]));
         let board = Board::new(
             lines[4].editable.clone(),
             lines[5].editable.clone(),
@@ -221,7 +344,8 @@ impl FRUEditor {
             lines[8].editable.clone(),
             lines[9].editable.clone(),
             lines[10].editable.clone(),
-        );
+        )
+        .with_custom_fields(custom_fields(lines, CUSTOM_KEYS[1]));
         let product = Product::new(
             lines[11].editable.clone(),
             lines[12].editable.clone(),
@@ -231,18 +355,21 @@ impl FRUEditor {
             lines[16].editable.clone(),
             lines[17].editable.clone(),
             lines[18].editable.clone(),
-        );
+        )
+        .with_custom_fields(custom_fields(lines, CUSTOM_KEYS[2]));
 
         let get_configs = |range: std::ops::Range<usize>| -> Vec<super::area::FieldConfig> {
-            lines[range].iter().map(|l| super::area::FieldConfig {
-                enabled: l.enabled,
-                reserved_bytes: l.reserved_bytes,
-            }).collect()
+            lines[range].iter().map(|l| l.field_config()).collect()
         };
 
-        let chassis_bytes = chassis.transfer_with_config(&get_configs(0..4));
-        let board_bytes = board.transfer_with_config(&get_configs(4..11));
-        let product_bytes = product.transfer_with_config(&get_configs(11..19));
+        let areas = chassis
+            .transfer_with_config(&get_configs(0..4))
+            .and_then(|c| Ok((c, board.transfer_with_config(&get_configs(4..11))?)))
+            .and_then(|(c, b)| Ok((c, b, product.transfer_with_config(&get_configs(11..19))?)));
+        let (chassis_bytes, board_bytes, product_bytes) = match areas {
+            Ok(areas) => areas,
+            Err(e) => return format!("{field_preview}\n\nFull FRU preview unavailable: {}", e),
+        };
 
         let mut fru_data = vec![0x01, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00];
         let mut current_offset = 1u8;
@@ -264,7 +391,7 @@ impl FRUEditor {
             fru_data.extend(&product_bytes);
         }
 
-        fru_data[7] = ((0x100u16 - (fru_data.iter().take(7).map(|&b| b as u16).sum::<u16>() % 256)) % 256) as u8;
+        fru_data[7] = zero_checksum(&fru_data[..7]);
 
         while fru_data.len() < 256 {
             fru_data.push(0x00);
@@ -284,7 +411,7 @@ impl FRUEditor {
                     hex_part.push(' ');
                 }
                 
-                if byte >= 32 && byte <= 126 {
+                if (32..=126).contains(&byte) {
                     ascii_part.push(byte as char);
                 } else {
                     ascii_part.push('.');
@@ -297,10 +424,11 @@ impl FRUEditor {
         format!("{field_preview}\n\nFull FRU Data Preview (256 bytes):\n{hex_dump}")
     }
 
+    #[allow(clippy::collapsible_match)] // One arm per key, its condition inside
     fn handle_key_event(
         &self,
         event: event::KeyEvent,
-        lines: &mut [Line],
+        lines: &mut Vec<Line>,
         cursor_x: &mut usize,
         cursor_y: &mut usize,
         hint_scroll: &mut u16,
@@ -328,14 +456,50 @@ impl FRUEditor {
                 KeyCode::Char('e') | KeyCode::Char('E') => {
                     lines[*cursor_y].enabled = !lines[*cursor_y].enabled;
                 }
+                KeyCode::Char('a') | KeyCode::Char('A') => {
+                    // New custom field after the last one of the selected line's area
+                    let key = area_custom_key(lines, *cursor_y);
+                    let area = CUSTOM_KEYS.iter().position(|k| *k == key).unwrap_or(0);
+                    let position = FIXED_LINES
+                        + lines[FIXED_LINES..]
+                            .iter()
+                            .filter(|line| {
+                                let index = CUSTOM_KEYS.iter().position(|k| Some(*k) == custom_key(line));
+                                index.is_some_and(|index| index <= area)
+                            })
+                            .count();
+                    lines.insert(position, custom_line(key, String::new(), 32, Encoding::default(), Padding::default()));
+                    renumber_custom_lines(lines);
+                    *cursor_y = position;
+                    *hint_scroll = 0;
+                }
+                KeyCode::Char('x') | KeyCode::Char('X') | KeyCode::Delete => {
+                    if *cursor_y >= FIXED_LINES {
+                        lines.remove(*cursor_y);
+                        renumber_custom_lines(lines);
+                        *cursor_y = (*cursor_y).min(lines.len() - 1);
+                        *hint_scroll = 0;
+                    }
+                }
+                KeyCode::Char('t') | KeyCode::Char('T') => {
+                    if !CODE_LINES.contains(cursor_y) {
+                        lines[*cursor_y].encoding = lines[*cursor_y].encoding.next();
+                    }
+                }
+                KeyCode::Char('p') | KeyCode::Char('P') => {
+                    if !CODE_LINES.contains(cursor_y) {
+                        lines[*cursor_y].padding = lines[*cursor_y].padding.next();
+                    }
+                }
                 KeyCode::Char('+') | KeyCode::Char('=') => {
-                    if lines[*cursor_y].reserved_bytes < 0x3F {
+                    if lines[*cursor_y].reserved_bytes < MAX_FIELD_LENGTH {
                         lines[*cursor_y].reserved_bytes += 1;
                     }
                 }
                 KeyCode::Char('-') | KeyCode::Char('_') => {
                     if lines[*cursor_y].reserved_bytes > 0 {
-                        lines[*cursor_y].reserved_bytes -= 1;
+                        // A reservation loaded beyond the limit drops straight to it
+                        lines[*cursor_y].reserved_bytes = (lines[*cursor_y].reserved_bytes - 1).min(MAX_FIELD_LENGTH);
                     }
                 }
                 KeyCode::Up => {
@@ -437,13 +601,35 @@ pub trait UI {
 impl UI for FRUEditor {
     fn save_to_file(&self, lines: &[Line], filename: &str) -> io::Result<()> {
         let mut file = File::create(filename)?;
-        for line in lines {
+        for line in &lines[..FIXED_LINES] {
             let key = line.immutable.strip_suffix(": ").unwrap_or(&line.immutable);
             writeln!(file, "{}: \"{}\"", key, line.editable)?;
         }
+        // Custom fields carry their settings; disabled ones are left out
+        for key in CUSTOM_KEYS {
+            let items: Vec<&Line> = lines[FIXED_LINES..]
+                .iter()
+                .filter(|line| line.enabled && custom_key(line) == Some(key))
+                .collect();
+            if items.is_empty() {
+                continue;
+            }
+            writeln!(file, "{}:", key)?;
+            for line in items {
+                writeln!(
+                    file,
+                    "  - {{ value: \"{}\", reserve_bytes: {}, encoding: \"{}\", pad: \"{}\" }}",
+                    line.editable,
+                    line.reserved_bytes,
+                    line.encoding.name(),
+                    line.padding.name()
+                )?;
+            }
+        }
         Ok(())
     }
 
+    #[allow(clippy::collapsible_match, clippy::implicit_saturating_sub)]
     fn run(&self, filename: &str, initial_data: Option<HashMap<String, ConfigField>>) -> Result<Option<Vec<Line>>, io::Error> {
         enable_raw_mode()?;
         let mut stdout: io::Stdout = io::stdout();
@@ -481,15 +667,17 @@ impl UI for FRUEditor {
                 let is_code = key.contains("type") || key.contains("mfg");
                 let default_reserve = if is_code { 0 } else { 32 };
 
-                let (editable, enabled, reserved_bytes) = if let Some(ref data) = initial_data {
+                let (editable, enabled, reserved_bytes, encoding) = if let Some(ref data) = initial_data {
                     if let Some(field) = data.get(&key) {
-                        (field.value(), true, field.reserve_bytes().unwrap_or(default_reserve))
+                        let encoding = field.encoding().and_then(|e| Encoding::parse(&e)).unwrap_or_default();
+                        (field.value(), true, field.reserve_bytes().unwrap_or(default_reserve), encoding)
                     } else {
-                        (String::new(), false, default_reserve)
+                        (String::new(), false, default_reserve, Encoding::default())
                     }
                 } else {
-                    (String::new(), true, default_reserve)
+                    (String::new(), true, default_reserve, Encoding::default())
                 };
+                let padding = initial_data.as_ref().map(|data| padding_of(data, &key)).unwrap_or_default();
                 
                 Line {
                     immutable: immutable_data.to_string(),
@@ -497,10 +685,25 @@ impl UI for FRUEditor {
                     selected: false,
                     enabled,
                     reserved_bytes,
+                    encoding,
+                    padding,
                 }
             })
             .collect();
 
+        if let Some(ref data) = initial_data {
+            for key in CUSTOM_KEYS {
+                let items = data.get(&key.to_lowercase()).map(|f| f.items()).unwrap_or_default();
+                let area_padding = padding_of(data, &key.to_lowercase());
+                for item in items {
+                    let encoding = item.encoding().and_then(|e| Encoding::parse(&e)).unwrap_or_default();
+                    let padding = item.pad().and_then(|p| Padding::parse(&p)).unwrap_or(area_padding);
+                    lines.push(custom_line(key, item.value(), item.reserve_bytes().unwrap_or(32), encoding, padding));
+                }
+            }
+            renumber_custom_lines(&mut lines);
+        }
+
         let mut cursor_x: usize = lines[0].immutable.len() + lines[0].editable.len();
         let mut cursor_y: usize = 0;
         let mut cursor_visible: bool = true;
@@ -515,10 +718,12 @@ impl UI for FRUEditor {
             } else {
                 let line = &lines[cursor_y];
                 format!(
-                    "Field: {}\n\nEnabled: {}\nReserved Bytes: {}\n\nInstructions:\n'e'     : Toggle Enable/Disable\n'+' / '-': Inc/Dec Reserved Bytes",
+                    "Field: {}\n\nEnabled: {}\nReserved Bytes: {}\nEncoding: {}\nPadding: {}\n\nInstructions:\n'e'     : Toggle Enable/Disable\n'+' / '-': Inc/Dec Reserved Bytes\n't'     : Cycle Encoding (8bit/auto/6bit/bcd/binary)\n'p'     : Cycle Padding (space/nul/ff/after_end)\n'a'     : Add a custom field to this area\n'x'     : Remove this custom field",
                     line.immutable.trim(),
                     if line.enabled { "YES" } else { "NO" },
-                    line.reserved_bytes
+                    line.reserve_info(),
+                    line.encoding.name(),
+                    line.padding.name()
                 )
             };
 
@@ -593,7 +798,7 @@ impl UI for FRUEditor {
                     .split(middle_chunks[0]);
 
                 for (line_index, line) in lines.iter().enumerate() {
-                    let mut style = if line.selected == true {
+                    let mut style = if line.selected {
                         Style::default().fg(Color::Yellow).add_modifier(Modifier::BOLD)
                     } else {
                         Style::default().fg(Color::Reset)
@@ -602,14 +807,19 @@ impl UI for FRUEditor {
                     if !line.enabled {
                         style = style.add_modifier(Modifier::DIM);
                     }
+                    if current_page == Page::Settings && line.reserve_exceeds() {
+                        style = style.fg(Color::Red);
+                    }
 
                     let content = if current_page == Page::Editor {
                         format!("{}{}", line.immutable, line.editable)
                     } else {
-                        format!("{} [Enabled: {:<3}] [Reserve: {:>2}]", 
+                        format!("{} [Enabled: {:<3}] [Reserve: {:>2}] [{}] [Pad: {}]", 
                             line.immutable, 
                             if line.enabled { "YES" } else { "NO" },
-                            line.reserved_bytes
+                            line.reserved_bytes,
+                            line.encoding.name(),
+                            line.padding.name()
                         )
                     };
 
@@ -634,7 +844,7 @@ impl UI for FRUEditor {
                     .split(chunk[2]);
 
                 let origin_title = &lines[cursor_y].immutable;
-                let new_title = origin_title.strip_suffix(": ").unwrap_or(&origin_title);
+                let new_title = origin_title.strip_suffix(": ").unwrap_or(origin_title);
                 let hint_title = format!("{} (PgUp/PgDn)", new_title);
 
                 let hint_paragraph = Paragraph::new(output_content.clone())
@@ -693,6 +903,8 @@ PgUp/Dn: Scroll hint";
                                 selected: false,
                                 enabled: l.enabled,
                                 reserved_bytes: l.reserved_bytes,
+                                encoding: l.encoding,
+                                padding: l.padding,
                             }).collect());
                         }
                         EventOutcome::Exit => break,
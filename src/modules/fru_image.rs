@@ -0,0 +1,536 @@
+use super::area::{zero_checksum, Area, CustomField, FieldConfig};
+use super::board_area::Board;
+use super::chassis_area::Chassis;
+use super::encoding::{Encoding, Padding, MAX_FIELD_LENGTH};
+use super::internal_area::Internal;
+use super::multirecord_area::{MultiRecord, Record};
+use super::product_area::Product;
+use super::sequence::{preview_patterns, SequenceState};
+use crate::{parse_language_code, try_parse_chassis_type, ConfigField, FruError, InternalUseConfig, LayoutConfig};
+use std::collections::HashMap;
+use std::fmt;
+use std::path::Path;
+
+/// Default output size in bytes, matching the CLI's `--size` default.
+pub const DEFAULT_FRU_SIZE: usize = 4096;
+
+/// Capacity in bytes of common I2C EEPROM parts.
+pub const EEPROM_PRESETS: &[(&str, usize)] = &[
+    ("24C02", 256),
+    ("24C04", 512),
+    ("24C08", 1024),
+    ("24C16", 2048),
+    ("24C32", 4096),
+    ("24C64", 8192),
+    ("24C128", 16384),
+    ("24C256", 32768),
+];
+
+/// Images with less free space than this (in percent of the image size) are reported.
+pub const LOW_HEADROOM_PERCENT: usize = 10;
+
+/// Size of a built image: a fixed byte count, or just what the areas need.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FruSize {
+    Bytes(usize),
+    /// The smallest 8-byte aligned image holding the header and areas, without padding.
+    Auto,
+}
+
+impl FruSize {
+    /// Parse a byte count, an EEPROM part from [`EEPROM_PRESETS`] such as `24C02`, or `auto`.
+    pub fn parse(input: &str) -> Result<FruSize, String> {
+        let trimmed = input.trim();
+        if trimmed.eq_ignore_ascii_case("auto") {
+            return Ok(FruSize::Auto);
+        }
+        if let Some((_, bytes)) = EEPROM_PRESETS.iter().find(|(name, _)| name.eq_ignore_ascii_case(trimmed)) {
+            return Ok(FruSize::Bytes(*bytes));
+        }
+        trimmed.parse().map(FruSize::Bytes).map_err(|_| {
+            let parts: Vec<&str> = EEPROM_PRESETS.iter().map(|(name, _)| *name).collect();
+            format!("invalid size '{}' (expected a byte count, 'auto' or one of: {})", input, parts.join(", "))
+        })
+    }
+
+    /// The smallest EEPROM part holding `bytes`.
+    pub fn smallest_part(bytes: usize) -> Option<&'static str> {
+        EEPROM_PRESETS.iter().find(|(_, b)| *b >= bytes).map(|(name, _)| *name)
+    }
+
+    /// The EEPROM part of exactly this size, if any.
+    pub fn part(&self) -> Option<&'static str> {
+        match self {
+            FruSize::Bytes(bytes) => EEPROM_PRESETS.iter().find(|(_, b)| b == bytes).map(|(name, _)| *name),
+            FruSize::Auto => None,
+        }
+    }
+}
+
+impl From<usize> for FruSize {
+    fn from(bytes: usize) -> Self {
+        FruSize::Bytes(bytes)
+    }
+}
+
+impl fmt::Display for FruSize {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match (self, self.part()) {
+            (FruSize::Bytes(bytes), Some(part)) => write!(f, "{} bytes ({})", bytes, part),
+            (FruSize::Bytes(bytes), None) => write!(f, "{} bytes", bytes),
+            (FruSize::Auto, _) => write!(f, "auto"),
+        }
+    }
+}
+
+/// Config keys of the fixed fields in image order: chassis 0..4, board 4..11, product 11..19.
+pub const FIELD_KEYS: &[&str] = &[
+    "chassis_type", "chassis_part_number", "chassis_serial_number", "chassis_extra",
+    "board_mfg_date_time", "board_manufacturer", "board_product_name", "board_serial_number",
+    "board_part_number", "board_fruid", "board_extra",
+    "product_manufacturer", "product_name", "product_part_number", "product_version",
+    "product_serial_number", "product_asset_tag", "product_fruid", "product_extra",
+];
+
+/// Config keys of the custom field lists of the chassis, board and product areas.
+pub const CUSTOM_KEYS: &[&str] = &["chassis_custom", "board_custom", "product_custom"];
+
+/// Config keys of the default padding of the chassis, board and product areas' fields.
+pub const PAD_KEYS: &[&str] = &["chassis_pad", "board_pad", "product_pad"];
+
+const CHASSIS_CONFIGS: std::ops::Range<usize> = 0..4;
+const BOARD_CONFIGS: std::ops::Range<usize> = 4..11;
+const PRODUCT_CONFIGS: std::ops::Range<usize> = 11..19;
+
+/// Fields holding codes (chassis type, mfg date) rather than type/length encoded strings.
+fn is_code_field(key: &str) -> bool {
+    key.contains("type") || key.contains("mfg")
+}
+
+/// Parse the padding named for `key`.
+fn parse_padding(key: &str, name: String) -> Result<Padding, FruError> {
+    Padding::parse(&name).ok_or_else(|| FruError::UnknownPadding { field: key.to_string(), name })
+}
+
+/// Settings of an enabled field from its config entry; `padding` applies unless the entry
+/// sets its own.
+fn field_config(key: &str, field: &ConfigField, default_reserve: usize, padding: Padding) -> Result<FieldConfig, FruError> {
+    let encoding = match field.encoding() {
+        Some(name) => Encoding::parse(&name).ok_or_else(|| FruError::UnknownEncoding {
+            field: key.to_string(),
+            name,
+        })?,
+        None => Encoding::default(),
+    };
+    let reserved_bytes = field.reserve_bytes().unwrap_or(default_reserve);
+    if reserved_bytes > MAX_FIELD_LENGTH {
+        return Err(FruError::ReserveTooLarge { field: key.to_string(), reserved: reserved_bytes, max: MAX_FIELD_LENGTH });
+    }
+    Ok(FieldConfig {
+        enabled: true,
+        reserved_bytes,
+        encoding,
+        padding: field.pad().map(|name| parse_padding(key, name)).transpose()?.unwrap_or(padding),
+        ..Default::default()
+    })
+}
+
+/// Placement of one area inside a built image.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ImageArea {
+    pub name: String,
+    /// Absolute offset of the area in bytes.
+    pub offset: usize,
+    pub length: usize,
+}
+
+/// A generated FRU image.
+#[derive(Debug, Clone)]
+pub struct FruImage {
+    /// The image, padded to the requested size with the layout's pad byte (0x00 by default).
+    pub data: Vec<u8>,
+    /// Bytes used by the header and areas, including space a layout keeps for them.
+    pub used: usize,
+    pub areas: Vec<ImageArea>,
+}
+
+impl FruImage {
+    pub fn as_bytes(&self) -> &[u8] {
+        &self.data
+    }
+
+    pub fn into_bytes(self) -> Vec<u8> {
+        self.data
+    }
+
+    pub fn area(&self, name: &str) -> Option<&ImageArea> {
+        self.areas.iter().find(|a| a.name == name)
+    }
+
+    /// Raw bytes of the named area.
+    pub fn area_data(&self, name: &str) -> Option<&[u8]> {
+        self.area(name).map(|a| &self.data[a.offset..a.offset + a.length])
+    }
+
+    /// Bytes of the image left free for later growth.
+    pub fn headroom(&self) -> usize {
+        self.data.len() - self.used
+    }
+
+    /// A warning when less than [`LOW_HEADROOM_PERCENT`] of the image is free.
+    pub fn headroom_warning(&self) -> Option<String> {
+        let size = self.data.len();
+        (self.headroom() * 100 < size * LOW_HEADROOM_PERCENT).then(|| format!(
+            "Only {} of {} bytes ({:.1}%) are left free for field growth",
+            self.headroom(),
+            size,
+            self.headroom() as f64 * 100.0 / size.max(1) as f64
+        ))
+    }
+}
+
+///
+/// Builds a FRU image from typed areas.
+///
+/// Areas left unset are omitted and get a zero offset in the common header. Without
+/// explicit field settings each area uses its `transfer_as_byte` defaults, and without a
+/// layout the areas are packed back-to-back after the header.
+///
+/// ```
+/// use fru_gen::modules::chassis_area::Chassis;
+/// use fru_gen::modules::fru_image::FruBuilder;
+///
+/// let chassis = Chassis::new(0x17, "CPN".to_string(), "CSN".to_string(), "".to_string());
+/// let image = FruBuilder::new().chassis(chassis).size(256).build().unwrap();
+/// assert_eq!(image.data.len(), 256);
+/// assert_eq!(image.data[2], 1); // chassis area right after the header
+/// ```
+#[derive(Debug)]
+pub struct FruBuilder {
+    internal: Option<Internal>,
+    chassis: Option<Chassis>,
+    board: Option<Board>,
+    product: Option<Product>,
+    multirecord: Option<MultiRecord>,
+    field_configs: Option<Vec<FieldConfig>>,
+    layout: Option<LayoutConfig>,
+    size: FruSize,
+}
+
+impl Default for FruBuilder {
+    fn default() -> Self {
+        FruBuilder {
+            internal: None,
+            chassis: None,
+            board: None,
+            product: None,
+            multirecord: None,
+            field_configs: None,
+            layout: None,
+            size: FruSize::Bytes(DEFAULT_FRU_SIZE),
+        }
+    }
+}
+
+impl FruBuilder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn internal(mut self, internal: Internal) -> Self {
+        self.internal = Some(internal);
+        self
+    }
+
+    pub fn chassis(mut self, chassis: Chassis) -> Self {
+        self.chassis = Some(chassis);
+        self
+    }
+
+    pub fn board(mut self, board: Board) -> Self {
+        self.board = Some(board);
+        self
+    }
+
+    pub fn product(mut self, product: Product) -> Self {
+        self.product = Some(product);
+        self
+    }
+
+    pub fn multirecord(mut self, multirecord: MultiRecord) -> Self {
+        self.multirecord = Some(multirecord);
+        self
+    }
+
+    /// Per-field settings for all fixed fields, in `FIELD_KEYS` order.
+    ///
+    /// `build` fails with `FruError::FieldConfigCount` unless there is one per key.
+    pub fn field_configs(mut self, field_configs: Vec<FieldConfig>) -> Self {
+        self.field_configs = Some(field_configs);
+        self
+    }
+
+    /// Pin areas to the offsets of a `[layout]` table, if any.
+    pub fn layout(mut self, layout: Option<&LayoutConfig>) -> Self {
+        self.layout = layout.cloned();
+        self
+    }
+
+    /// Total size of the output image: bytes, or [`FruSize::Auto`].
+    pub fn size(mut self, size: impl Into<FruSize>) -> Self {
+        self.size = size.into();
+        self
+    }
+
+    ///
+    /// Create a builder from a loaded config map and its multi-records.
+    ///
+    /// Fields missing from the map are disabled; the empty internal use area the CLI has
+    /// always written is included.
+    pub fn from_config(config_map: &HashMap<String, ConfigField>, records: Vec<Record>) -> Result<Self, FruError> {
+        let value = |key: &str| config_map.get(key).map(|f| f.value()).unwrap_or_default();
+        // Area-wide padding from `Board_Pad` etc., for fields that do not set their own
+        let area_padding = |key: &str| -> Result<Padding, FruError> {
+            let area = key.split('_').next().unwrap_or_default();
+            match config_map.get(&format!("{}_pad", area)) {
+                Some(field) => parse_padding(&format!("{}_pad", area), field.value()),
+                None => Ok(Padding::default()),
+            }
+        };
+        let custom = |key: &str| -> Result<Vec<CustomField>, FruError> {
+            let items = config_map.get(key).map(|f| f.items()).unwrap_or_default();
+            let padding = area_padding(key)?;
+            items
+                .iter()
+                .enumerate()
+                .map(|(i, item)| {
+                    let config = field_config(&format!("{}[{}]", key, i), item, 32, padding)?;
+                    Ok(CustomField { value: item.value(), config })
+                })
+                .collect()
+        };
+
+        let chassis_type = config_map.get("chassis_type").map(|f| f.value()).unwrap_or_else(|| "0x02".to_string());
+        let chassis = Chassis::new(
+            try_parse_chassis_type(&chassis_type)?,
+            value("chassis_part_number"),
+            value("chassis_serial_number"),
+            value("chassis_extra"),
+        )
+        .with_custom_fields(custom("chassis_custom")?);
+
+        let language = |key: &str| -> Result<u8, FruError> {
+            match config_map.get(key).map(|f| f.value()) {
+                Some(value) => parse_language_code(&value).ok_or_else(|| FruError::InvalidLanguage {
+                    field: key.to_string(),
+                    value,
+                }),
+                None => Ok(0),
+            }
+        };
+
+        let board = Board::new(
+            config_map.get("board_mfg_date_time").map(|f| f.value()).unwrap_or_else(|| "0".to_string()),
+            value("board_manufacturer"),
+            value("board_product_name"),
+            value("board_serial_number"),
+            value("board_part_number"),
+            value("board_fruid"),
+            value("board_extra"),
+        )
+        .with_language_code(language("board_language")?)
+        .with_custom_fields(custom("board_custom")?);
+
+        let product = Product::new(
+            value("product_manufacturer"),
+            value("product_name"),
+            value("product_part_number"),
+            value("product_version"),
+            value("product_serial_number"),
+            value("product_asset_tag"),
+            value("product_fruid"),
+            value("product_extra"),
+        )
+        .with_language_code(language("product_language")?)
+        .with_custom_fields(custom("product_custom")?);
+
+        let mut field_configs = Vec::with_capacity(FIELD_KEYS.len());
+        for key in FIELD_KEYS {
+            let default_reserve = if is_code_field(key) { 0 } else { 32 };
+            let config = match config_map.get(*key) {
+                Some(field) => field_config(key, field, default_reserve, area_padding(key)?)?,
+                None => FieldConfig {
+                    enabled: false,
+                    reserved_bytes: default_reserve,
+                    ..Default::default()
+                },
+            };
+            field_configs.push(config);
+        }
+
+        Ok(FruBuilder::new()
+            .internal(Internal::new(String::new()))
+            .chassis(chassis)
+            .board(board)
+            .product(product)
+            .multirecord(MultiRecord::new(records))
+            .field_configs(field_configs))
+    }
+
+    /// Use the internal use area an `[internal_use]` table describes, if any.
+    pub fn internal_use(self, config: Option<&InternalUseConfig>) -> Result<Self, FruError> {
+        match config {
+            Some(config) => Ok(self.internal(Internal::from_config(config)?)),
+            None => Ok(self),
+        }
+    }
+
+    /// Load a TOML/YAML/JSON config file (including its `[[multirecord]]`, `[internal_use]`
+    /// and `[layout]` entries) into a builder.
+    pub fn from_config_file(config_path: &str) -> anyhow::Result<Self> {
+        Self::from_config_map(config_path, &crate::load_config(config_path)?)
+    }
+
+    /// Like [`FruBuilder::from_config_file`], with serial patterns expanded to the values the
+    /// next run would use. `state_file` is only read.
+    pub fn preview_config_file(config_path: &str, state_file: &Path) -> anyhow::Result<Self> {
+        let mut config_map = crate::load_config(config_path)?;
+        preview_patterns(&mut config_map, SequenceState::load(state_file)?).map_err(anyhow::Error::msg)?;
+        Self::from_config_map(config_path, &config_map)
+    }
+
+    fn from_config_map(config_path: &str, config_map: &HashMap<String, ConfigField>) -> anyhow::Result<Self> {
+        let records = crate::load_multirecords(config_path)?;
+        let internal_use = crate::load_internal_use(config_path)?;
+        let layout = crate::load_layout(config_path)?;
+        Ok(Self::from_config(config_map, records)?
+            .internal_use(internal_use.as_ref())?
+            .layout(layout.as_ref()))
+    }
+
+    fn transfer(&self, area: &dyn Area, range: std::ops::Range<usize>) -> Result<Vec<u8>, FruError> {
+        match &self.field_configs {
+            Some(configs) => area.transfer_with_config(&configs[range]),
+            None => area.transfer_as_byte(),
+        }
+    }
+
+    ///
+    /// Encode all areas, lay them out after the common header and pad to the requested size.
+    ///
+    /// Areas pinned by the layout go to their offsets first. The others take the first gap
+    /// they fit in, in header order, except the multi-record area, which stays last.
+    pub fn build(&self) -> Result<FruImage, FruError> {
+        if let Some(configs) = &self.field_configs {
+            if configs.len() != FIELD_KEYS.len() {
+                return Err(FruError::FieldConfigCount { expected: FIELD_KEYS.len(), actual: configs.len() });
+            }
+        }
+        let mut area_data: Vec<(usize, &str, Vec<u8>)> = Vec::new();
+        if let Some(internal) = &self.internal {
+            area_data.push((1, "Internal Use Area", internal.transfer_as_byte()?));
+        }
+        if let Some(chassis) = &self.chassis {
+            area_data.push((2, "Chassis Info Area", self.transfer(chassis, CHASSIS_CONFIGS)?));
+        }
+        if let Some(board) = &self.board {
+            area_data.push((3, "Board Info Area", self.transfer(board, BOARD_CONFIGS)?));
+        }
+        if let Some(product) = &self.product {
+            area_data.push((4, "Product Info Area", self.transfer(product, PRODUCT_CONFIGS)?));
+        }
+        // MultiRecord Area ( always the last area )
+        if let Some(multirecord) = &self.multirecord {
+            area_data.push((5, "MultiRecord Area", multirecord.transfer_as_byte()?));
+        }
+        area_data.retain(|(_, _, data)| !data.is_empty());
+
+        let slots = self.layout.as_ref().map(|l| l.slots()).unwrap_or_default();
+        let pad_byte = self.layout.as_ref().and_then(|l| l.pad_byte).unwrap_or(0x00);
+
+        // Space taken so far as (start, end, name), kept reservations included
+        let mut taken: Vec<(usize, usize, &str)> = vec![(0, 8, "Common Header")];
+        let mut placement = vec![0; area_data.len()];
+        for (i, (slot, name, data)) in area_data.iter().enumerate() {
+            let Some(pin) = slots[slot - 1] else { continue };
+            if pin.offset == 0 {
+                return Err(FruError::InvalidLayout(format!("{} cannot be placed at offset 0, the common header", name)));
+            }
+            // Both are in 8-byte units the common header and area length bytes hold as one byte
+            if pin.offset > 0xFF {
+                return Err(FruError::InvalidLayout(format!(
+                    "{} offset {} is beyond the common header's limit of 255 (0x7F8)",
+                    name, pin.offset
+                )));
+            }
+            if let Some(units) = pin.max_size.filter(|&units| units > 0xFF) {
+                return Err(FruError::InvalidLayout(format!(
+                    "{} max_size {} is beyond an area's limit of 255 (0x7F8 bytes)",
+                    name, units
+                )));
+            }
+            let (offset, max_size) = (pin.offset * 8, pin.max_size.map(|units| units * 8));
+            if let Some(max_size) = max_size.filter(|&max| data.len() > max) {
+                return Err(FruError::AreaTooLarge { area: name.to_string(), len: data.len(), max: max_size });
+            }
+            let end = offset + data.len().max(max_size.unwrap_or(0));
+            if let Some((start, other_end, other)) = taken.iter().find(|(start, e, _)| offset < *e && *start < end) {
+                return Err(FruError::InvalidLayout(format!(
+                    "{} at 0x{:X}-0x{:X} overlaps {} at 0x{:X}-0x{:X}",
+                    name, offset, end, other, start, other_end
+                )));
+            }
+            taken.push((offset, end, name));
+            placement[i] = offset;
+        }
+        for (i, (slot, name, data)) in area_data.iter().enumerate() {
+            if slots[slot - 1].is_some() {
+                continue;
+            }
+            taken.sort();
+            // Areas start on 8-byte boundaries, the unit of the common header's offsets
+            let offset = if *slot == 5 {
+                taken.iter().map(|(_, end, _)| end.next_multiple_of(8)).max().unwrap_or(8)
+            } else {
+                // First gap after the header that holds the area
+                taken
+                    .iter()
+                    .map(|(_, end, _)| end.next_multiple_of(8))
+                    .find(|&start| !taken.iter().any(|(s, e, _)| start < *e && *s < start + data.len()))
+                    .unwrap_or(8)
+            };
+            taken.push((offset, offset + data.len(), name));
+            placement[i] = offset;
+        }
+
+        // Common Header: format version, five area offsets, pad, checksum
+        let used = taken.iter().map(|(_, end, _)| *end).max().unwrap_or(8);
+        let mut fru_data = vec![pad_byte; used];
+        fru_data[..8].copy_from_slice(&[0x01, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00]);
+        let mut areas = Vec::new();
+        for ((slot, name, data), offset) in area_data.into_iter().zip(placement) {
+            if !offset.is_multiple_of(8) {
+                return Err(FruError::InvalidLayout(format!("{} at 0x{:X} is not on an 8-byte boundary", name, offset)));
+            }
+            if offset / 8 > u8::MAX as usize {
+                return Err(FruError::OffsetOverflow { area: name.to_string(), offset });
+            }
+            fru_data[slot] = (offset / 8) as u8;
+            areas.push(ImageArea { name: name.to_string(), offset, length: data.len() });
+            fru_data[offset..offset + data.len()].copy_from_slice(&data);
+        }
+        areas.sort_by_key(|a| a.offset);
+
+        // Calculate common Header checksum
+        fru_data[7] = zero_checksum(&fru_data[..7]);
+
+        let size = match self.size {
+            FruSize::Bytes(size) if used > size => return Err(FruError::ImageTooLarge { limit: size, actual: used }),
+            FruSize::Bytes(size) => size,
+            FruSize::Auto => used.div_ceil(8) * 8,
+        };
+        fru_data.resize(size, pad_byte);
+
+        Ok(FruImage { data: fru_data, used, areas })
+    }
+}
@@ -0,0 +1,343 @@
+use crate::FruError;
+use std::collections::BTreeMap;
+use std::fmt::Write;
+use std::path::Path;
+
+/// Data bytes per Intel HEX / S-record line.
+const RECORD_LENGTH: usize = 16;
+
+/// Largest address span a HEX or S-record file may cover; FRU images are a few KiB.
+const MAX_IMAGE_SPAN: u64 = 64 * 1024;
+
+/// Bytes per line of a C array.
+const C_ARRAY_COLUMNS: usize = 12;
+
+/// File format a FRU image is written in.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ImageFormat {
+    /// Raw bytes, as written to the EEPROM.
+    Binary,
+    /// Intel HEX with extended linear address records.
+    IntelHex,
+    /// Motorola S-record (S1/S2/S3, chosen by the highest address).
+    Srec,
+    /// `static const uint8_t fru[]` C header with an include guard.
+    CArray,
+    /// Standard base64 on a single line.
+    Base64,
+}
+
+impl ImageFormat {
+    pub const NAMES: &'static [&'static str] = &["binary", "ihex", "srec", "c", "base64"];
+
+    /// Parse a `--format` value.
+    pub fn parse(name: &str) -> Option<ImageFormat> {
+        match name.trim().to_lowercase().as_str() {
+            "binary" | "bin" | "raw" => Some(ImageFormat::Binary),
+            "ihex" | "hex" | "intel-hex" | "intel_hex" => Some(ImageFormat::IntelHex),
+            "srec" | "s19" | "s28" | "s37" | "mot" => Some(ImageFormat::Srec),
+            "c" | "c-array" | "c_array" | "h" => Some(ImageFormat::CArray),
+            "base64" | "b64" => Some(ImageFormat::Base64),
+            _ => None,
+        }
+    }
+
+    /// Intel HEX for `.hex`/`.ihex`, S-record for `.srec`/`.s19`/`.s28`/`.s37`/`.mot`,
+    /// C for `.c`/`.h`, base64 for `.b64`/`.base64`, raw binary otherwise.
+    pub fn from_path(path: &Path) -> ImageFormat {
+        match path.extension().and_then(|s| s.to_str()).map(|s| s.to_lowercase()).as_deref() {
+            Some("hex") | Some("ihex") => ImageFormat::IntelHex,
+            Some("srec") | Some("s19") | Some("s28") | Some("s37") | Some("mot") => ImageFormat::Srec,
+            Some("c") | Some("h") => ImageFormat::CArray,
+            Some("b64") | Some("base64") => ImageFormat::Base64,
+            _ => ImageFormat::Binary,
+        }
+    }
+
+    /// File extension written for this format.
+    pub fn extension(self) -> &'static str {
+        match self {
+            ImageFormat::Binary => "bin",
+            ImageFormat::IntelHex => "hex",
+            ImageFormat::Srec => "srec",
+            ImageFormat::CArray => "h",
+            ImageFormat::Base64 => "b64",
+        }
+    }
+}
+
+///
+/// Encode a FRU image for writing in `format`.
+///
+/// `base_address` is where the image starts in the programmer's address space; it only
+/// affects Intel HEX and S-record output.
+///
+/// # Example
+/// ```
+/// use fru_gen::modules::image_format::{encode_image, ImageFormat};
+///
+/// let hex = encode_image(&[0x01, 0x00], ImageFormat::IntelHex, 0).unwrap();
+/// assert_eq!(String::from_utf8(hex).unwrap(), ":020000000100FD\n:00000001FF\n");
+/// ```
+pub fn encode_image(data: &[u8], format: ImageFormat, base_address: u32) -> Result<Vec<u8>, FruError> {
+    if matches!(format, ImageFormat::IntelHex | ImageFormat::Srec)
+        && base_address as u64 + data.len() as u64 > u32::MAX as u64 + 1
+    {
+        return Err(FruError::InvalidImage(format!(
+            "{} bytes at base address 0x{:08X} exceed the 32-bit address space",
+            data.len(),
+            base_address
+        )));
+    }
+    Ok(match format {
+        ImageFormat::Binary => data.to_vec(),
+        ImageFormat::IntelHex => write_intel_hex(data, base_address).into_bytes(),
+        ImageFormat::Srec => write_srec(data, base_address).into_bytes(),
+        ImageFormat::CArray => write_c_array(data).into_bytes(),
+        ImageFormat::Base64 => {
+            use base64::Engine;
+            let mut text = base64::engine::general_purpose::STANDARD.encode(data);
+            text.push('\n');
+            text.into_bytes()
+        }
+    })
+}
+
+/// An image read back from a file, with the format and base address it was stored at.
+#[derive(Debug, Clone, PartialEq)]
+pub struct LoadedImage {
+    pub format: ImageFormat,
+    pub base_address: u32,
+    pub data: Vec<u8>,
+}
+
+///
+/// Read a FRU image stored as raw binary, Intel HEX or S-record.
+///
+/// Text formats are recognised by their first record; anything else is taken as raw
+/// binary (a FRU image starts with format version 0x01, never with ':' or 'S').
+/// Gaps between records are filled with 0x00, and the image starts at the lowest address.
+pub fn read_image_data(bytes: &[u8]) -> Result<LoadedImage, FruError> {
+    let format = match bytes.iter().find(|b| !b.is_ascii_whitespace()) {
+        Some(b':') => ImageFormat::IntelHex,
+        Some(b'S') if bytes.iter().filter(|b| !b.is_ascii_whitespace()).nth(1).is_some_and(|b| b.is_ascii_digit()) => {
+            ImageFormat::Srec
+        }
+        _ => {
+            return Ok(LoadedImage { format: ImageFormat::Binary, base_address: 0, data: bytes.to_vec() });
+        }
+    };
+    let text = std::str::from_utf8(bytes)
+        .map_err(|_| FruError::InvalidImage("text image is not valid UTF-8".to_string()))?;
+    let chunks = match format {
+        ImageFormat::IntelHex => parse_intel_hex(text)?,
+        _ => parse_srec(text)?,
+    };
+    let (base_address, data) = assemble(chunks)?;
+    Ok(LoadedImage { format, base_address, data })
+}
+
+fn write_intel_hex(data: &[u8], base_address: u32) -> String {
+    let mut out = String::new();
+    let mut upper = 0u32;
+    for (index, chunk) in data.chunks(RECORD_LENGTH).enumerate() {
+        let address = base_address + (index * RECORD_LENGTH) as u32;
+        // A record may not cross a 64 KiB boundary, so split where it would
+        let split = (0x1_0000 - (address & 0xFFFF) as usize).min(chunk.len());
+        for (offset, part) in [(0, &chunk[..split]), (split, &chunk[split..])] {
+            if part.is_empty() {
+                continue;
+            }
+            let address = address + offset as u32;
+            if address >> 16 != upper {
+                upper = address >> 16;
+                intel_hex_record(&mut out, 0, 0x04, &(upper as u16).to_be_bytes());
+            }
+            intel_hex_record(&mut out, address as u16, 0x00, part);
+        }
+    }
+    intel_hex_record(&mut out, 0, 0x01, &[]);
+    out
+}
+
+fn intel_hex_record(out: &mut String, address: u16, kind: u8, data: &[u8]) {
+    let mut bytes = vec![data.len() as u8];
+    bytes.extend(address.to_be_bytes());
+    bytes.push(kind);
+    bytes.extend(data);
+    let checksum = bytes.iter().fold(0u8, |sum, b| sum.wrapping_add(*b)).wrapping_neg();
+    out.push(':');
+    for byte in bytes.iter().chain([&checksum]) {
+        let _ = write!(out, "{:02X}", byte);
+    }
+    out.push('\n');
+}
+
+fn write_srec(data: &[u8], base_address: u32) -> String {
+    let end = base_address as u64 + data.len().saturating_sub(1) as u64;
+    let (data_type, end_type, address_len) = match end {
+        0..=0xFFFF => (b'1', b'9', 2),
+        0x1_0000..=0xFF_FFFF => (b'2', b'8', 3),
+        _ => (b'3', b'7', 4),
+    };
+
+    let mut out = String::new();
+    srec_record(&mut out, b'0', 2, 0, b"FRU");
+    let mut count = 0usize;
+    for (index, chunk) in data.chunks(RECORD_LENGTH).enumerate() {
+        srec_record(&mut out, data_type, address_len, base_address + (index * RECORD_LENGTH) as u32, chunk);
+        count += 1;
+    }
+    if count <= 0xFFFF {
+        srec_record(&mut out, b'5', 2, count as u32, &[]);
+    } else if count <= 0xFF_FFFF {
+        srec_record(&mut out, b'6', 3, count as u32, &[]);
+    }
+    srec_record(&mut out, end_type, address_len, 0, &[]);
+    out
+}
+
+fn srec_record(out: &mut String, kind: u8, address_len: usize, address: u32, data: &[u8]) {
+    let mut bytes = vec![(address_len + data.len() + 1) as u8];
+    bytes.extend(&address.to_be_bytes()[4 - address_len..]);
+    bytes.extend(data);
+    let checksum = !bytes.iter().fold(0u8, |sum, b| sum.wrapping_add(*b));
+    out.push('S');
+    out.push(kind as char);
+    for byte in bytes.iter().chain([&checksum]) {
+        let _ = write!(out, "{:02X}", byte);
+    }
+    out.push('\n');
+}
+
+/// Include guard of the C header; `static` lets several sources include it without clashing.
+const C_ARRAY_GUARD: &str = "FRU_GEN_IMAGE_H";
+
+fn write_c_array(data: &[u8]) -> String {
+    let mut out = format!(
+        "/* FRU image, {} bytes */\n#ifndef {guard}\n#define {guard}\n\n#include <stdint.h>\n\nstatic const uint8_t fru[{}] = {{\n",
+        data.len(),
+        data.len(),
+        guard = C_ARRAY_GUARD
+    );
+    for line in data.chunks(C_ARRAY_COLUMNS) {
+        let bytes: Vec<String> = line.iter().map(|b| format!("0x{:02X}", b)).collect();
+        let _ = writeln!(out, "    {},", bytes.join(", "));
+    }
+    let _ = writeln!(out, "}};\n\n#endif /* {} */", C_ARRAY_GUARD);
+    out
+}
+
+/// Decode the hex digits of one record (after its start code) and check the byte count.
+fn record_bytes(line_number: usize, digits: &str) -> Result<Vec<u8>, FruError> {
+    let error = |message: &str| FruError::InvalidImage(format!("line {}: {}", line_number, message));
+    if !digits.len().is_multiple_of(2) || !digits.bytes().all(|b| b.is_ascii_hexdigit()) {
+        return Err(error("record is not a sequence of hex bytes"));
+    }
+    let bytes: Vec<u8> = (0..digits.len())
+        .step_by(2)
+        .map(|i| u8::from_str_radix(&digits[i..i + 2], 16).unwrap_or(0))
+        .collect();
+    if bytes.is_empty() {
+        return Err(error("empty record"));
+    }
+    Ok(bytes)
+}
+
+fn parse_intel_hex(text: &str) -> Result<Vec<(u32, Vec<u8>)>, FruError> {
+    let mut chunks = Vec::new();
+    let mut upper = 0u32;
+    let mut ended = false;
+    for (index, line) in text.lines().enumerate().map(|(i, l)| (i + 1, l.trim())) {
+        if line.is_empty() {
+            continue;
+        }
+        let error = |message: String| FruError::InvalidImage(format!("line {}: {}", index, message));
+        if ended {
+            return Err(error("data after the end-of-file record".to_string()));
+        }
+        let digits = line.strip_prefix(':').ok_or_else(|| error("record does not start with ':'".to_string()))?;
+        let bytes = record_bytes(index, digits)?;
+        if bytes.len() < 5 || bytes.len() != bytes[0] as usize + 5 {
+            return Err(error("record length does not match its byte count".to_string()));
+        }
+        if bytes.iter().fold(0u8, |sum, b| sum.wrapping_add(*b)) != 0 {
+            return Err(error("bad record checksum".to_string()));
+        }
+        let address = u16::from_be_bytes([bytes[1], bytes[2]]) as u32;
+        let data = &bytes[4..bytes.len() - 1];
+        match bytes[3] {
+            0x00 => chunks.push((upper.wrapping_add(address), data.to_vec())),
+            0x01 => ended = true,
+            0x02 if data.len() == 2 => upper = (u16::from_be_bytes([data[0], data[1]]) as u32) << 4,
+            0x04 if data.len() == 2 => upper = (u16::from_be_bytes([data[0], data[1]]) as u32) << 16,
+            0x03 | 0x05 => {}
+            kind => return Err(error(format!("unsupported record type 0x{:02X}", kind))),
+        }
+    }
+    if !ended {
+        return Err(FruError::InvalidImage("Intel HEX file has no end-of-file record".to_string()));
+    }
+    Ok(chunks)
+}
+
+fn parse_srec(text: &str) -> Result<Vec<(u32, Vec<u8>)>, FruError> {
+    let mut chunks = Vec::new();
+    for (index, line) in text.lines().enumerate().map(|(i, l)| (i + 1, l.trim())) {
+        if line.is_empty() {
+            continue;
+        }
+        let error = |message: String| FruError::InvalidImage(format!("line {}: {}", index, message));
+        let kind = line
+            .strip_prefix('S')
+            .and_then(|rest| rest.chars().next())
+            .ok_or_else(|| error("record does not start with 'S'".to_string()))?;
+        let bytes = record_bytes(index, &line[1 + kind.len_utf8()..])?;
+        if bytes.len() != bytes[0] as usize + 1 {
+            return Err(error("record length does not match its byte count".to_string()));
+        }
+        if bytes.iter().fold(0u8, |sum, b| sum.wrapping_add(*b)) != 0xFF {
+            return Err(error("bad record checksum".to_string()));
+        }
+        let address_len = match kind {
+            '1' => 2,
+            '2' => 3,
+            '3' => 4,
+            '0' | '5' | '6' | '7' | '8' | '9' => continue,
+            other => return Err(error(format!("unsupported record type 'S{}'", other))),
+        };
+        if bytes.len() < address_len + 2 {
+            return Err(error("record is shorter than its address".to_string()));
+        }
+        let address = bytes[1..=address_len].iter().fold(0u32, |a, b| (a << 8) | *b as u32);
+        chunks.push((address, bytes[address_len + 1..bytes.len() - 1].to_vec()));
+    }
+    Ok(chunks)
+}
+
+/// Place address-tagged chunks into one contiguous image starting at the lowest address.
+fn assemble(chunks: Vec<(u32, Vec<u8>)>) -> Result<(u32, Vec<u8>), FruError> {
+    let mut bytes = BTreeMap::new();
+    for (address, data) in chunks {
+        for (offset, byte) in data.into_iter().enumerate() {
+            let address = address as u64 + offset as u64;
+            if bytes.insert(address, byte).is_some_and(|previous| previous != byte) {
+                return Err(FruError::InvalidImage(format!("conflicting data at address 0x{:08X}", address)));
+            }
+        }
+    }
+    let (Some(&start), Some(&end)) = (bytes.keys().next(), bytes.keys().next_back()) else {
+        return Err(FruError::InvalidImage("file contains no data records".to_string()));
+    };
+    if end - start + 1 > MAX_IMAGE_SPAN {
+        return Err(FruError::InvalidImage(format!(
+            "data spans 0x{:08X}-0x{:08X}, more than the {} bytes a FRU image can use",
+            start, end, MAX_IMAGE_SPAN
+        )));
+    }
+    let mut data = vec![0x00; (end - start + 1) as usize];
+    for (address, byte) in bytes {
+        data[(address - start) as usize] = byte;
+    }
+    Ok((start as u32, data))
+}
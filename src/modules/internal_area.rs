@@ -1,54 +1,111 @@
-use super::area::Area;
+use super::area::{zero_checksum, Area};
+use crate::{parse_hex_bytes, FruError, InternalUseConfig};
 use serde::Deserialize;
 
+/// Payload and reservation bytes the area can hold: after the header and version byte,
+/// up to the last offset the common header can address.
+pub const MAX_INTERNAL_USE_BYTES: usize = 0x7F8 - 8 - 1;
+
 #[derive(Debug, Deserialize)]
 pub struct Internal {
+    /// Area payload after the format version byte, as a hex string.
     pub internal_info: String,
+    /// Payload bytes to keep for later writes; shorter payloads are padded with 0x00.
+    #[serde(default)]
+    pub reserved_bytes: usize,
 }
 
 impl Internal {
     pub fn new(internal_info: String) -> Self {
-        Internal { internal_info }
+        Internal { internal_info, reserved_bytes: 0 }
+    }
+
+    pub fn with_reserved_bytes(mut self, reserved_bytes: usize) -> Self {
+        self.reserved_bytes = reserved_bytes;
+        self
+    }
+
+    /// Resolve the payload of an `[internal_use]` table.
+    pub fn from_config(config: &InternalUseConfig) -> Result<Self, FruError> {
+        let payload = match &config.payload {
+            Some(source) => source.resolve().map_err(|message| FruError::FieldEncoding {
+                area: "Internal Use Area".to_string(),
+                field: "payload".to_string(),
+                message,
+            })?,
+            None => Vec::new(),
+        };
+        let internal = Internal::new(payload.iter().map(|b| format!("{:02X}", b)).collect())
+            .with_reserved_bytes(config.reserve_bytes.unwrap_or(0));
+        internal.validate()?;
+        Ok(internal)
+    }
+
+    fn payload(&self) -> Result<Vec<u8>, FruError> {
+        parse_hex_bytes(&self.internal_info).map_err(|message| FruError::FieldEncoding {
+            area: "Internal Use Area".to_string(),
+            field: "Internal information".to_string(),
+            message,
+        })
     }
 }
 
 impl Area for Internal {
-    fn check_area_length(&self, field_name: &str, field_value: &str) {
+    fn check_area_length(&self, field_name: &str, field_value: &str) -> Result<(), FruError> {
         if field_value.len() > 0x3F {
-            panic!(
-                "Error: String length of {} exceed limitation\nExp:[0x3F], Act:[0x{:02X}]",
-                field_name,
-                field_value.len()
-            );
+            return Err(FruError::FieldTooLong {
+                area: "Internal Use Area".to_string(),
+                field: field_name.to_string(),
+                len: field_value.len(),
+            });
         }
+        Ok(())
     }
 
-    fn validate(&self) {
-        self.check_area_length("Internal information", &self.internal_info);
+    fn validate(&self) -> Result<(), FruError> {
+        let len = self.payload()?.len();
+        if len.max(self.reserved_bytes) > MAX_INTERNAL_USE_BYTES {
+            return Err(FruError::InternalUseOutOfRange {
+                len: len.max(self.reserved_bytes),
+                max: MAX_INTERNAL_USE_BYTES,
+            });
+        }
+        if self.reserved_bytes > 0 && len > self.reserved_bytes {
+            return Err(FruError::InternalUseTooLarge { len, reserved: self.reserved_bytes });
+        }
+        Ok(())
     }
 
-    fn transfer_as_byte(&self) -> Vec<u8> {
-        self.validate();
+    fn transfer_as_byte(&self) -> Result<Vec<u8>, FruError> {
+        self.validate()?;
+        let payload = self.payload()?;
         let mut internal_use_area = Vec::new();
 
         internal_use_area.push(0x01); // Version Code.
 
+        if !payload.is_empty() || self.reserved_bytes > 0 {
+            // Payload and reservation, padded to a multiple of 8 bytes
+            internal_use_area.extend(&payload);
+            internal_use_area.resize(1 + payload.len().max(self.reserved_bytes), 0x00);
+            internal_use_area.resize(internal_use_area.len().div_ceil(8) * 8, 0x00);
+            return Ok(internal_use_area);
+        }
+
         internal_use_area.push(0x00); // Checksum placeholder
 
         while internal_use_area.len() % 8 != 0 {
             internal_use_area.push(0x00);
         }
 
-        let checksum =
-            (0x100u16 - (internal_use_area.iter().map(|&i| i as u16).sum::<u16>() % 256)) % 256;
+        let checksum = zero_checksum(&internal_use_area);
         if let Some(last_byte) = internal_use_area.last_mut() {
-            *last_byte = checksum as u8;
+            *last_byte = checksum;
         }
 
-        internal_use_area
+        Ok(internal_use_area)
     }
 
-    fn transfer_with_config(&self, _field_configs: &[super::area::FieldConfig]) -> Vec<u8> {
+    fn transfer_with_config(&self, _field_configs: &[super::area::FieldConfig]) -> Result<Vec<u8>, FruError> {
         self.transfer_as_byte()
     }
 }
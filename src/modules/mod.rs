@@ -1,6 +1,17 @@
 pub mod area;
+pub mod batch;
 pub mod board_area;
 pub mod chassis_area;
+pub mod decode;
+pub mod diff;
+pub mod encoding;
+pub mod export;
 pub mod fru_editor;
+pub mod fru_image;
+pub mod image_format;
 pub mod internal_area;
+pub mod multirecord_area;
+pub mod patch;
 pub mod product_area;
+pub mod sequence;
+pub mod validate;
@@ -0,0 +1,571 @@
+use super::area::{zero_checksum, Area, FieldConfig};
+use crate::{parse_hex_bytes, FruError, PayloadSource};
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+/// Record format version written in bits 3:0 of every record header.
+pub const RECORD_FORMAT_VERSION: u8 = 0x02;
+
+/// Maximum number of data bytes a single record can carry (length is one byte).
+pub const MAX_RECORD_DATA: usize = 0xFF;
+
+/// Power Supply Information record (type 0x00).
+///
+/// Voltages are in 10 mV units, currents in A, frequencies in Hz and times in ms,
+/// as laid out in the IPMI FRU specification.
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+#[serde(default)]
+pub struct PowerSupplyInfo {
+    /// Overall capacity in watts (12 bits).
+    pub overall_capacity: u16,
+    /// Peak VA, 0xFFFF if not specified.
+    pub peak_va: u16,
+    /// Inrush current in A, 0xFF if not specified.
+    pub inrush_current: u8,
+    /// Inrush interval in ms.
+    pub inrush_interval: u8,
+    pub low_input_voltage_1: u16,
+    pub high_input_voltage_1: u16,
+    /// Second input range, 0 for single-range supplies.
+    pub low_input_voltage_2: u16,
+    pub high_input_voltage_2: u16,
+    pub low_input_frequency: u8,
+    pub high_input_frequency: u8,
+    /// A/C dropout tolerance in ms.
+    pub dropout_tolerance: u8,
+    pub predictive_fail_support: bool,
+    pub power_factor_correction: bool,
+    pub autoswitch: bool,
+    pub hot_swap: bool,
+    /// Predictive fail tachometer pulses / pin polarity bit.
+    pub predictive_fail_polarity: bool,
+    /// Peak capacity in watts (12 bits).
+    pub peak_capacity: u16,
+    /// Hold-up time in seconds at peak capacity (4 bits).
+    pub hold_up_time: u8,
+    /// Combined wattage voltage codes: 0 = 12V, 1 = -12V, 2 = 5V, 3 = 3.3V.
+    pub combined_voltage_1: u8,
+    pub combined_voltage_2: u8,
+    /// Total combined wattage, 0 if not specified.
+    pub total_combined_wattage: u16,
+    /// Predictive fail tachometer lower threshold in RPS.
+    pub tach_lower_threshold: u8,
+}
+
+impl Default for PowerSupplyInfo {
+    fn default() -> Self {
+        PowerSupplyInfo {
+            overall_capacity: 0,
+            peak_va: 0xFFFF,
+            inrush_current: 0xFF,
+            inrush_interval: 0,
+            low_input_voltage_1: 0,
+            high_input_voltage_1: 0,
+            low_input_voltage_2: 0,
+            high_input_voltage_2: 0,
+            low_input_frequency: 0,
+            high_input_frequency: 0,
+            dropout_tolerance: 0,
+            predictive_fail_support: false,
+            power_factor_correction: false,
+            autoswitch: false,
+            hot_swap: false,
+            predictive_fail_polarity: false,
+            peak_capacity: 0,
+            hold_up_time: 0,
+            combined_voltage_1: 0,
+            combined_voltage_2: 0,
+            total_combined_wattage: 0,
+            tach_lower_threshold: 0,
+        }
+    }
+}
+
+/// DC Output record (type 0x01). Voltages in 10 mV units, ripple in mV, currents in mA.
+#[derive(Debug, Clone, Default, PartialEq, Deserialize, Serialize)]
+#[serde(default)]
+pub struct DcOutput {
+    /// Output number (4 bits).
+    pub output_number: u8,
+    /// Output is active in standby.
+    pub standby: bool,
+    pub nominal_voltage: i16,
+    pub max_negative_deviation: i16,
+    pub max_positive_deviation: i16,
+    pub ripple_noise: u16,
+    pub min_current: u16,
+    pub max_current: u16,
+}
+
+/// DC Load record (type 0x02). Voltages in 10 mV units, ripple in mV, currents in mA.
+#[derive(Debug, Clone, Default, PartialEq, Deserialize, Serialize)]
+#[serde(default)]
+pub struct DcLoad {
+    /// Output number (4 bits).
+    pub output_number: u8,
+    pub nominal_voltage: i16,
+    pub min_voltage: i16,
+    pub max_voltage: i16,
+    pub ripple_noise: u16,
+    pub min_current: u16,
+    pub max_current: u16,
+}
+
+/// Management Access sub-record types: (code, config name, label, min length, max length).
+pub const MANAGEMENT_SUB_RECORD_TABLE: &[(u8, &str, &str, usize, usize)] = &[
+    (0x01, "system_url", "System URL", 16, 256),
+    (0x02, "system_name", "System Name", 8, 64),
+    (0x03, "system_ping_address", "System Ping Address", 8, 64),
+    (0x04, "component_url", "Component URL", 16, 256),
+    (0x05, "component_name", "Component Name", 8, 64),
+    (0x06, "component_ping_address", "Component Ping Address", 8, 64),
+    (0x07, "system_unique_id", "System Unique ID", 16, 16),
+];
+
+/// Sub-record type of the System Unique ID, whose value is a 16-byte GUID.
+pub const SYSTEM_UNIQUE_ID: u8 = 0x07;
+
+pub fn parse_management_sub_record(input: &str) -> Option<u8> {
+    let trimmed = input.trim();
+    if let Some((code, ..)) = MANAGEMENT_SUB_RECORD_TABLE
+        .iter()
+        .find(|(_, name, label, ..)| name.eq_ignore_ascii_case(trimmed) || label.eq_ignore_ascii_case(trimmed))
+    {
+        return Some(*code);
+    }
+    crate::parser_hex_string(trimmed).ok()
+}
+
+fn deserialize_sub_record<'de, D: Deserializer<'de>>(deserializer: D) -> Result<u8, D::Error> {
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum SubRecord {
+        Code(u8),
+        Name(String),
+    }
+    match SubRecord::deserialize(deserializer)? {
+        SubRecord::Code(code) => Ok(code),
+        SubRecord::Name(name) => parse_management_sub_record(&name).ok_or_else(|| {
+            serde::de::Error::custom(format!("unknown management access sub-record '{}'", name))
+        }),
+    }
+}
+
+fn serialize_sub_record<S: Serializer>(code: &u8, serializer: S) -> Result<S::Ok, S::Error> {
+    match MANAGEMENT_SUB_RECORD_TABLE.iter().find(|(c, ..)| c == code) {
+        Some((_, name, ..)) => serializer.serialize_str(name),
+        None => serializer.serialize_u8(*code),
+    }
+}
+
+/// Management Access record (type 0x03).
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+pub struct ManagementAccess {
+    /// Sub-record type, given in config as a name (e.g. "system_url") or code (0x01-0x07).
+    #[serde(deserialize_with = "deserialize_sub_record", serialize_with = "serialize_sub_record")]
+    pub sub_record: u8,
+    /// Text for URLs, names and ping addresses; hex string (dashes allowed) for the System Unique ID.
+    pub value: String,
+}
+
+impl ManagementAccess {
+    pub fn label(&self) -> &'static str {
+        MANAGEMENT_SUB_RECORD_TABLE
+            .iter()
+            .find(|(code, ..)| *code == self.sub_record)
+            .map(|(_, _, label, ..)| *label)
+            .unwrap_or("Unknown")
+    }
+
+    fn value_bytes(&self) -> Vec<u8> {
+        if self.sub_record == SYSTEM_UNIQUE_ID {
+            parse_hex_bytes(&self.value).unwrap_or_default()
+        } else {
+            self.value.as_bytes().to_vec()
+        }
+    }
+}
+
+/// Base (type 0x04) and Extended (type 0x05) Compatibility records.
+#[derive(Debug, Clone, Default, PartialEq, Deserialize, Serialize)]
+#[serde(default)]
+pub struct Compatibility {
+    /// IANA enterprise number of the manufacturer (3 bytes).
+    pub manufacturer_id: u32,
+    pub entity_id: u8,
+    pub compatibility_base: u8,
+    pub code_start: u8,
+    /// Code range mask bytes, one bit per compatibility code from `code_start`.
+    pub code_range_mask: Vec<u8>,
+}
+
+/// First record type ID reserved for OEM records.
+pub const OEM_RECORD_TYPE_MIN: u8 = 0xC0;
+
+#[derive(Deserialize, Serialize)]
+#[serde(deny_unknown_fields)]
+struct OemConfig {
+    type_id: u8,
+    manufacturer_id: u32,
+    payload: PayloadSource,
+}
+
+/// OEM record (types 0xC0-0xFF): a 3-byte IANA enterprise number followed by vendor data.
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+#[serde(try_from = "OemConfig", into = "OemConfig")]
+pub struct Oem {
+    pub type_id: u8,
+    /// IANA enterprise number of the vendor defining the payload (3 bytes).
+    pub manufacturer_id: u32,
+    pub data: Vec<u8>,
+}
+
+impl From<Oem> for OemConfig {
+    fn from(oem: Oem) -> Self {
+        let hex = oem.data.iter().map(|b| format!("{:02X}", b)).collect::<Vec<_>>().join(":");
+        OemConfig {
+            type_id: oem.type_id,
+            manufacturer_id: oem.manufacturer_id,
+            payload: PayloadSource { hex: Some(hex), ..Default::default() },
+        }
+    }
+}
+
+impl TryFrom<OemConfig> for Oem {
+    type Error = String;
+
+    fn try_from(config: OemConfig) -> Result<Self, Self::Error> {
+        Ok(Oem {
+            type_id: config.type_id,
+            manufacturer_id: config.manufacturer_id,
+            data: config
+                .payload
+                .resolve()
+                .map_err(|e| format!("OEM record 0x{:02X}: {}", config.type_id, e))?,
+        })
+    }
+}
+
+/// One entry of the MultiRecord Area.
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum Record {
+    PowerSupply(PowerSupplyInfo),
+    DcOutput(DcOutput),
+    DcLoad(DcLoad),
+    ManagementAccess(ManagementAccess),
+    BaseCompatibility(Compatibility),
+    ExtendedCompatibility(Compatibility),
+    Oem(Oem),
+    /// A record read from an image whose type is not understood; kept verbatim.
+    #[serde(skip)]
+    Unknown { type_id: u8, data: Vec<u8> },
+}
+
+fn le16(data: &[u8], at: usize) -> u16 {
+    data[at] as u16 | (data[at + 1] as u16) << 8
+}
+
+impl Record {
+    pub fn type_id(&self) -> u8 {
+        match self {
+            Record::PowerSupply(_) => 0x00,
+            Record::DcOutput(_) => 0x01,
+            Record::DcLoad(_) => 0x02,
+            Record::ManagementAccess(_) => 0x03,
+            Record::BaseCompatibility(_) => 0x04,
+            Record::ExtendedCompatibility(_) => 0x05,
+            Record::Oem(oem) => oem.type_id,
+            Record::Unknown { type_id, .. } => *type_id,
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Record::PowerSupply(_) => "Power Supply Information",
+            Record::DcOutput(_) => "DC Output",
+            Record::DcLoad(_) => "DC Load",
+            Record::ManagementAccess(_) => "Management Access",
+            Record::BaseCompatibility(_) => "Base Compatibility",
+            Record::ExtendedCompatibility(_) => "Extended Compatibility",
+            Record::Oem(_) => "OEM",
+            Record::Unknown { .. } => "Unknown",
+        }
+    }
+
+    /// Record data bytes, excluding the 5-byte record header.
+    pub fn payload(&self) -> Vec<u8> {
+        let mut data = Vec::new();
+        match self {
+            Record::PowerSupply(ps) => {
+                data.extend((ps.overall_capacity & 0x0FFF).to_le_bytes());
+                data.extend(ps.peak_va.to_le_bytes());
+                data.push(ps.inrush_current);
+                data.push(ps.inrush_interval);
+                data.extend(ps.low_input_voltage_1.to_le_bytes());
+                data.extend(ps.high_input_voltage_1.to_le_bytes());
+                data.extend(ps.low_input_voltage_2.to_le_bytes());
+                data.extend(ps.high_input_voltage_2.to_le_bytes());
+                data.push(ps.low_input_frequency);
+                data.push(ps.high_input_frequency);
+                data.push(ps.dropout_tolerance);
+                data.push(
+                    (ps.predictive_fail_polarity as u8) << 4
+                        | (ps.hot_swap as u8) << 3
+                        | (ps.autoswitch as u8) << 2
+                        | (ps.power_factor_correction as u8) << 1
+                        | ps.predictive_fail_support as u8,
+                );
+                let peak = (ps.hold_up_time as u16 & 0x0F) << 12 | (ps.peak_capacity & 0x0FFF);
+                data.extend(peak.to_le_bytes());
+                data.push((ps.combined_voltage_1 & 0x0F) << 4 | (ps.combined_voltage_2 & 0x0F));
+                data.extend(ps.total_combined_wattage.to_le_bytes());
+                data.push(ps.tach_lower_threshold);
+            }
+            Record::DcOutput(dc) => {
+                data.push((dc.standby as u8) << 7 | (dc.output_number & 0x0F));
+                data.extend(dc.nominal_voltage.to_le_bytes());
+                data.extend(dc.max_negative_deviation.to_le_bytes());
+                data.extend(dc.max_positive_deviation.to_le_bytes());
+                data.extend(dc.ripple_noise.to_le_bytes());
+                data.extend(dc.min_current.to_le_bytes());
+                data.extend(dc.max_current.to_le_bytes());
+            }
+            Record::DcLoad(dl) => {
+                data.push(dl.output_number & 0x0F);
+                data.extend(dl.nominal_voltage.to_le_bytes());
+                data.extend(dl.min_voltage.to_le_bytes());
+                data.extend(dl.max_voltage.to_le_bytes());
+                data.extend(dl.ripple_noise.to_le_bytes());
+                data.extend(dl.min_current.to_le_bytes());
+                data.extend(dl.max_current.to_le_bytes());
+            }
+            Record::ManagementAccess(ma) => {
+                data.push(ma.sub_record);
+                data.extend(ma.value_bytes());
+            }
+            Record::BaseCompatibility(c) | Record::ExtendedCompatibility(c) => {
+                data.extend(&c.manufacturer_id.to_le_bytes()[..3]);
+                data.push(c.entity_id);
+                data.push(c.compatibility_base);
+                data.push(c.code_start);
+                data.extend(&c.code_range_mask);
+            }
+            Record::Oem(oem) => {
+                data.extend(&oem.manufacturer_id.to_le_bytes()[..3]);
+                data.extend(&oem.data);
+            }
+            Record::Unknown { data: raw, .. } => data.extend_from_slice(raw),
+        }
+        data
+    }
+
+    /// Check a record against the limits of its type.
+    pub fn check(&self) -> Result<(), String> {
+        match self {
+            Record::ManagementAccess(ma) => {
+                let (_, _, label, min, max) = MANAGEMENT_SUB_RECORD_TABLE
+                    .iter()
+                    .find(|(code, ..)| *code == ma.sub_record)
+                    .ok_or_else(|| format!("Unknown management access sub-record type 0x{:02X}", ma.sub_record))?;
+                let len = ma.value_bytes().len();
+                if len < *min || len > *max {
+                    return Err(format!(
+                        "{} must be {}-{} bytes, got {}",
+                        label, min, max, len
+                    ));
+                }
+            }
+            Record::BaseCompatibility(c) | Record::ExtendedCompatibility(c)
+                if c.manufacturer_id > 0xFF_FFFF =>
+            {
+                return Err(format!(
+                    "{} manufacturer ID 0x{:X} does not fit in 3 bytes",
+                    self.name(),
+                    c.manufacturer_id
+                ));
+            }
+            Record::Oem(oem) => {
+                if oem.type_id < OEM_RECORD_TYPE_MIN {
+                    return Err(format!(
+                        "OEM record type 0x{:02X} is outside the OEM range 0xC0-0xFF",
+                        oem.type_id
+                    ));
+                }
+                if oem.manufacturer_id > 0xFF_FFFF {
+                    return Err(format!(
+                        "OEM manufacturer ID 0x{:X} does not fit in 3 bytes",
+                        oem.manufacturer_id
+                    ));
+                }
+                if 3 + oem.data.len() > MAX_RECORD_DATA {
+                    return Err(format!(
+                        "OEM record 0x{:02X} carries {} bytes (3-byte manufacturer ID + {} byte payload), limit is {}",
+                        oem.type_id,
+                        3 + oem.data.len(),
+                        oem.data.len(),
+                        MAX_RECORD_DATA
+                    ));
+                }
+            }
+            _ => {}
+        }
+
+        let len = self.payload().len();
+        if len > MAX_RECORD_DATA {
+            return Err(format!(
+                "{} record carries {} bytes, limit is {}",
+                self.name(),
+                len,
+                MAX_RECORD_DATA
+            ));
+        }
+        Ok(())
+    }
+
+    /// Rebuild a typed record from its type ID and data bytes.
+    ///
+    /// Records of unknown type, or whose length does not match their type, are
+    /// returned as `Record::Unknown` so that they survive a round trip untouched.
+    pub fn from_bytes(type_id: u8, data: &[u8]) -> Record {
+        match (type_id, data.len()) {
+            (0x00, 24) => Record::PowerSupply(PowerSupplyInfo {
+                overall_capacity: le16(data, 0) & 0x0FFF,
+                peak_va: le16(data, 2),
+                inrush_current: data[4],
+                inrush_interval: data[5],
+                low_input_voltage_1: le16(data, 6),
+                high_input_voltage_1: le16(data, 8),
+                low_input_voltage_2: le16(data, 10),
+                high_input_voltage_2: le16(data, 12),
+                low_input_frequency: data[14],
+                high_input_frequency: data[15],
+                dropout_tolerance: data[16],
+                predictive_fail_support: data[17] & 0x01 != 0,
+                power_factor_correction: data[17] & 0x02 != 0,
+                autoswitch: data[17] & 0x04 != 0,
+                hot_swap: data[17] & 0x08 != 0,
+                predictive_fail_polarity: data[17] & 0x10 != 0,
+                peak_capacity: le16(data, 18) & 0x0FFF,
+                hold_up_time: (le16(data, 18) >> 12) as u8,
+                combined_voltage_1: data[20] >> 4,
+                combined_voltage_2: data[20] & 0x0F,
+                total_combined_wattage: le16(data, 21),
+                tach_lower_threshold: data[23],
+            }),
+            (0x01, 13) => Record::DcOutput(DcOutput {
+                output_number: data[0] & 0x0F,
+                standby: data[0] & 0x80 != 0,
+                nominal_voltage: le16(data, 1) as i16,
+                max_negative_deviation: le16(data, 3) as i16,
+                max_positive_deviation: le16(data, 5) as i16,
+                ripple_noise: le16(data, 7),
+                min_current: le16(data, 9),
+                max_current: le16(data, 11),
+            }),
+            (0x02, 13) => Record::DcLoad(DcLoad {
+                output_number: data[0] & 0x0F,
+                nominal_voltage: le16(data, 1) as i16,
+                min_voltage: le16(data, 3) as i16,
+                max_voltage: le16(data, 5) as i16,
+                ripple_noise: le16(data, 7),
+                min_current: le16(data, 9),
+                max_current: le16(data, 11),
+            }),
+            (0x03, 1..) => Record::ManagementAccess(ManagementAccess {
+                sub_record: data[0],
+                value: if data[0] == SYSTEM_UNIQUE_ID {
+                    data[1..].iter().map(|b| format!("{:02X}", b)).collect()
+                } else {
+                    data[1..].iter().map(|&b| b as char).collect()
+                },
+            }),
+            (0x04 | 0x05, 6..) => {
+                let compatibility = Compatibility {
+                    manufacturer_id: data[0] as u32 | (data[1] as u32) << 8 | (data[2] as u32) << 16,
+                    entity_id: data[3],
+                    compatibility_base: data[4],
+                    code_start: data[5],
+                    code_range_mask: data[6..].to_vec(),
+                };
+                if type_id == 0x04 {
+                    Record::BaseCompatibility(compatibility)
+                } else {
+                    Record::ExtendedCompatibility(compatibility)
+                }
+            }
+            (OEM_RECORD_TYPE_MIN.., 3..) => Record::Oem(Oem {
+                type_id,
+                manufacturer_id: data[0] as u32 | (data[1] as u32) << 8 | (data[2] as u32) << 16,
+                data: data[3..].to_vec(),
+            }),
+            _ => Record::Unknown {
+                type_id,
+                data: data.to_vec(),
+            },
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct MultiRecord {
+    pub records: Vec<Record>,
+}
+
+impl MultiRecord {
+    pub fn new(records: Vec<Record>) -> Self {
+        MultiRecord { records }
+    }
+
+    pub fn print_all(&self) {
+        for record in &self.records {
+            println!("Record 0x{:02X} ({}) = {:?}", record.type_id(), record.name(), record);
+        }
+    }
+}
+
+impl Area for MultiRecord {
+    fn check_area_length(&self, field_name: &str, field_value: &str) -> Result<(), FruError> {
+        if field_value.len() > MAX_RECORD_DATA {
+            return Err(FruError::InvalidRecord(format!(
+                "Data length of {} exceed limitation\nExp:[0xFF], Act:[0x{:02X}]",
+                field_name,
+                field_value.len()
+            )));
+        }
+        Ok(())
+    }
+
+    fn validate(&self) -> Result<(), FruError> {
+        for record in &self.records {
+            record.check().map_err(FruError::InvalidRecord)?;
+        }
+        Ok(())
+    }
+
+    fn transfer_as_byte(&self) -> Result<Vec<u8>, FruError> {
+        self.validate()?;
+        let mut multirecord_area = Vec::new();
+
+        for (i, record) in self.records.iter().enumerate() {
+            let data = record.payload();
+            let end_of_list = if i + 1 == self.records.len() { 0x80 } else { 0x00 };
+
+            let mut header = vec![
+                record.type_id(),
+                end_of_list | RECORD_FORMAT_VERSION,
+                data.len() as u8,
+                zero_checksum(&data),
+            ];
+            header.push(zero_checksum(&header));
+
+            multirecord_area.extend(header);
+            multirecord_area.extend(data);
+        }
+
+        Ok(multirecord_area)
+    }
+
+    fn transfer_with_config(&self, _field_configs: &[FieldConfig]) -> Result<Vec<u8>, FruError> {
+        self.transfer_as_byte()
+    }
+}
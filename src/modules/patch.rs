@@ -0,0 +1,218 @@
+use super::area::zero_checksum;
+use super::board_area::check_mfg_time;
+use super::decode::{decode_fru, DecodedArea, DecodedField, BOARD_FIELDS, CHASSIS_FIELDS, PRODUCT_FIELDS};
+use super::encoding::{encode_text, is_english, Encoding, MAX_FIELD_LENGTH};
+use crate::{try_parse_chassis_type, FruError};
+
+/// Area `patch_image` can edit: (area name, common header byte, predefined fields, fixed prefix length).
+type PatchableArea = (&'static str, usize, &'static [(&'static str, &'static str)], usize);
+
+const PATCHABLE_AREAS: &[PatchableArea] = &[
+    ("Chassis Info Area", 2, CHASSIS_FIELDS, 3),
+    ("Board Info Area", 3, BOARD_FIELDS, 6),
+    ("Product Info Area", 4, PRODUCT_FIELDS, 3),
+];
+
+/// Area a patch key belongs to, as an index into `PATCHABLE_AREAS`.
+fn area_of_key(key: &str) -> Option<usize> {
+    match key {
+        "chassis_type" => Some(0),
+        "board_mfg_date_time" => Some(1),
+        _ => PATCHABLE_AREAS
+            .iter()
+            .position(|(_, _, fields, _)| fields.iter().any(|(k, _)| *k == key)),
+    }
+}
+
+/// Encode a replacement field, keeping its original type and length when the value allows it.
+fn encode_replacement(area: &str, key: &str, value: &str, original: &DecodedField, language: u8) -> Result<Vec<u8>, FruError> {
+    let type_code = Encoding::from_type_code(original.type_code());
+    let (encoding, mut bytes) = match encode_text(value, type_code, language) {
+        Ok(encoded) => encoded,
+        Err(_) if type_code != Encoding::Binary => encode_text(value, Encoding::Ascii8, language)
+            .map_err(|message| FruError::FieldEncoding { area: area.to_string(), field: key.to_string(), message })?,
+        Err(message) => return Err(FruError::FieldEncoding { area: area.to_string(), field: key.to_string(), message }),
+    };
+    if bytes.len() > MAX_FIELD_LENGTH {
+        return Err(FruError::FieldTooLong { area: area.to_string(), field: key.to_string(), len: bytes.len() });
+    }
+    // Keep space reserved by the original field, as generation with reserve_bytes left it
+    if encoding == Encoding::Ascii8 && !is_english(language) {
+        while bytes.len() + 2 <= original.data.len() {
+            bytes.extend_from_slice(&[b' ', 0x00]);
+        }
+    } else if bytes.len() < original.data.len() {
+        bytes.resize(original.data.len(), encoding.pad_byte());
+    }
+    let type_code = encoding.type_code().unwrap_or(0b11);
+    if type_code == 0b11 && bytes.len() == 1 {
+        bytes.push(encoding.pad_byte()); // 0xC1 would read as the end-of-fields marker
+    }
+    let mut field = vec![type_code << 6 | bytes.len() as u8];
+    field.extend(bytes);
+    Ok(field)
+}
+
+/// Byte the image is padded with after its areas: 0xFF when the tail is erased
+/// EEPROM (a `[layout]` `pad_byte`), else 0x00.
+fn pad_byte(data: &[u8], areas: &[&DecodedArea]) -> u8 {
+    let end = areas.iter().map(|a| a.offset + a.length).max().unwrap_or(8);
+    match data.get(end..) {
+        Some(tail) if !tail.is_empty() && tail.iter().all(|&b| b == 0xFF) => 0xFF,
+        _ => 0x00,
+    }
+}
+
+///
+/// Re-lay out one info area with the given changes.
+///
+/// The area keeps its original length when the new content fits, and otherwise grows to
+/// the next multiple of 8 bytes. Length byte and checksum are recomputed.
+fn patch_area(
+    data: &[u8],
+    decoded: &DecodedArea,
+    prefix_len: usize,
+    sets: &[(&str, &str)],
+) -> Result<Vec<u8>, FruError> {
+    let name = decoded.name.as_str();
+    let area = &data[decoded.offset..decoded.offset + decoded.length];
+    let language = if name == "Chassis Info Area" { 0 } else { area[2] };
+
+    let mut body = area[..prefix_len].to_vec();
+    let mut fields: Vec<Vec<u8>> = decoded
+        .fields
+        .iter()
+        .map(|f| {
+            let start = f.offset - decoded.offset;
+            area[start..start + 1 + f.data.len()].to_vec()
+        })
+        .collect();
+
+    for &(key, value) in sets {
+        match key {
+            "chassis_type" => body[2] = try_parse_chassis_type(value)?,
+            "board_mfg_date_time" => {
+                let minutes = check_mfg_time(value).map_err(|message| FruError::FieldEncoding {
+                    area: name.to_string(),
+                    field: key.to_string(),
+                    message,
+                })?;
+                body[3..6].copy_from_slice(&minutes.to_le_bytes()[..3]);
+            }
+            _ => {
+                let index = decoded
+                    .fields
+                    .iter()
+                    .position(|f| f.key == key)
+                    .ok_or_else(|| FruError::InvalidImage(format!("{} has no '{}' field", name, key)))?;
+                fields[index] = encode_replacement(name, key, value, &decoded.fields[index], language)?;
+            }
+        }
+    }
+
+    body.extend(fields.concat());
+    body.push(0xC1);
+    let length = if body.len() < area.len() { area.len() } else { (body.len() + 1).div_ceil(8) * 8 };
+    if length / 8 > 0xFF {
+        return Err(FruError::InvalidImage(format!("{} would grow beyond 0x7F8 bytes", name)));
+    }
+    body.resize(length - 1, 0x00);
+    body[1] = (length / 8) as u8;
+    body.push(zero_checksum(&body));
+    Ok(body)
+}
+
+///
+/// Replace fields of an existing FRU image in place.
+///
+/// `sets` are (config key, value) pairs: any predefined Chassis/Board/Product field key
+/// such as `product_asset_tag`, plus `chassis_type` and `board_mfg_date_time`. Edited
+/// areas are re-laid out with fresh length and checksum; when one grows, the areas after
+/// it move (using free 0x00 or 0xFF pad space first) and the common header is updated. All other bytes,
+/// including the internal use area and unknown multi-records, are kept as they are.
+/// The image keeps its size.
+///
+/// # Example
+/// ```no_run
+/// use fru_gen::modules::patch::patch_image;
+///
+/// let image = std::fs::read("fru_gen.bin")?;
+/// let patched = patch_image(&image, &[("product_asset_tag".to_string(), "ABC123".to_string())])?;
+/// std::fs::write("fru_gen.bin", patched)?;
+/// # Ok::<(), Box<dyn std::error::Error>>(())
+/// ```
+pub fn patch_image(data: &[u8], sets: &[(String, String)]) -> Result<Vec<u8>, FruError> {
+    let fru = decode_fru(data).map_err(|e| FruError::InvalidImage(e.to_string()))?;
+
+    let mut grouped: Vec<Vec<(&str, &str)>> = vec![Vec::new(); PATCHABLE_AREAS.len()];
+    let keys: Vec<(String, &str)> = sets.iter().map(|(k, v)| (k.trim().to_lowercase(), v.as_str())).collect();
+    for (key, value) in &keys {
+        let index = area_of_key(key).ok_or_else(|| FruError::UnknownField(key.clone()))?;
+        grouped[index].push((key.as_str(), value));
+    }
+
+    let mut replaced: Vec<(usize, Vec<u8>)> = Vec::new();
+    for (i, &(name, _, _, prefix_len)) in PATCHABLE_AREAS.iter().enumerate() {
+        if grouped[i].is_empty() {
+            continue;
+        }
+        let decoded = fru.area(name).ok_or_else(|| FruError::MissingArea(name.to_string()))?;
+        if let Some(violation) = fru.violations.iter().find(|v| v.area == name) {
+            return Err(FruError::InvalidImage(violation.to_string()));
+        }
+        replaced.push((decoded.offset, patch_area(data, decoded, prefix_len, &grouped[i])?));
+    }
+
+    let mut areas: Vec<&DecodedArea> = fru.areas.iter().collect();
+    areas.sort_by_key(|a| a.offset);
+    if let Some(area) = areas.iter().find(|a| a.offset + a.length > data.len()) {
+        return Err(FruError::InvalidImage(format!(
+            "{} at offset 0x{:X} runs past the end of the {}-byte image",
+            area.name,
+            area.offset,
+            data.len()
+        )));
+    }
+    let pad = pad_byte(data, &areas);
+
+    let mut out = data[..8].to_vec();
+    let mut src = 8;
+    for area in areas {
+        if area.offset < src {
+            return Err(FruError::InvalidImage(format!("{} overlaps the area before it", area.name)));
+        }
+        // Free pad bytes right after a grown area absorb the growth before anything moves
+        let mut gap = &data[src..area.offset];
+        let shift = out.len().saturating_sub(src);
+        let free = gap.iter().take(shift).take_while(|&&b| b == pad).count();
+        gap = &gap[free..];
+        out.extend_from_slice(gap);
+        out.resize(out.len().div_ceil(8) * 8, pad);
+
+        let new_offset = out.len();
+        if new_offset / 8 > 0xFF {
+            return Err(FruError::OffsetOverflow { area: area.name.clone(), offset: new_offset });
+        }
+        match replaced.iter().find(|(offset, _)| *offset == area.offset) {
+            Some((_, bytes)) => out.extend_from_slice(bytes),
+            None => out.extend_from_slice(&data[area.offset..area.offset + area.length]),
+        }
+        if let Some(&(_, index, _, _)) = PATCHABLE_AREAS.iter().find(|(name, _, _, _)| *name == area.name) {
+            out[index] = (new_offset / 8) as u8;
+        } else if area.name == "Internal Use Area" {
+            out[1] = (new_offset / 8) as u8;
+        } else if area.name == "MultiRecord Area" {
+            out[5] = (new_offset / 8) as u8;
+        }
+        src = area.offset + area.length;
+    }
+
+    let tail = &data[src..];
+    let excess = (out.len() + tail.len()).saturating_sub(data.len());
+    if excess > tail.len() || tail[tail.len() - excess..].iter().any(|&b| b != pad) {
+        return Err(FruError::ImageTooLarge { limit: data.len(), actual: out.len() + tail.len() });
+    }
+    out.extend_from_slice(&tail[..tail.len() - excess]);
+    out[7] = zero_checksum(&out[..7]);
+    Ok(out)
+}
@@ -1,4 +1,6 @@
-use super::area::{Area, FieldConfig};
+use super::area::{zero_checksum, Area, CustomField, FieldConfig};
+use crate::FruError;
+use super::encoding;
 use serde::Deserialize;
 
 #[derive(Debug, Deserialize)]
@@ -11,9 +13,16 @@ pub struct Product {
     pub product_asset_tag: String,
     pub product_fru_file_id: String,
     pub product_extra: String,
+    /// Further custom fields after `product_extra`, each with its own settings.
+    #[serde(skip)]
+    pub product_custom: Vec<CustomField>,
+    /// IPMI language code of the area, see `LANGUAGE_CODE_TABLE` (0 = English).
+    #[serde(default)]
+    pub language_code: u8,
 }
 
 impl Product {
+    #[allow(clippy::too_many_arguments)]
     pub fn new(
         product_manufacturer: String,
         product_product_name: String,
@@ -33,8 +42,21 @@ impl Product {
             product_asset_tag,
             product_fru_file_id,
             product_extra,
+            product_custom: Vec::new(),
+            language_code: 0,
         }
     }
+
+    pub fn with_language_code(mut self, language_code: u8) -> Self {
+        self.language_code = language_code;
+        self
+    }
+
+    pub fn with_custom_fields(mut self, product_custom: Vec<CustomField>) -> Self {
+        self.product_custom = product_custom;
+        self
+    }
+
     pub fn print_all(&self) {
         println!("Product Manufacturer   = {}", &self.product_manufacturer);
         println!("Product Name           = {}", &self.product_product_name);
@@ -44,77 +66,86 @@ impl Product {
         println!("Product Asset Tag      = {}", &self.product_asset_tag);
         println!("Product Fru ID         = {}", &self.product_fru_file_id);
         println!("Product Extra          = {}", &self.product_extra);
+        for (i, field) in self.product_custom.iter().enumerate() {
+            println!("Product Custom {:<7} = {}", i + 1, &field.value);
+        }
     }
 }
 
 impl Area for Product {
-    fn check_area_length(&self, field_name: &str, field_value: &str) {
-        if field_value.len() > 0x3F {
-            panic!(
-                "Error: String length of {} exceed limitation\nExp:[0x3F], Act:[0x{:02X}]",
-                field_name,
-                field_value.len()
-            );
+    fn check_area_length(&self, field_name: &str, field_value: &str) -> Result<(), FruError> {
+        let length = encoding::text_length(field_value, self.language_code);
+        if length > 0x3F {
+            return Err(FruError::FieldTooLong {
+                area: "Product Info Area".to_string(),
+                field: field_name.to_string(),
+                len: length,
+            });
         }
+        Ok(())
     }
 
-    fn validate(&self) {
-        self.check_area_length("Product Manufacturer", &self.product_manufacturer);
-        self.check_area_length("Product Name", &self.product_product_name);
-        self.check_area_length("Product Part Number", &self.product_part_number);
-        self.check_area_length("Product Version", &self.product_version);
-        self.check_area_length("Product Serial Number", &self.product_serial_number);
-        self.check_area_length("Product Asset Tag", &self.product_asset_tag);
-        self.check_area_length("Product Fru ID", &self.product_fru_file_id);
-        self.check_area_length("Product Extra", &self.product_extra);
+    fn validate(&self) -> Result<(), FruError> {
+        self.check_area_length("Product Manufacturer", &self.product_manufacturer)?;
+        self.check_area_length("Product Name", &self.product_product_name)?;
+        self.check_area_length("Product Part Number", &self.product_part_number)?;
+        self.check_area_length("Product Version", &self.product_version)?;
+        self.check_area_length("Product Serial Number", &self.product_serial_number)?;
+        self.check_area_length("Product Asset Tag", &self.product_asset_tag)?;
+        self.check_area_length("Product Fru ID", &self.product_fru_file_id)?;
+        self.check_area_length("Product Extra", &self.product_extra)?;
+        for (i, field) in self.product_custom.iter().enumerate() {
+            self.check_area_length(&format!("Product Custom {}", i + 1), &field.value)?;
+        }
+        Ok(())
     }
 
-    fn transfer_as_byte(&self) -> Vec<u8> {
+    fn transfer_as_byte(&self) -> Result<Vec<u8>, FruError> {
         let defaults = vec![
-            FieldConfig { enabled: true, reserved_bytes: 32 },
-            FieldConfig { enabled: true, reserved_bytes: 32 },
-            FieldConfig { enabled: true, reserved_bytes: 32 },
-            FieldConfig { enabled: true, reserved_bytes: 32 },
-            FieldConfig { enabled: true, reserved_bytes: 32 },
-            FieldConfig { enabled: true, reserved_bytes: 32 },
-            FieldConfig { enabled: true, reserved_bytes: 32 },
-            FieldConfig { enabled: true, reserved_bytes: 32 },
+            FieldConfig { enabled: true, reserved_bytes: 32, ..Default::default() },
+            FieldConfig { enabled: true, reserved_bytes: 32, ..Default::default() },
+            FieldConfig { enabled: true, reserved_bytes: 32, ..Default::default() },
+            FieldConfig { enabled: true, reserved_bytes: 32, ..Default::default() },
+            FieldConfig { enabled: true, reserved_bytes: 32, ..Default::default() },
+            FieldConfig { enabled: true, reserved_bytes: 32, ..Default::default() },
+            FieldConfig { enabled: true, reserved_bytes: 32, ..Default::default() },
+            FieldConfig { enabled: true, reserved_bytes: 32, ..Default::default() },
         ];
         self.transfer_with_config(&defaults)
     }
 
-    fn transfer_with_config(&self, field_configs: &[FieldConfig]) -> Vec<u8> {
+    fn transfer_with_config(&self, field_configs: &[FieldConfig]) -> Result<Vec<u8>, FruError> {
         let mut product_area = Vec::new();
 
         // Product Area Header
         product_area.push(0x01); // Format version
         product_area.push(0x00); // Area length
-        product_area.push(0x00); // Language code ( 0 for English )
+        product_area.push(self.language_code); // Language code ( 0 for English )
 
-        let encode_field = |field: &str, config: &FieldConfig| -> Option<Vec<u8>> {
-            if !config.enabled {
-                return None;
+        let mut free_space = 0;
+        let mut encode_field = |name: &str, field: &str, config: &FieldConfig| -> Result<Option<Vec<u8>>, FruError> {
+            let config = FieldConfig { language: self.language_code, ..config.clone() };
+            let encoded = encoding::encode_area_field("Product Info Area", name, field, &config)?;
+            if let Some(f) = &encoded {
+                free_space += encoding::free_space(f, &config);
             }
-            let mut bytes = field.as_bytes().to_vec();
-            if config.reserved_bytes > 0 && bytes.len() < config.reserved_bytes {
-                bytes.resize(config.reserved_bytes, b' ');
-            }
-            let len = bytes.len().min(0x3F);
-            let mut res = vec![0xC0 | len as u8];
-            res.extend_from_slice(&bytes[..len]);
-            Some(res)
+            Ok(encoded)
         };
 
-        if let Some(f) = encode_field(&self.product_manufacturer, &field_configs[0]) { product_area.extend(f); }
-        if let Some(f) = encode_field(&self.product_product_name, &field_configs[1]) { product_area.extend(f); }
-        if let Some(f) = encode_field(&self.product_part_number, &field_configs[2]) { product_area.extend(f); }
-        if let Some(f) = encode_field(&self.product_version, &field_configs[3]) { product_area.extend(f); }
-        if let Some(f) = encode_field(&self.product_serial_number, &field_configs[4]) { product_area.extend(f); }
-        if let Some(f) = encode_field(&self.product_asset_tag, &field_configs[5]) { product_area.extend(f); }
-        if let Some(f) = encode_field(&self.product_fru_file_id, &field_configs[6]) { product_area.extend(f); }
-        if let Some(f) = encode_field(&self.product_extra, &field_configs[7]) { product_area.extend(f); }
+        if let Some(f) = encode_field("Product Manufacturer", &self.product_manufacturer, &field_configs[0])? { product_area.extend(f); }
+        if let Some(f) = encode_field("Product Name", &self.product_product_name, &field_configs[1])? { product_area.extend(f); }
+        if let Some(f) = encode_field("Product Part Number", &self.product_part_number, &field_configs[2])? { product_area.extend(f); }
+        if let Some(f) = encode_field("Product Version", &self.product_version, &field_configs[3])? { product_area.extend(f); }
+        if let Some(f) = encode_field("Product Serial Number", &self.product_serial_number, &field_configs[4])? { product_area.extend(f); }
+        if let Some(f) = encode_field("Product Asset Tag", &self.product_asset_tag, &field_configs[5])? { product_area.extend(f); }
+        if let Some(f) = encode_field("Product Fru ID", &self.product_fru_file_id, &field_configs[6])? { product_area.extend(f); }
+        if let Some(f) = encode_field("Product Extra", &self.product_extra, &field_configs[7])? { product_area.extend(f); }
+        for (i, field) in self.product_custom.iter().enumerate() {
+            if let Some(f) = encode_field(&format!("Product Custom {}", i + 1), &field.value, &field.config)? { product_area.extend(f); }
+        }
 
         product_area.push(0xC1);
+        product_area.resize(product_area.len() + free_space, 0x00); // Reservations kept after the end marker
         product_area.push(0x00); // Checksum placeholder
 
         // fill up the rest area space with 8 Byte
@@ -126,12 +157,11 @@ impl Area for Product {
         product_area[1] = (product_area.len() / 8) as u8;
 
         // Update checksum
-        let checksum =
-            (0x100u16 - (product_area.iter().map(|&b| b as u16).sum::<u16>() % 256)) % 256; // Calculate checksum
+        let checksum = zero_checksum(&product_area); // Calculate checksum
         if let Some(last_byte) = product_area.last_mut() {
-            *last_byte = checksum as u8;
+            *last_byte = checksum;
         }
 
-        product_area
+        Ok(product_area)
     }
 }
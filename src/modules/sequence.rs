@@ -0,0 +1,222 @@
+use super::encoding::MAX_FIELD_LENGTH;
+use crate::ConfigField;
+use anyhow::{Context, Result};
+use chrono::{DateTime, Datelike, Utc};
+use std::collections::{BTreeMap, HashMap};
+use std::path::{Path, PathBuf};
+
+/// Default file persisting the last used sequence number of every pattern.
+pub const DEFAULT_STATE_FILE: &str = "fru_gen_state.json";
+
+/// Tokens recognised inside a pattern, besides `{seq}` / `{seq:05}`.
+const DATE_TOKENS: &[&str] = &["YYYY", "YY", "MM", "DD", "WW"];
+
+/// Whether a config value is a pattern, i.e. contains `{seq...}` or a date token.
+pub fn is_pattern(value: &str) -> bool {
+    value.contains("{seq") || DATE_TOKENS.iter().any(|t| value.contains(&format!("{{{}}}", t)))
+}
+
+/// Whether a pattern draws on the sequence counter (date-only patterns do not).
+pub fn uses_sequence(pattern: &str) -> bool {
+    pattern.contains("{seq")
+}
+
+///
+/// Expand a pattern for one unit.
+///
+/// `{YYYY}`, `{YY}`, `{MM}`, `{DD}` and `{WW}` (ISO week) come from `now` (UTC);
+/// `{seq}` is the sequence number, `{seq:05}` the same zero-padded to five digits (at most 63).
+pub fn expand_pattern(pattern: &str, seq: u64, now: DateTime<Utc>) -> Result<String, String> {
+    let mut out = String::new();
+    let mut rest = pattern;
+    while let Some(start) = rest.find('{') {
+        let end = rest[start..]
+            .find('}')
+            .map(|e| start + e)
+            .ok_or_else(|| format!("unterminated '{{' in pattern '{}'", pattern))?;
+        out.push_str(&rest[..start]);
+        let token = &rest[start + 1..end];
+        let value = match token {
+            "YYYY" => format!("{:04}", now.year()),
+            "YY" => format!("{:02}", now.year() % 100),
+            "MM" => format!("{:02}", now.month()),
+            "DD" => format!("{:02}", now.day()),
+            "WW" => format!("{:02}", now.iso_week().week()),
+            "seq" => seq.to_string(),
+            _ => match token.strip_prefix("seq:") {
+                Some(width) => {
+                    // No field holds more than MAX_FIELD_LENGTH bytes, so wider is never valid
+                    let width: usize = width
+                        .parse()
+                        .ok()
+                        .filter(|&width| width <= MAX_FIELD_LENGTH)
+                        .ok_or_else(|| format!("invalid width in '{{{}}}' of pattern '{}'", token, pattern))?;
+                    format!("{:0width$}", seq, width = width)
+                }
+                None => return Err(format!("unknown token '{{{}}}' in pattern '{}'", token, pattern)),
+            },
+        };
+        out.push_str(&value);
+        rest = &rest[end + 1..];
+    }
+    out.push_str(rest);
+    Ok(out)
+}
+
+fn out_of_numbers(pattern: &str) -> String {
+    format!("pattern '{}' is out of sequence numbers", pattern)
+}
+
+/// Last used sequence number per pattern, persisted as JSON.
+#[derive(Debug, Clone, Default)]
+pub struct SequenceState {
+    path: PathBuf,
+    last: BTreeMap<String, u64>,
+}
+
+impl SequenceState {
+    /// Load the state file; a missing file is an empty state.
+    pub fn load(path: &Path) -> Result<Self> {
+        let last = if path.exists() {
+            let text = std::fs::read_to_string(path)
+                .with_context(|| format!("Could not read sequence state '{}'", path.display()))?;
+            serde_json::from_str(&text)
+                .with_context(|| format!("Sequence state '{}' is not a JSON object of numbers", path.display()))?
+        } else {
+            BTreeMap::new()
+        };
+        Ok(SequenceState { path: path.to_path_buf(), last })
+    }
+
+    /// Write the state through a temporary file, so an interrupted save keeps the old one.
+    pub fn save(&self) -> Result<()> {
+        let temp = self.path.with_extension("tmp");
+        std::fs::write(&temp, serde_json::to_string_pretty(&self.last)?)
+            .and_then(|()| std::fs::rename(&temp, &self.path))
+            .with_context(|| format!("Could not write sequence state '{}'", self.path.display()))
+    }
+
+    pub fn last(&self, pattern: &str) -> Option<u64> {
+        self.last.get(pattern).copied()
+    }
+
+    /// Remember `seq` as used for `pattern`; the stored value never goes down.
+    pub fn record(&mut self, pattern: &str, seq: u64) {
+        let entry = self.last.entry(pattern.to_string()).or_insert(seq);
+        *entry = (*entry).max(seq);
+    }
+}
+
+///
+/// Expand the patterns of a config to the values the next generation run would use.
+///
+/// For commands that only read a config (validate, decode, diff): nothing is committed
+/// and the state is never saved.
+pub fn preview_patterns(config_map: &mut HashMap<String, ConfigField>, state: SequenceState) -> Result<(), String> {
+    SequenceAllocator::new(state, None).expand_unit(config_map, 0).map(|_| ())
+}
+
+///
+/// Hands out sequence numbers for the units of one run.
+///
+/// Unit `n` (0-based) of a run gets `start + n` for every pattern, where `start` is the
+/// explicit start value or one past the pattern's last used number. Only numbers passed
+/// to [`SequenceAllocator::commit`] are recorded, so failed units leave gaps, never reuse.
+#[derive(Debug)]
+pub struct SequenceAllocator {
+    state: SequenceState,
+    start: Option<u64>,
+    starts: HashMap<String, u64>,
+    now: DateTime<Utc>,
+}
+
+impl SequenceAllocator {
+    pub fn new(state: SequenceState, start: Option<u64>) -> Self {
+        SequenceAllocator { state, start, starts: HashMap::new(), now: Utc::now() }
+    }
+
+    fn start_for(&mut self, pattern: &str) -> Result<u64, String> {
+        if let Some(&start) = self.starts.get(pattern) {
+            return Ok(start);
+        }
+        let last = self.state.last(pattern);
+        let start = match (self.start, last) {
+            (Some(start), Some(last)) if start <= last => {
+                return Err(format!(
+                    "start {} would reuse serials of pattern '{}' (last used {})",
+                    start, pattern, last
+                ))
+            }
+            (Some(start), _) => start,
+            (None, Some(last)) => last.checked_add(1).ok_or_else(|| out_of_numbers(pattern))?,
+            (None, None) => 1,
+        };
+        self.starts.insert(pattern.to_string(), start);
+        Ok(start)
+    }
+
+    /// One field with its pattern expanded, or unchanged when it holds none.
+    fn expand_field(
+        &mut self,
+        key: &str,
+        field: &ConfigField,
+        unit: u64,
+        used: &mut Vec<(String, u64)>,
+    ) -> Result<ConfigField, String> {
+        let pattern = field.value();
+        if !is_pattern(&pattern) {
+            return Ok(field.clone());
+        }
+        let seq = if uses_sequence(&pattern) {
+            self.start_for(&pattern)?.checked_add(unit).ok_or_else(|| out_of_numbers(&pattern))?
+        } else {
+            0
+        };
+        let value = expand_pattern(&pattern, seq, self.now).map_err(|e| format!("{}: {}", key, e))?;
+        if uses_sequence(&pattern) {
+            used.push((pattern, seq));
+        }
+        Ok(field.with_value(value))
+    }
+
+    ///
+    /// Expand every pattern field of a unit's config in place, including list items.
+    ///
+    /// Returns the (pattern, sequence number) pairs to commit once the unit was produced.
+    pub fn expand_unit(
+        &mut self,
+        config_map: &mut HashMap<String, ConfigField>,
+        unit: u64,
+    ) -> Result<Vec<(String, u64)>, String> {
+        let mut used = Vec::new();
+        let mut keys: Vec<String> = config_map.keys().cloned().collect();
+        keys.sort();
+        for key in keys {
+            let field = match &config_map[&key] {
+                ConfigField::List(items) => ConfigField::List(
+                    items
+                        .iter()
+                        .map(|item| self.expand_field(&key, item, unit, &mut used))
+                        .collect::<Result<_, _>>()?,
+                ),
+                field => self.expand_field(&key, field, unit, &mut used)?,
+            };
+            config_map.insert(key, field);
+        }
+        Ok(used)
+    }
+
+    pub fn commit(&mut self, used: &[(String, u64)]) {
+        for (pattern, seq) in used {
+            self.state.record(pattern, *seq);
+        }
+    }
+
+    /// Persist the state if any sequence numbers were handed out.
+    pub fn save(&self) -> Result<()> {
+        if self.starts.is_empty() {
+            return Ok(());
+        }
+        self.state.save()
+    }
+}
@@ -0,0 +1,313 @@
+use super::board_area::check_mfg_time;
+use super::decode::decode_fru;
+use super::encoding::{encode_text, Encoding, Padding, MAX_FIELD_LENGTH};
+use super::fru_image::{FruBuilder, FruSize, CUSTOM_KEYS, FIELD_KEYS, PAD_KEYS};
+use super::internal_area::Internal;
+use super::multirecord_area::Record;
+use super::sequence::{preview_patterns, SequenceState};
+use crate::{
+    parse_language_code, try_parse_chassis_type, unknown_config_keys, ConfigField, InternalUseConfig, LayoutConfig,
+    CHASSIS_TYPE_TABLE,
+};
+use std::collections::HashMap;
+use std::fmt;
+use std::path::Path;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Severity {
+    /// The image (or the image a config produces) does not conform to the FRU spec.
+    Error,
+    /// Conforming, but probably not what was intended.
+    Warning,
+}
+
+impl fmt::Display for Severity {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Severity::Error => write!(f, "error"),
+            Severity::Warning => write!(f, "warning"),
+        }
+    }
+}
+
+/// One problem found by validation.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Finding {
+    pub severity: Severity,
+    pub area: String,
+    pub message: String,
+}
+
+impl fmt::Display for Finding {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}: [{}] {}", self.severity, self.area, self.message)
+    }
+}
+
+/// All findings of one validation run.
+#[derive(Debug, Default)]
+pub struct ValidationReport {
+    pub findings: Vec<Finding>,
+}
+
+impl ValidationReport {
+    fn push(&mut self, severity: Severity, area: &str, message: String) {
+        self.findings.push(Finding {
+            severity,
+            area: area.to_string(),
+            message,
+        });
+    }
+
+    pub fn errors(&self) -> usize {
+        self.findings.iter().filter(|f| f.severity == Severity::Error).count()
+    }
+
+    pub fn warnings(&self) -> usize {
+        self.findings.iter().filter(|f| f.severity == Severity::Warning).count()
+    }
+
+    /// True when there are no errors; warnings do not fail validation.
+    pub fn is_ok(&self) -> bool {
+        self.errors() == 0
+    }
+}
+
+fn check_chassis_type(report: &mut ValidationReport, code: u8) {
+    if code as usize >= CHASSIS_TYPE_TABLE.len() {
+        report.push(Severity::Error, "Chassis Info Area", format!(
+            "Chassis type 0x{:02X} is outside the SMBIOS range (0x00-0x{:02X})",
+            code,
+            CHASSIS_TYPE_TABLE.len() - 1
+        ));
+    }
+}
+
+fn area_of_field(key: &str) -> &'static str {
+    if key.starts_with("chassis") {
+        "Chassis Info Area"
+    } else if key.starts_with("board") {
+        "Board Info Area"
+    } else {
+        "Product Info Area"
+    }
+}
+
+/// Check a FRU binary: everything the decoder flags, plus chassis type range, area overlap
+/// and an unspecified manufacturing date.
+pub fn validate_image(data: &[u8]) -> ValidationReport {
+    let mut report = ValidationReport::default();
+    let fru = match decode_fru(data) {
+        Ok(fru) => fru,
+        Err(e) => {
+            report.push(Severity::Error, "Common Header", e.to_string());
+            return report;
+        }
+    };
+
+    for violation in &fru.violations {
+        report.push(Severity::Error, &violation.area, violation.message.clone());
+    }
+
+    if let Some(chassis) = &fru.chassis {
+        check_chassis_type(&mut report, chassis.chassis_type);
+    }
+    if let Some(board) = &fru.board {
+        if board.board_mfg_date_time == "0" {
+            report.push(Severity::Warning, "Board Info Area", "Manufacturing date is unspecified".to_string());
+        }
+    }
+
+    let mut areas: Vec<_> = fru.areas.iter().collect();
+    areas.sort_by_key(|a| a.offset);
+    for pair in areas.windows(2) {
+        if pair[0].offset + pair[0].length > pair[1].offset {
+            report.push(Severity::Error, &pair[0].name, format!(
+                "Area 0x{:X}-0x{:X} overlaps {} at 0x{:X}",
+                pair[0].offset,
+                pair[0].offset + pair[0].length,
+                pair[1].name,
+                pair[1].offset
+            ));
+        }
+    }
+    report
+}
+
+///
+/// Check a loaded config the way generation would use it.
+///
+/// Values that generation silently corrects (unknown chassis names, pre-1996 dates) or
+/// rejects (fields or reservations beyond 63 bytes) are reported as errors. Keys generation ignores are
+/// warnings, or errors when `strict`. When the config itself is clean, the image it
+/// produces at `size` (with `internal_use` and `layout`, if given) is built and checked
+/// as well; a fixed size leaving little room for field growth is a warning.
+pub fn validate_config(
+    config_map: &HashMap<String, ConfigField>,
+    records: Vec<Record>,
+    internal_use: Option<&InternalUseConfig>,
+    layout: Option<&LayoutConfig>,
+    size: impl Into<FruSize>,
+    strict: bool,
+) -> ValidationReport {
+    let mut report = ValidationReport::default();
+    let size = size.into();
+
+    let severity = if strict { Severity::Error } else { Severity::Warning };
+    for message in unknown_config_keys(config_map) {
+        report.push(severity, "Config", message);
+    }
+
+    if let Some(field) = config_map.get("chassis_type") {
+        match try_parse_chassis_type(&field.value()) {
+            Ok(code) => check_chassis_type(&mut report, code),
+            Err(e) => report.push(Severity::Error, "Chassis Info Area", e.to_string()),
+        }
+    }
+
+    match config_map.get("board_mfg_date_time").map(|f| check_mfg_time(&f.value())) {
+        Some(Ok(0)) | None => {
+            report.push(Severity::Warning, "Board Info Area", "Manufacturing date is unspecified".to_string())
+        }
+        Some(Ok(_)) => {}
+        Some(Err(e)) => report.push(Severity::Error, "Board Info Area", format!("Board_mfg_date_time: {}", e)),
+    }
+
+    let mut language = |key: &str, area: &str| -> u8 {
+        let value = config_map.get(key).map(|f| f.value()).unwrap_or_default();
+        parse_language_code(&value).unwrap_or_else(|| {
+            report.push(Severity::Error, area, format!("Unknown language '{}' for '{}'", value, key));
+            0
+        })
+    };
+    let board_language = language("board_language", "Board Info Area");
+    let product_language = language("product_language", "Product Info Area");
+
+    // Custom list items are checked like the predefined fields, as `board_custom[0]` etc.
+    let mut fields: Vec<(String, ConfigField)> = FIELD_KEYS
+        .iter()
+        .filter(|k| !k.contains("type") && !k.contains("mfg"))
+        .filter_map(|key| config_map.get(*key).map(|field| (key.to_string(), field.clone())))
+        .collect();
+    for key in CUSTOM_KEYS {
+        let items = config_map.get(*key).map(|f| f.items()).unwrap_or_default();
+        fields.extend(items.into_iter().enumerate().map(|(i, item)| (format!("{}[{}]", key, i), item)));
+    }
+
+    for key in PAD_KEYS {
+        if let Some(name) = config_map.get(*key).map(|f| f.value()).filter(|name| Padding::parse(name).is_none()) {
+            report.push(Severity::Error, area_of_field(key), format!("Unknown padding '{}' for '{}'", name, key));
+        }
+    }
+
+    for (key, field) in &fields {
+        let area = area_of_field(key);
+        if let Some(name) = field.pad().filter(|name| Padding::parse(name).is_none()) {
+            report.push(Severity::Error, area, format!("Unknown padding '{}' for '{}'", name, key));
+        }
+        if let Some(reserved) = field.reserve_bytes().filter(|&reserved| reserved > MAX_FIELD_LENGTH) {
+            report.push(Severity::Error, area, format!(
+                "Field '{}' reserves {} bytes, more than the 63 a type/length byte allows",
+                key, reserved
+            ));
+        }
+        let encoding = match field.encoding() {
+            Some(name) => match Encoding::parse(&name) {
+                Some(encoding) => encoding,
+                None => {
+                    report.push(Severity::Error, area, format!("Unknown encoding '{}' for '{}'", name, key));
+                    continue;
+                }
+            },
+            None => Encoding::default(),
+        };
+        let language = match area {
+            "Board Info Area" => board_language,
+            "Product Info Area" => product_language,
+            _ => 0,
+        };
+        match encode_text(&field.value(), encoding, language) {
+            Ok((_, bytes)) if bytes.len() > MAX_FIELD_LENGTH => report.push(Severity::Error, area, format!(
+                "Field '{}' encodes to {} bytes, more than the 63 a type/length byte allows",
+                key,
+                bytes.len()
+            )),
+            Ok(_) => {}
+            Err(e) => report.push(Severity::Error, area, format!("Field '{}': {}", key, e)),
+        }
+    }
+
+    if let Some(Err(e)) = internal_use.map(Internal::from_config) {
+        report.push(Severity::Error, "Internal Use Area", e.to_string());
+    }
+
+    if !report.is_ok() {
+        return report;
+    }
+
+    let image = FruBuilder::from_config(config_map, records)
+        .and_then(|builder| builder.internal_use(internal_use))
+        .and_then(|builder| builder.layout(layout).size(size).build());
+    match image {
+        Ok(image) => {
+            if let Some(warning) = image.headroom_warning().filter(|_| size != FruSize::Auto) {
+                report.push(Severity::Warning, "Image", warning);
+            }
+            for finding in validate_image(&image.data).findings {
+                if !report.findings.contains(&finding) {
+                    report.findings.push(finding);
+                }
+            }
+        }
+        Err(e) => report.push(Severity::Error, "Image", e.to_string()),
+    }
+    report
+}
+
+/// Load a config file and validate it; multi-record, internal use and layout errors become
+/// findings. Serial patterns are checked with the values the next run would give them,
+/// read from `state_file`.
+pub fn validate_config_file(
+    config_path: &str,
+    size: FruSize,
+    strict: bool,
+    state_file: &Path,
+) -> anyhow::Result<ValidationReport> {
+    let mut config_map = crate::load_config(config_path)?;
+    let mut expanded = config_map.clone();
+    let pattern_error = match preview_patterns(&mut expanded, SequenceState::load(state_file)?) {
+        Ok(()) => {
+            config_map = expanded;
+            None
+        }
+        Err(e) => Some(e),
+    };
+    let (records, record_error) = match crate::load_multirecords(config_path) {
+        Ok(records) => (records, None),
+        Err(e) => (Vec::new(), Some(e)),
+    };
+    let (internal_use, internal_error) = match crate::load_internal_use(config_path) {
+        Ok(internal_use) => (internal_use, None),
+        Err(e) => (None, Some(e)),
+    };
+
+    let (layout, layout_error) = match crate::load_layout(config_path) {
+        Ok(layout) => (layout, None),
+        Err(e) => (None, Some(e)),
+    };
+
+    let mut report = validate_config(&config_map, records, internal_use.as_ref(), layout.as_ref(), size, strict);
+    if let Some(e) = pattern_error {
+        report.push(Severity::Error, "Config", e);
+    }
+    if let Some(e) = record_error {
+        report.push(Severity::Error, "MultiRecord Area", e.to_string());
+    }
+    if let Some(e) = internal_error {
+        report.push(Severity::Error, "Internal Use Area", e.to_string());
+    }
+    if let Some(e) = layout_error {
+        report.push(Severity::Error, "Layout", e.to_string());
+    }
+    Ok(report)
+}
@@ -82,3 +82,459 @@ fn test_invalid_config_path() {
        .failure()
        .stderr(predicate::str::contains("Configuration file 'non_existent_file.toml' not found"));
 }
+
+#[test]
+fn test_decode_generated_binary() {
+    let dir = tempdir().unwrap();
+    let config_path = dir.path().join("decode.toml");
+    let output_path = dir.path().join("decode.bin");
+
+    let mut build_cmd = Command::cargo_bin("fru_gen").unwrap();
+    build_cmd.arg("-b").arg(config_path.to_str().unwrap()).assert().success();
+
+    let mut gen_cmd = Command::cargo_bin("fru_gen").unwrap();
+    gen_cmd.arg("-r").arg(config_path.to_str().unwrap())
+       .arg("-o").arg(output_path.to_str().unwrap())
+       .assert()
+       .success();
+
+    let mut cmd = Command::cargo_bin("fru_gen").unwrap();
+    cmd.arg("decode").arg(output_path.to_str().unwrap())
+       .assert()
+       .success()
+       .stdout(predicate::str::contains(" Chassis Type          : Rack Mount Chassis"))
+       .stdout(predicate::str::contains(" Board Mfg Date        : Unspecified"))
+       .stdout(predicate::str::contains(" Product Asset Tag     : AssetTag"))
+       .stdout(predicate::str::contains("Product FRU ID").not());
+}
+
+#[test]
+fn test_decode_json_output() {
+    let dir = tempdir().unwrap();
+    let toml_path = dir.path().join("decode.toml");
+    let bin_path = dir.path().join("decode.bin");
+    let json_path = dir.path().join("decode.json");
+
+    let mut build_cmd = Command::cargo_bin("fru_gen").unwrap();
+    build_cmd.arg("-b").arg(toml_path.to_str().unwrap()).assert().success();
+    let mut gen_cmd = Command::cargo_bin("fru_gen").unwrap();
+    gen_cmd.arg("-r").arg(toml_path.to_str().unwrap())
+       .arg("-o").arg(bin_path.to_str().unwrap())
+       .assert()
+       .success();
+    let mut export_cmd = Command::cargo_bin("fru_gen").unwrap();
+    export_cmd.arg("export").arg(bin_path.to_str().unwrap())
+       .arg("-o").arg(json_path.to_str().unwrap())
+       .assert()
+       .success();
+
+    // The JSON config regenerates the image, so both dumps agree
+    let dump = |file: &std::path::Path| {
+        let mut cmd = Command::cargo_bin("fru_gen").unwrap();
+        let output = cmd.arg("decode").arg(file.to_str().unwrap()).arg("--json").output().unwrap();
+        assert!(output.status.success());
+        serde_json::from_slice::<serde_json::Value>(&output.stdout).unwrap()
+    };
+    let json = dump(&json_path);
+    assert_eq!(json, dump(&bin_path));
+
+    assert_eq!(json["compliant"], true);
+    assert_eq!(json["header"]["chassis_info_offset"], 16);
+    let areas = json["areas"].as_array().unwrap();
+    let board = areas.iter().find(|a| a["name"] == "Board Info Area").unwrap();
+    assert_eq!(board["offset"], json["header"]["board_info_offset"]);
+    assert!(board["checksum"].is_u64());
+    let product = areas.iter().find(|a| a["name"] == "Product Info Area").unwrap();
+    let tag = product["fields"].as_array().unwrap().iter().find(|f| f["key"] == "product_asset_tag").unwrap();
+    assert_eq!(tag["value"].as_str().unwrap().trim_end(), "AssetTag");
+    assert!(tag["data"].as_str().unwrap().starts_with("4173736574546167"));
+    let chassis = areas.iter().find(|a| a["name"] == "Chassis Info Area").unwrap();
+    assert_eq!(chassis["chassis_type_name"], "Rack Mount Chassis");
+}
+
+#[test]
+fn test_validate_config_and_binary() {
+    let dir = tempdir().unwrap();
+    let config_path = dir.path().join("validate.toml");
+    let output_path = dir.path().join("validate.bin");
+
+    let mut build_cmd = Command::cargo_bin("fru_gen").unwrap();
+    build_cmd.arg("-b").arg(config_path.to_str().unwrap()).assert().success();
+
+    let mut cmd = Command::cargo_bin("fru_gen").unwrap();
+    cmd.arg("validate").arg(config_path.to_str().unwrap())
+       .assert()
+       .success()
+       .stdout(predicate::str::contains("0 error(s), 1 warning(s)"));
+
+    let mut gen_cmd = Command::cargo_bin("fru_gen").unwrap();
+    gen_cmd.arg("-r").arg(config_path.to_str().unwrap())
+       .arg("-o").arg(output_path.to_str().unwrap())
+       .assert()
+       .success();
+
+    // Corrupt the common header checksum
+    let mut image = fs::read(&output_path).unwrap();
+    image[7] = image[7].wrapping_add(1);
+    fs::write(&output_path, image).unwrap();
+
+    let mut cmd = Command::cargo_bin("fru_gen").unwrap();
+    cmd.arg("validate").arg(output_path.to_str().unwrap())
+       .assert()
+       .failure()
+       .stdout(predicate::str::contains("error: [Common Header] Checksum mismatch"))
+       .stderr(predicate::str::contains("does not conform"));
+}
+
+#[test]
+fn test_validate_reports_config_typos() {
+    let dir = tempdir().unwrap();
+    let config_path = dir.path().join("typos.toml");
+    fs::write(&config_path, r#"
+Chassis_type = "Rack Mount"
+Board_mfg_date_time = "19950101000000"
+Board_Manufacturer = "MFG"
+"#).unwrap();
+
+    let mut cmd = Command::cargo_bin("fru_gen").unwrap();
+    cmd.arg("validate").arg(config_path.to_str().unwrap())
+       .assert()
+       .failure()
+       .stdout(predicate::str::contains("Unknown chassis type 'Rack Mount'"))
+       .stdout(predicate::str::contains("before 1996-01-01"))
+       .stdout(predicate::str::contains("2 error(s)"));
+}
+
+#[test]
+fn test_strict_rejects_misspelled_config_keys() {
+    let dir = tempdir().unwrap();
+    let config_path = dir.path().join("misspelled.toml");
+    let output_path = dir.path().join("misspelled.bin");
+    fs::write(&config_path, "Board_Manufacturer = \"MFG\"\nBoard_Serial_Num = \"SN1\"\n").unwrap();
+
+    let mut cmd = Command::cargo_bin("fru_gen").unwrap();
+    cmd.arg("-r").arg(config_path.to_str().unwrap())
+       .arg("-o").arg(output_path.to_str().unwrap())
+       .assert()
+       .success()
+       .stderr(predicate::str::contains("Unknown config key 'board_serial_num' (did you mean 'board_serial_number'?)"));
+    fs::remove_file(&output_path).unwrap();
+
+    let mut strict_cmd = Command::cargo_bin("fru_gen").unwrap();
+    strict_cmd.arg("--strict")
+       .arg("-r").arg(config_path.to_str().unwrap())
+       .arg("-o").arg(output_path.to_str().unwrap())
+       .assert()
+       .failure()
+       .stderr(predicate::str::contains("did you mean 'board_serial_number'?"));
+    assert!(!output_path.exists());
+
+    let mut validate_cmd = Command::cargo_bin("fru_gen").unwrap();
+    validate_cmd.arg("--strict").arg("validate").arg(config_path.to_str().unwrap())
+       .assert()
+       .failure()
+       .stdout(predicate::str::contains("error: [Config] Unknown config key 'board_serial_num'"));
+}
+
+#[test]
+fn test_batch_generation_from_csv() {
+    let dir = tempdir().unwrap();
+    let config_path = dir.path().join("base.toml");
+    let manifest_path = dir.path().join("units.csv");
+    let out_dir = dir.path().join("lot");
+    let report_path = dir.path().join("report.json");
+
+    let mut build_cmd = Command::cargo_bin("fru_gen").unwrap();
+    build_cmd.arg("-b").arg(config_path.to_str().unwrap()).assert().success();
+    fs::write(&manifest_path, "Board_Serial_Number,Product_Serial_Number\nSN001,PSN001\nSN002,PSN002\nSN003,\n").unwrap();
+
+    let mut cmd = Command::cargo_bin("fru_gen").unwrap();
+    cmd.arg("-r").arg(config_path.to_str().unwrap())
+       .arg("--size").arg("1024")
+       .arg("batch").arg(manifest_path.to_str().unwrap())
+       .arg("--out-dir").arg(out_dir.to_str().unwrap())
+       .arg("--report").arg(report_path.to_str().unwrap())
+       .assert()
+       .success()
+       .stdout(predicate::str::contains("Produced 3 image(s), 0 row(s) failed"));
+
+    assert_eq!(fs::metadata(out_dir.join("SN002.bin")).unwrap().len(), 1024);
+    let mut decode_cmd = Command::cargo_bin("fru_gen").unwrap();
+    decode_cmd.arg("decode").arg(out_dir.join("SN002.bin").to_str().unwrap())
+       .assert()
+       .success()
+       .stdout(predicate::str::contains("PSN002"));
+
+    let report = fs::read_to_string(report_path).unwrap();
+    assert!(report.contains("\"produced\": 3"));
+}
+
+#[test]
+fn test_batch_count_continues_serial_sequence() {
+    let dir = tempdir().unwrap();
+    let config_path = dir.path().join("base.toml");
+    let state_path = dir.path().join("serials.json");
+    let out_dir = dir.path().join("lot");
+
+    let mut build_cmd = Command::cargo_bin("fru_gen").unwrap();
+    build_cmd.arg("-b").arg(config_path.to_str().unwrap()).assert().success();
+    let template = fs::read_to_string(&config_path).unwrap();
+    let config: String = template
+        .lines()
+        .map(|line| if line.starts_with("Board_Serial_Number") {
+            "Board_Serial_Number = \"SN{seq:04}\"".to_string()
+        } else {
+            line.to_string()
+        })
+        .collect::<Vec<_>>()
+        .join("\n");
+    fs::write(&config_path, config).unwrap();
+
+    let batch = |extra: &[&str]| {
+        let mut cmd = Command::cargo_bin("fru_gen").unwrap();
+        cmd.arg("-r").arg(config_path.to_str().unwrap())
+           .arg("--size").arg("1024")
+           .arg("--state").arg(state_path.to_str().unwrap())
+           .arg("batch")
+           .arg("--out-dir").arg(out_dir.to_str().unwrap())
+           .args(extra)
+           .assert()
+    };
+
+    batch(&["--count", "2"]).success();
+    batch(&["--count", "1"]).success().stdout(predicate::str::contains("SN0003.bin"));
+    assert!(out_dir.join("SN0001.bin").exists());
+    assert!(fs::read_to_string(&state_path).unwrap().contains("\"SN{seq:04}\": 3"));
+
+    batch(&["--count", "1", "--start", "2"])
+        .failure()
+        .stdout(predicate::str::contains("would reuse serials"));
+}
+
+#[test]
+fn test_read_only_commands_expand_serial_patterns() {
+    let dir = tempdir().unwrap();
+    let config_path = dir.path().join("base.toml");
+    let state_path = dir.path().join("serials.json");
+    let bin_path = dir.path().join("unit.bin");
+
+    let mut build_cmd = Command::cargo_bin("fru_gen").unwrap();
+    build_cmd.arg("-b").arg(config_path.to_str().unwrap()).assert().success();
+    let template = fs::read_to_string(&config_path).unwrap();
+    let config: String = template
+        .lines()
+        .map(|line| if line.starts_with("Board_Serial_Number") {
+            "Board_Serial_Number = { value = \"SN{YY}{WW}{seq:05}\", encoding = \"6bit_ascii\" }".to_string()
+        } else {
+            line.to_string()
+        })
+        .collect::<Vec<_>>()
+        .join("\n");
+    fs::write(&config_path, config).unwrap();
+
+    let run = |args: &[&str]| {
+        let mut cmd = Command::cargo_bin("fru_gen").unwrap();
+        cmd.arg("--state").arg(state_path.to_str().unwrap()).args(args).assert()
+    };
+
+    run(&["validate", config_path.to_str().unwrap()])
+        .success()
+        .stdout(predicate::str::contains("0 error(s)"));
+    assert!(!state_path.exists());
+
+    // Before generation, the config previews the serial the next run hands out
+    run(&["-r", config_path.to_str().unwrap(), "-o", bin_path.to_str().unwrap()]).success();
+    run(&["diff", bin_path.to_str().unwrap(), config_path.to_str().unwrap()])
+        .failure()
+        .stdout(predicate::str::contains("00001\" -> \"SN"))
+        .stdout(predicate::str::contains("{seq").not());
+    run(&["decode", config_path.to_str().unwrap()])
+        .success()
+        .stdout(predicate::str::contains("00002"));
+    assert!(fs::read_to_string(&state_path).unwrap().contains(": 1"));
+}
+
+#[test]
+fn test_patch_binary_field() {
+    let dir = tempdir().unwrap();
+    let config_path = dir.path().join("base.toml");
+    let bin_path = dir.path().join("fru.bin");
+    let patched_path = dir.path().join("patched.bin");
+
+    let mut build_cmd = Command::cargo_bin("fru_gen").unwrap();
+    build_cmd.arg("-b").arg(config_path.to_str().unwrap()).assert().success();
+    let mut gen_cmd = Command::cargo_bin("fru_gen").unwrap();
+    gen_cmd.arg("-r").arg(config_path.to_str().unwrap())
+       .arg("-o").arg(bin_path.to_str().unwrap())
+       .assert()
+       .success();
+
+    let mut cmd = Command::cargo_bin("fru_gen").unwrap();
+    cmd.arg("patch").arg(bin_path.to_str().unwrap())
+       .arg("--set").arg("product_asset_tag=ABC123")
+       .arg("--output").arg(patched_path.to_str().unwrap())
+       .assert()
+       .success()
+       .stdout(predicate::str::contains("Patched 1 field(s)"));
+
+    let mut decode_cmd = Command::cargo_bin("fru_gen").unwrap();
+    decode_cmd.arg("decode").arg(patched_path.to_str().unwrap())
+       .assert()
+       .success()
+       .stdout(predicate::str::contains("ABC123"))
+       .stderr(predicate::str::is_empty());
+
+    let mut bad_cmd = Command::cargo_bin("fru_gen").unwrap();
+    bad_cmd.arg("patch").arg(bin_path.to_str().unwrap())
+       .arg("--set").arg("product_colour=red")
+       .assert()
+       .failure()
+       .stderr(predicate::str::contains("Unknown field 'product_colour'"));
+}
+
+#[test]
+fn test_diff_binary_against_config() {
+    let dir = tempdir().unwrap();
+    let config_path = dir.path().join("base.toml");
+    let bin_path = dir.path().join("fru.bin");
+
+    let mut build_cmd = Command::cargo_bin("fru_gen").unwrap();
+    build_cmd.arg("-b").arg(config_path.to_str().unwrap()).assert().success();
+    let mut gen_cmd = Command::cargo_bin("fru_gen").unwrap();
+    gen_cmd.arg("-r").arg(config_path.to_str().unwrap())
+       .arg("-o").arg(bin_path.to_str().unwrap())
+       .assert()
+       .success();
+
+    let mut same_cmd = Command::cargo_bin("fru_gen").unwrap();
+    same_cmd.arg("diff").arg(bin_path.to_str().unwrap()).arg(config_path.to_str().unwrap())
+       .assert()
+       .success()
+       .stdout(predicate::str::contains("0 field difference(s)"));
+
+    let mut patch_cmd = Command::cargo_bin("fru_gen").unwrap();
+    patch_cmd.arg("patch").arg(bin_path.to_str().unwrap())
+       .arg("--set").arg("board_serial_number=SN99999")
+       .assert()
+       .success();
+
+    let mut diff_cmd = Command::cargo_bin("fru_gen").unwrap();
+    diff_cmd.arg("diff").arg(config_path.to_str().unwrap()).arg(bin_path.to_str().unwrap())
+       .assert()
+       .failure()
+       .stdout(predicate::str::contains("[Board Info Area] Board Serial Number: \"SN12345\" -> \"SN99999\""))
+       .stdout(predicate::str::contains("Differing bytes:"));
+}
+
+#[test]
+fn test_export_binary_to_config() {
+    let dir = tempdir().unwrap();
+    let config_path = dir.path().join("base.toml");
+    let bin_path = dir.path().join("fru.bin");
+    let exported_path = dir.path().join("recovered.yaml");
+
+    let mut build_cmd = Command::cargo_bin("fru_gen").unwrap();
+    build_cmd.arg("-b").arg(config_path.to_str().unwrap()).assert().success();
+    let mut gen_cmd = Command::cargo_bin("fru_gen").unwrap();
+    gen_cmd.arg("-r").arg(config_path.to_str().unwrap())
+       .arg("-o").arg(bin_path.to_str().unwrap())
+       .assert()
+       .success();
+
+    let mut cmd = Command::cargo_bin("fru_gen").unwrap();
+    cmd.arg("export").arg(bin_path.to_str().unwrap())
+       .arg("-o").arg(exported_path.to_str().unwrap())
+       .assert()
+       .success();
+    let exported = fs::read_to_string(&exported_path).unwrap();
+    assert!(exported.contains("Board_Manufacturer: \"qwfqwfg\""));
+
+    let mut diff_cmd = Command::cargo_bin("fru_gen").unwrap();
+    diff_cmd.arg("diff").arg(bin_path.to_str().unwrap()).arg(exported_path.to_str().unwrap())
+       .assert()
+       .success();
+}
+
+#[test]
+fn test_output_formats() {
+    let dir = tempdir().unwrap();
+    let toml_path = dir.path().join("formats.toml");
+    let bin_path = dir.path().join("formats.bin");
+    let hex_path = dir.path().join("formats.hex");
+    let srec_path = dir.path().join("formats.out");
+    let header_path = dir.path().join("fru.h");
+
+    let mut build_cmd = Command::cargo_bin("fru_gen").unwrap();
+    build_cmd.arg("-b").arg(toml_path.to_str().unwrap()).assert().success();
+    let generate = |output: &std::path::Path, extra: &[&str]| {
+        let mut cmd = Command::cargo_bin("fru_gen").unwrap();
+        cmd.args(extra)
+           .arg("-r").arg(toml_path.to_str().unwrap())
+           .arg("-o").arg(output.to_str().unwrap())
+           .assert()
+           .success();
+    };
+    generate(&bin_path, &[]);
+    generate(&hex_path, &["--base-address", "0x1000"]);
+    generate(&srec_path, &["--format", "srec"]);
+    generate(&header_path, &[]);
+
+    let hex = fs::read_to_string(&hex_path).unwrap();
+    assert!(hex.starts_with(":10100000"), "{}", hex);
+    assert!(fs::read_to_string(&srec_path).unwrap().starts_with("S0"));
+    assert!(fs::read_to_string(&header_path).unwrap().contains("static const uint8_t fru[4096] = {"));
+
+    // HEX and S-record files decode and compare like the raw binary
+    for path in [&hex_path, &srec_path] {
+        let mut diff_cmd = Command::cargo_bin("fru_gen").unwrap();
+        diff_cmd.arg("diff").arg(bin_path.to_str().unwrap()).arg(path.to_str().unwrap())
+           .assert()
+           .success();
+    }
+
+    // Patching keeps the file's format and base address
+    let mut patch_cmd = Command::cargo_bin("fru_gen").unwrap();
+    patch_cmd.arg("patch").arg(hex_path.to_str().unwrap())
+       .arg("--set").arg("product_asset_tag=HEX42")
+       .assert()
+       .success();
+    assert!(fs::read_to_string(&hex_path).unwrap().starts_with(":10100000"));
+    let mut decode_cmd = Command::cargo_bin("fru_gen").unwrap();
+    decode_cmd.arg("decode").arg(hex_path.to_str().unwrap())
+       .assert()
+       .success()
+       .stdout(predicate::str::contains("HEX42"));
+
+    let mut bad_cmd = Command::cargo_bin("fru_gen").unwrap();
+    bad_cmd.arg("--format").arg("xml")
+       .arg("-r").arg(toml_path.to_str().unwrap())
+       .assert()
+       .failure()
+       .stderr(predicate::str::contains("unknown format 'xml'"));
+}
+
+#[test]
+fn test_size_presets_and_auto() {
+    let dir = tempdir().unwrap();
+    let toml_path = dir.path().join("size.toml");
+    let bin_path = dir.path().join("size.bin");
+
+    let mut build_cmd = Command::cargo_bin("fru_gen").unwrap();
+    build_cmd.arg("-b").arg(toml_path.to_str().unwrap()).assert().success();
+    let generate = |size: &str| {
+        let mut cmd = Command::cargo_bin("fru_gen").unwrap();
+        cmd.arg("-r").arg(toml_path.to_str().unwrap())
+           .arg("-o").arg(bin_path.to_str().unwrap())
+           .arg("--size").arg(size)
+           .assert()
+    };
+
+    generate("24C16").success().stdout(predicate::str::contains("Fru Size: 2048 bytes (24C16)"));
+    assert_eq!(fs::metadata(&bin_path).unwrap().len(), 2048);
+    generate("auto").success().stdout(predicate::str::contains("(auto)"));
+    let auto_len = fs::metadata(&bin_path).unwrap().len();
+    assert!(auto_len < 2048 && auto_len.is_multiple_of(8));
+
+    generate("24C02").failure().stderr(predicate::str::contains("Hint: '--size 24C08'"));
+    generate(&(auto_len + 8).to_string()).success().stderr(predicate::str::contains("left free for field growth"));
+}
@@ -1,4 +1,5 @@
-use fru_gen::{load_config, parse_chassis_type, parser_hex_string};
+use fru_gen::{language_name, load_config, load_internal_use, load_layout, read_config_section, suggest_key, unknown_config_keys, load_multirecords, parse_chassis_type, parse_hex_bytes, parse_language_code, parser_hex_string, try_parse_chassis_type, AreaSlot, FruError};
+use fru_gen::modules::multirecord_area::Record;
 use std::fs::File;
 use std::io::Write;
 use tempfile::tempdir;
@@ -24,6 +25,28 @@ fn test_parse_chassis_type() {
     assert_eq!(parse_chassis_type("Super Computer"), 2);
 }
 
+#[test]
+fn test_try_parse_chassis_type() {
+    assert_eq!(try_parse_chassis_type("Rack Mount Chassis"), Ok(17));
+    assert_eq!(try_parse_chassis_type("0x17"), Ok(0x17));
+    assert_eq!(
+        try_parse_chassis_type("Super Computer"),
+        Err(FruError::InvalidChassisType("Super Computer".to_string()))
+    );
+}
+
+#[test]
+fn test_parse_language_code() {
+    assert_eq!(parse_language_code("en"), Some(0));
+    assert_eq!(parse_language_code(""), Some(0));
+    // ISO codes win over hex ("de" would otherwise be 0xDE)
+    assert_eq!(parse_language_code("de"), Some(22));
+    assert_eq!(parse_language_code("  Japanese "), Some(53));
+    assert_eq!(parse_language_code("0x87"), Some(0x87));
+    assert_eq!(parse_language_code("Klingon"), None);
+    assert_eq!(language_name(135), "Chinese");
+}
+
 #[test]
 fn test_load_config_toml() {
     let dir = tempdir().unwrap();
@@ -49,3 +72,200 @@ fn test_load_config_yaml() {
     assert_eq!(config.get("key").unwrap().value(), "value");
     assert_eq!(config.get("chassis_part_number").unwrap().value(), "CPN123");
 }
+
+#[test]
+fn test_load_config_json() {
+    let dir = tempdir().unwrap();
+    let file_path = dir.path().join("test.json");
+    let mut file = File::create(&file_path).unwrap();
+    writeln!(file, r#"{{"Board_Manufacturer": "MyMfg", "Product_Version": {{"value": "1.0", "encoding": "bcd_plus"}},"#).unwrap();
+    writeln!(file, r#" "multirecord": [{{"type": "dc_output", "output_number": 2, "nominal_voltage": 500}}]}}"#).unwrap();
+
+    let config = load_config(file_path.to_str().unwrap()).unwrap();
+    assert!(!config.contains_key("multirecord"));
+    assert_eq!(config.get("board_manufacturer").unwrap().value(), "MyMfg");
+    assert_eq!(config.get("product_version").unwrap().value(), "1.0");
+
+    let records = load_multirecords(file_path.to_str().unwrap()).unwrap();
+    assert!(matches!(&records[0], Record::DcOutput(dc) if dc.output_number == 2 && dc.nominal_voltage == 500));
+}
+
+#[test]
+fn test_load_config_sections() {
+    let dir = tempdir().unwrap();
+    let file_path = dir.path().join("sections.toml");
+    std::fs::write(&file_path, r#"
+Chassis_type = "Blade"
+
+[board]
+Manufacturer = "MyMfg"
+mfg_date_time = "20240102030400"
+
+[product]
+name = "P1"
+asset_tag = { value = "Tag", reserve_bytes = 8 }
+
+[[multirecord]]
+type = "dc_output"
+output_number = 1
+"#).unwrap();
+    let path = file_path.to_str().unwrap();
+
+    let config = load_config(path).unwrap();
+    assert_eq!(config.len(), 5);
+    assert_eq!(config.get("chassis_type").unwrap().value(), "Blade");
+    assert_eq!(config.get("board_manufacturer").unwrap().value(), "MyMfg");
+    assert_eq!(config.get("product_name").unwrap().value(), "P1");
+    assert_eq!(config.get("product_asset_tag").unwrap().reserve_bytes(), Some(8));
+    assert_eq!(load_multirecords(path).unwrap().len(), 1);
+
+    let product = read_config_section(path, "product").unwrap();
+    assert_eq!(product.len(), 2);
+    assert_eq!(product.get("product_name").unwrap().value(), "P1");
+    assert!(read_config_section(path, "chassis").is_err());
+}
+
+#[test]
+fn test_load_config_sections_rejects_unknown_and_duplicate_keys() {
+    let dir = tempdir().unwrap();
+    let file_path = dir.path().join("typo.yaml");
+    std::fs::write(&file_path, "board:\n  manufacter: MyMfg\n").unwrap();
+    let err = load_config(file_path.to_str().unwrap()).unwrap_err().to_string();
+    assert!(err.contains("Unknown key 'manufacter' in [board]"), "{}", err);
+    assert!(err.contains("manufacturer"), "{}", err);
+
+    let file_path = dir.path().join("twice.toml");
+    std::fs::write(&file_path, "Product_Name = \"A\"\n[product]\nname = \"B\"\n").unwrap();
+    let err = load_config(file_path.to_str().unwrap()).unwrap_err().to_string();
+    assert!(err.contains("'product_name' is set both"), "{}", err);
+}
+
+#[test]
+fn test_unknown_config_keys_suggest_known_ones() {
+    assert_eq!(suggest_key("Board_Serial_Num"), Some("board_serial_number"));
+    assert_eq!(suggest_key("product_asettag"), Some("product_asset_tag"));
+    assert_eq!(suggest_key("color"), None);
+
+    let dir = tempdir().unwrap();
+    let file_path = dir.path().join("typos.json");
+    std::fs::write(&file_path, r#"{"_comment": "exported", "Board_Serial_Num": "SN1", "Chassis_Type": "Blade", "color": "red"}"#).unwrap();
+    let config = load_config(file_path.to_str().unwrap()).unwrap();
+    assert_eq!(unknown_config_keys(&config), vec![
+        "Unknown config key 'board_serial_num' (did you mean 'board_serial_number'?)".to_string(),
+        "Unknown config key 'color'".to_string(),
+    ]);
+}
+
+#[test]
+fn test_load_multirecords() {
+    let dir = tempdir().unwrap();
+    let file_path = dir.path().join("multirecord.toml");
+    let mut file = File::create(&file_path).unwrap();
+    writeln!(file, "Board_Manufacturer = \"MyMfg\"").unwrap();
+    writeln!(file, "[[multirecord]]").unwrap();
+    writeln!(file, "type = \"dc_output\"").unwrap();
+    writeln!(file, "output_number = 3").unwrap();
+    writeln!(file, "nominal_voltage = 1200").unwrap();
+
+    let config = load_config(file_path.to_str().unwrap()).unwrap();
+    assert!(!config.contains_key("multirecord"));
+    assert_eq!(config.get("board_manufacturer").unwrap().value(), "MyMfg");
+
+    let records = load_multirecords(file_path.to_str().unwrap()).unwrap();
+    assert_eq!(records.len(), 1);
+    match &records[0] {
+        Record::DcOutput(dc) => {
+            assert_eq!(dc.output_number, 3);
+            assert_eq!(dc.nominal_voltage, 1200);
+        }
+        other => panic!("unexpected record {:?}", other),
+    }
+}
+
+#[test]
+fn test_load_multirecords_rejects_invalid_records() {
+    let dir = tempdir().unwrap();
+    let file_path = dir.path().join("management.toml");
+    let mut file = File::create(&file_path).unwrap();
+    writeln!(file, "[[multirecord]]").unwrap();
+    writeln!(file, "type = \"management_access\"").unwrap();
+    writeln!(file, "sub_record = \"system_url\"").unwrap();
+    writeln!(file, "value = \"http://x\"").unwrap();
+
+    let err = load_multirecords(file_path.to_str().unwrap()).unwrap_err();
+    assert!(err.to_string().contains("System URL must be 16-256 bytes"));
+}
+
+#[test]
+fn test_parse_hex_bytes() {
+    assert_eq!(parse_hex_bytes("0x001122").unwrap(), vec![0x00, 0x11, 0x22]);
+    assert_eq!(parse_hex_bytes("00:1b:21").unwrap(), vec![0x00, 0x1B, 0x21]);
+    assert_eq!(parse_hex_bytes("AA BB-CC").unwrap(), vec![0xAA, 0xBB, 0xCC]);
+    assert!(parse_hex_bytes("ABC").is_err());
+    assert!(parse_hex_bytes("0G").is_err());
+}
+
+#[test]
+fn test_load_oem_multirecords() {
+    let dir = tempdir().unwrap();
+    let blob_path = dir.path().join("blob.bin");
+    std::fs::write(&blob_path, [0x01, 0x02, 0x03]).unwrap();
+    let big_path = dir.path().join("big.bin");
+    std::fs::write(&big_path, vec![0xAB; 253]).unwrap();
+
+    let file_path = dir.path().join("oem.toml");
+    let mut file = File::create(&file_path).unwrap();
+    writeln!(file, "[[multirecord]]").unwrap();
+    writeln!(file, "type = \"oem\"").unwrap();
+    writeln!(file, "type_id = 0xC0").unwrap();
+    writeln!(file, "manufacturer_id = 0x00A2B3").unwrap();
+    writeln!(file, "payload = {{ base64 = \"ABshOk9c\" }}").unwrap();
+    writeln!(file, "[[multirecord]]").unwrap();
+    writeln!(file, "type = \"oem\"").unwrap();
+    writeln!(file, "type_id = 0xC1").unwrap();
+    writeln!(file, "manufacturer_id = 343").unwrap();
+    writeln!(file, "payload = {{ file = {:?} }}", blob_path.to_str().unwrap()).unwrap();
+    drop(file);
+
+    let records = load_multirecords(file_path.to_str().unwrap()).unwrap();
+    assert_eq!(records[0].payload(), vec![0xB3, 0xA2, 0x00, 0x00, 0x1B, 0x21, 0x3A, 0x4F, 0x5C]);
+    assert_eq!(records[1].payload(), vec![0x57, 0x01, 0x00, 0x01, 0x02, 0x03]);
+
+    // 3-byte manufacturer ID + 253 byte payload exceeds the 255-byte record limit
+    let content = std::fs::read_to_string(&file_path).unwrap();
+    std::fs::write(&file_path, content.replace("blob.bin", "big.bin")).unwrap();
+    let err = load_multirecords(file_path.to_str().unwrap()).unwrap_err();
+    assert!(err.to_string().contains("limit is 255"), "{}", err);
+
+    // Relative payload paths are read next to the config, not from the working directory
+    std::fs::write(&file_path, content.replace(blob_path.to_str().unwrap(), "blob.bin")).unwrap();
+    assert!(!std::path::Path::new("blob.bin").exists());
+    let records = load_multirecords(file_path.to_str().unwrap()).unwrap();
+    assert_eq!(records[1].payload(), vec![0x57, 0x01, 0x00, 0x01, 0x02, 0x03]);
+    std::fs::write(&file_path, "[internal_use]\npayload = { file = \"blob.bin\" }\n").unwrap();
+    let internal_use = load_internal_use(file_path.to_str().unwrap()).unwrap().unwrap();
+    assert_eq!(internal_use.payload.unwrap().file.unwrap(), blob_path.to_str().unwrap());
+}
+
+#[test]
+fn test_load_layout() {
+    let dir = tempdir().unwrap();
+    let json_path = dir.path().join("layout.json");
+    std::fs::write(&json_path, r#"{"Board_Manufacturer": "MyMfg", "layout": {"pad_byte": 255, "board": {"offset": 18, "max_size": 32}}}"#).unwrap();
+    let yaml_path = dir.path().join("layout.yaml");
+    std::fs::write(&yaml_path, "Board_Manufacturer: MyMfg\nlayout:\n  pad_byte: \"0xFF\"\n  board: { offset: 18, max_size: 32 }\n").unwrap();
+
+    for path in [&json_path, &yaml_path] {
+        let path = path.to_str().unwrap();
+        let layout = load_layout(path).unwrap().unwrap();
+        assert_eq!(layout.pad_byte, Some(0xFF));
+        assert_eq!(layout.slots()[2], Some(AreaSlot { offset: 18, max_size: Some(32) }));
+        assert!(!load_config(path).unwrap().contains_key("layout"));
+    }
+
+    let toml_path = dir.path().join("bad.toml");
+    std::fs::write(&toml_path, "[layout]\nboard = { offset = 18, size = 32 }\n").unwrap();
+    assert!(load_layout(toml_path.to_str().unwrap()).is_err());
+    std::fs::write(&toml_path, "Board_Manufacturer = \"MyMfg\"\n").unwrap();
+    assert_eq!(load_layout(toml_path.to_str().unwrap()).unwrap(), None);
+}
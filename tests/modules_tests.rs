@@ -1,7 +1,19 @@
-use fru_gen::modules::area::{Area, FieldConfig};
+use fru_gen::FruError;
+use fru_gen::modules::area::{zero_checksum, Area, CustomField, FieldConfig};
 use fru_gen::modules::chassis_area::Chassis;
 use fru_gen::modules::board_area::{Board, parse_mfg_time};
 use fru_gen::modules::product_area::Product;
+use fru_gen::modules::decode::decode_fru;
+use fru_gen::modules::fru_image::{FruBuilder, FruSize};
+use fru_gen::modules::image_format::{encode_image, read_image_data, ImageFormat};
+use fru_gen::modules::batch::{read_manifest, run_batch, BatchOptions};
+use fru_gen::modules::validate::{validate_config, validate_image};
+use fru_gen::modules::patch::patch_image;
+use fru_gen::modules::diff::diff_images;
+use fru_gen::modules::export::{export_image, ConfigFormat};
+use fru_gen::modules::board_area::check_mfg_time;
+use fru_gen::modules::encoding::{decode_text, decode_value, encode_field, encode_text, encode_value, Encoding, Padding};
+use fru_gen::modules::multirecord_area::{Compatibility, DcLoad, ManagementAccess, MultiRecord, PowerSupplyInfo, Record};
 
 #[test]
 fn test_chassis_area_generation() {
@@ -13,13 +25,13 @@ fn test_chassis_area_generation() {
     );
     
     let configs = vec![
-        FieldConfig { enabled: true, reserved_bytes: 0 },
-        FieldConfig { enabled: true, reserved_bytes: 8 },
-        FieldConfig { enabled: true, reserved_bytes: 8 },
-        FieldConfig { enabled: true, reserved_bytes: 8 },
+        FieldConfig { enabled: true, reserved_bytes: 0, ..Default::default() },
+        FieldConfig { enabled: true, reserved_bytes: 8, ..Default::default() },
+        FieldConfig { enabled: true, reserved_bytes: 8, ..Default::default() },
+        FieldConfig { enabled: true, reserved_bytes: 8, ..Default::default() },
     ];
     
-    let bytes = chassis.transfer_with_config(&configs);
+    let bytes = chassis.transfer_with_config(&configs).unwrap();
     
     assert_eq!(bytes[0], 0x01); // Version
     assert_eq!(bytes[2], 0x11); // Type
@@ -56,16 +68,16 @@ fn test_board_area_generation() {
     );
     
     let configs = vec![
-        FieldConfig { enabled: true, reserved_bytes: 0 }, // mfg time
-        FieldConfig { enabled: true, reserved_bytes: 4 },
-        FieldConfig { enabled: true, reserved_bytes: 4 },
-        FieldConfig { enabled: true, reserved_bytes: 4 },
-        FieldConfig { enabled: true, reserved_bytes: 4 },
-        FieldConfig { enabled: true, reserved_bytes: 4 },
-        FieldConfig { enabled: true, reserved_bytes: 4 },
+        FieldConfig { enabled: true, reserved_bytes: 0, ..Default::default() }, // mfg time
+        FieldConfig { enabled: true, reserved_bytes: 4, ..Default::default() },
+        FieldConfig { enabled: true, reserved_bytes: 4, ..Default::default() },
+        FieldConfig { enabled: true, reserved_bytes: 4, ..Default::default() },
+        FieldConfig { enabled: true, reserved_bytes: 4, ..Default::default() },
+        FieldConfig { enabled: true, reserved_bytes: 4, ..Default::default() },
+        FieldConfig { enabled: true, reserved_bytes: 4, ..Default::default() },
     ];
     
-    let bytes = board.transfer_with_config(&configs);
+    let bytes = board.transfer_with_config(&configs).unwrap();
     assert_eq!(bytes[0], 0x01);
     assert_eq!(bytes.len() % 8, 0);
 }
@@ -84,17 +96,1087 @@ fn test_product_area_generation() {
     );
     
     let configs = vec![
-        FieldConfig { enabled: true, reserved_bytes: 4 },
-        FieldConfig { enabled: true, reserved_bytes: 4 },
-        FieldConfig { enabled: true, reserved_bytes: 4 },
-        FieldConfig { enabled: true, reserved_bytes: 4 },
-        FieldConfig { enabled: true, reserved_bytes: 4 },
-        FieldConfig { enabled: true, reserved_bytes: 4 },
-        FieldConfig { enabled: true, reserved_bytes: 4 },
-        FieldConfig { enabled: true, reserved_bytes: 4 },
+        FieldConfig { enabled: true, reserved_bytes: 4, ..Default::default() },
+        FieldConfig { enabled: true, reserved_bytes: 4, ..Default::default() },
+        FieldConfig { enabled: true, reserved_bytes: 4, ..Default::default() },
+        FieldConfig { enabled: true, reserved_bytes: 4, ..Default::default() },
+        FieldConfig { enabled: true, reserved_bytes: 4, ..Default::default() },
+        FieldConfig { enabled: true, reserved_bytes: 4, ..Default::default() },
+        FieldConfig { enabled: true, reserved_bytes: 4, ..Default::default() },
+        FieldConfig { enabled: true, reserved_bytes: 4, ..Default::default() },
     ];
     
-    let bytes = product.transfer_with_config(&configs);
+    let bytes = product.transfer_with_config(&configs).unwrap();
     assert_eq!(bytes[0], 0x01);
     assert_eq!(bytes.len() % 8, 0);
 }
+
+fn assemble_image(chassis: &[u8], board: &[u8], product: &[u8]) -> Vec<u8> {
+    let mut image = vec![0x01, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00];
+    let mut offset = 1u8;
+    for (slot, area) in [(2, chassis), (3, board), (4, product)] {
+        if area.is_empty() {
+            continue;
+        }
+        image[slot] = offset;
+        image.extend_from_slice(area);
+        offset += (area.len() / 8) as u8;
+    }
+    image[7] = zero_checksum(&image[..7]);
+    image
+}
+
+#[test]
+fn test_decode_round_trip() {
+    let chassis = Chassis::new(0x17, "CPN".to_string(), "CSN".to_string(), "CEXTRA".to_string());
+    let board = Board::new(
+        "19960101010000".to_string(),
+        "MFG".to_string(),
+        "PROD".to_string(),
+        "SN".to_string(),
+        "PN".to_string(),
+        "FRUID".to_string(),
+        "EXTRA".to_string(),
+    );
+    let product = Product::new(
+        "PMFG".to_string(),
+        "NAME".to_string(),
+        "PPN".to_string(),
+        "VER".to_string(),
+        "PSN".to_string(),
+        "TAG".to_string(),
+        "PFRUID".to_string(),
+        "PEXTRA".to_string(),
+    );
+    let image = assemble_image(
+        &chassis.transfer_as_byte().unwrap(),
+        &board.transfer_as_byte().unwrap(),
+        &product.transfer_as_byte().unwrap(),
+    );
+
+    let fru = decode_fru(&image).unwrap();
+    assert!(fru.is_compliant(), "{:?}", fru.violations);
+
+    let c = fru.chassis.as_ref().unwrap();
+    assert_eq!(c.chassis_type, 0x17);
+    assert_eq!(c.chassis_part_number.trim_end(), "CPN");
+    assert_eq!(c.chassis_extra.trim_end(), "CEXTRA");
+
+    let b = fru.board.as_ref().unwrap();
+    assert_eq!(b.board_mfg_date_time, "60");
+    assert_eq!(b.board_fru_file_id.trim_end(), "FRUID");
+
+    let p = fru.product.as_ref().unwrap();
+    assert_eq!(p.product_asset_tag.trim_end(), "TAG");
+    assert_eq!(fru.area("Product Info Area").unwrap().fields.len(), 8);
+}
+
+#[test]
+fn test_decode_reports_violations() {
+    let chassis = Chassis::new(0x17, "CPN".to_string(), "CSN".to_string(), "".to_string());
+    let mut image = assemble_image(&chassis.transfer_as_byte().unwrap(), &[], &[]);
+    image[7] = image[7].wrapping_add(1); // break header checksum
+    let chassis_checksum = image.len() - 1;
+    image[chassis_checksum] ^= 0xFF;
+
+    let fru = decode_fru(&image).unwrap();
+    assert!(fru.chassis.is_some());
+    assert!(fru.violations.iter().any(|v| v.area == "Common Header"));
+    assert!(fru.violations.iter().any(|v| v.area == "Chassis Info Area" && v.message.contains("Checksum")));
+
+    assert!(decode_fru(&[0x01, 0x00]).is_err());
+}
+
+#[test]
+fn test_multirecord_area_generation() {
+    let multirecord = MultiRecord::new(vec![
+        Record::PowerSupply(PowerSupplyInfo { overall_capacity: 800, hot_swap: true, ..Default::default() }),
+        Record::DcLoad(DcLoad { output_number: 2, nominal_voltage: 500, ..Default::default() }),
+    ]);
+    let bytes = multirecord.transfer_as_byte().unwrap();
+
+    // Power supply record: 5-byte header + 24 data bytes, not end of list
+    assert_eq!(bytes[0], 0x00);
+    assert_eq!(bytes[1], 0x02);
+    assert_eq!(bytes[2], 24);
+    assert_eq!(bytes[..5].iter().fold(0u8, |a, &b| a.wrapping_add(b)), 0);
+    // DC load record follows and terminates the list
+    assert_eq!(bytes[29], 0x02);
+    assert_eq!(bytes[30], 0x82);
+    assert_eq!(bytes.len(), 29 + 5 + 13);
+
+    let chassis = Chassis::new(0x17, "CPN".to_string(), "CSN".to_string(), "".to_string());
+    let mut image = assemble_image(&chassis.transfer_as_byte().unwrap(), &[], &[]);
+    image[5] = (image.len() / 8) as u8;
+    image[7] = 0;
+    image[7] = zero_checksum(&image[..7]);
+    image.extend(&bytes);
+
+    let fru = decode_fru(&image).unwrap();
+    assert!(fru.is_compliant(), "{:?}", fru.violations);
+    assert_eq!(fru.multirecord.unwrap().records, multirecord.records);
+}
+
+#[test]
+fn test_power_supply_flag_bits() {
+    let flags = |ps: PowerSupplyInfo| Record::PowerSupply(ps).payload()[17];
+    assert_eq!(flags(PowerSupplyInfo { predictive_fail_support: true, ..Default::default() }), 0x01);
+    assert_eq!(flags(PowerSupplyInfo { power_factor_correction: true, ..Default::default() }), 0x02);
+    assert_eq!(flags(PowerSupplyInfo { autoswitch: true, ..Default::default() }), 0x04);
+    assert_eq!(flags(PowerSupplyInfo { hot_swap: true, ..Default::default() }), 0x08);
+    assert_eq!(flags(PowerSupplyInfo { predictive_fail_polarity: true, ..Default::default() }), 0x10);
+
+    let mut data = vec![0u8; 24];
+    data[17] = 0x09;
+    match Record::from_bytes(0x00, &data) {
+        Record::PowerSupply(ps) => {
+            assert!(ps.hot_swap && ps.predictive_fail_support);
+            assert!(!ps.power_factor_correction && !ps.autoswitch && !ps.predictive_fail_polarity);
+        }
+        other => panic!("unexpected record {:?}", other),
+    }
+}
+
+#[test]
+fn test_management_and_compatibility_records() {
+    let url = Record::ManagementAccess(ManagementAccess {
+        sub_record: 0x01,
+        value: "https://bmc.example.com/".to_string(),
+    });
+    assert!(url.check().is_ok());
+    assert_eq!(url.payload()[0], 0x01);
+
+    let short_name = Record::ManagementAccess(ManagementAccess {
+        sub_record: 0x02,
+        value: "bmc".to_string(),
+    });
+    assert!(short_name.check().unwrap_err().contains("System Name"));
+
+    let bad_type = Record::ManagementAccess(ManagementAccess {
+        sub_record: 0x09,
+        value: "whatever-value".to_string(),
+    });
+    assert!(bad_type.check().is_err());
+
+    let guid = Record::ManagementAccess(ManagementAccess {
+        sub_record: 0x07,
+        value: "01234567-89ab-cdef-0123-456789abcdef".to_string(),
+    });
+    assert_eq!(guid.payload().len(), 17);
+
+    let compat = Record::ExtendedCompatibility(Compatibility {
+        manufacturer_id: 0x00A2B3,
+        entity_id: 0x0A,
+        compatibility_base: 0x01,
+        code_start: 0x00,
+        code_range_mask: vec![0xFF, 0x03],
+    });
+    assert_eq!(compat.payload(), vec![0xB3, 0xA2, 0x00, 0x0A, 0x01, 0x00, 0xFF, 0x03]);
+    assert_eq!(Record::from_bytes(0x05, &compat.payload()), compat);
+}
+
+#[test]
+fn test_field_encodings_round_trip() {
+    // BCD plus: two characters per byte, odd lengths padded with a space nibble
+    let (enc, bytes) = encode_value("1996-01.5", Encoding::BcdPlus).unwrap();
+    assert_eq!(enc, Encoding::BcdPlus);
+    assert_eq!(bytes, vec![0x19, 0x96, 0xB0, 0x1C, 0x5A]);
+    assert_eq!(decode_value(0x40 | 5, &bytes), "1996-01.5 ");
+
+    // 6-bit packed ASCII: four characters per three bytes
+    let (_, bytes) = encode_value("IPMI", Encoding::SixBitAscii).unwrap();
+    assert_eq!(bytes.len(), 3);
+    assert_eq!(decode_value(0x80 | 3, &bytes), "IPMI");
+    assert!(encode_value("lower", Encoding::SixBitAscii).is_err());
+
+    // Binary data is given as hex
+    let (_, bytes) = encode_value("DE AD BE EF", Encoding::Binary).unwrap();
+    assert_eq!(bytes, vec![0xDE, 0xAD, 0xBE, 0xEF]);
+    assert_eq!(decode_value(0x04, &bytes), "DE AD BE EF");
+
+    // Auto picks the smallest encoding that fits
+    assert_eq!(encode_value("123456", Encoding::Auto).unwrap().0, Encoding::BcdPlus);
+    assert_eq!(encode_value("SN-ABC123", Encoding::Auto).unwrap().0, Encoding::SixBitAscii);
+    assert_eq!(encode_value("Serial abc", Encoding::Auto).unwrap().0, Encoding::Ascii8);
+    assert_eq!(Encoding::parse("6bit"), Some(Encoding::SixBitAscii));
+    assert_eq!(Encoding::parse("bogus"), None);
+}
+
+#[test]
+fn test_encoded_field_reservation() {
+    let config = FieldConfig { enabled: true, reserved_bytes: 6, encoding: Encoding::SixBitAscii, ..Default::default() };
+    let field = encode_field("ABCD", &config).unwrap().unwrap();
+    assert_eq!(field[0], 0x80 | 6);
+    assert_eq!(decode_value(field[0], &field[1..]), "ABCD    ");
+
+    let chassis = Chassis::new(0x17, "PART123".to_string(), "12345678".to_string(), "".to_string());
+    let configs = vec![
+        FieldConfig { enabled: true, ..Default::default() },
+        FieldConfig { enabled: true, encoding: Encoding::Auto, ..Default::default() },
+        FieldConfig { enabled: true, encoding: Encoding::Auto, ..Default::default() },
+        FieldConfig { enabled: true, ..Default::default() },
+    ];
+    let image = assemble_image(&chassis.transfer_with_config(&configs).unwrap(), &[], &[]);
+    let fru = decode_fru(&image).unwrap();
+    let fields = &fru.area("Chassis Info Area").unwrap().fields;
+    assert_eq!(fields[0].type_code(), 0b10);
+    assert_eq!(fields[0].value, "PART123 ");
+    assert_eq!(fields[1].type_code(), 0b01);
+    assert_eq!(fields[1].value, "12345678");
+
+    // A single 8-bit character would give type/length 0xC1, the end-of-fields marker
+    let config = FieldConfig { enabled: true, reserved_bytes: 0, ..Default::default() };
+    assert_eq!(encode_field("x", &config).unwrap().unwrap(), vec![0xC2, b'x', b' ']);
+    let auto = FieldConfig { encoding: Encoding::Auto, ..config.clone() };
+    assert_eq!(encode_field("x", &auto).unwrap().unwrap(), vec![0xC2, b'x', b' ']);
+    assert_eq!(encode_field("X", &auto).unwrap().unwrap()[0], 0x81);
+    let chassis = Chassis::new(0x17, "x".to_string(), "".to_string(), "y".to_string());
+    let image = assemble_image(&chassis.transfer_with_config(&vec![config; 4]).unwrap(), &[], &[]);
+    let fru = decode_fru(&image).unwrap();
+    assert!(fru.is_compliant(), "{:?}", fru.violations);
+    assert_eq!(fru.area("Chassis Info Area").unwrap().fields.len(), 3);
+}
+
+#[test]
+fn test_unicode_fields_for_non_english_language() {
+    // German (22) stores 8-bit text as UTF-16LE; English keeps Latin-1
+    let (_, bytes) = encode_text("Grüße", Encoding::Ascii8, 22).unwrap();
+    assert_eq!(bytes, vec![b'G', 0, b'r', 0, 0xFC, 0, 0xDF, 0, b'e', 0]);
+    assert_eq!(decode_text(0xC0 | 10, &bytes, 22), "Grüße");
+    assert_eq!(encode_text("Grüße", Encoding::Ascii8, 25).unwrap().1.len(), 5);
+    assert!(encode_value("日本", Encoding::Ascii8).is_err());
+
+    // Padding keeps whole characters and the length stays even
+    let config = FieldConfig { enabled: true, reserved_bytes: 5, language: 53, ..Default::default() };
+    let field = encode_field("日本", &config).unwrap().unwrap();
+    assert_eq!(field[0], 0xC0 | 6);
+    assert_eq!(decode_text(field[0], &field[1..], 53), "日本 ");
+    let config = FieldConfig { enabled: true, language: 53, ..Default::default() };
+    let field = encode_field(&"あ".repeat(31), &config).unwrap().unwrap();
+    assert_eq!(field[0] & 0x3F, 62);
+    let err = encode_field(&"あ".repeat(32), &config).unwrap_err();
+    assert!(err.contains("64 bytes, more than the 62"), "{}", err);
+
+    let configs: Vec<FieldConfig> = (0..8).map(|_| FieldConfig { enabled: true, ..Default::default() }).collect();
+    let product = Product::new(
+        "Hersteller".to_string(),
+        "Überwachungsgerät".to_string(),
+        "".to_string(),
+        "".to_string(),
+        "".to_string(),
+        "".to_string(),
+        "".to_string(),
+        "".to_string(),
+    )
+    .with_language_code(22);
+    let product_data = product.transfer_with_config(&configs).unwrap();
+    assert_eq!(product_data[2], 22);
+
+    let image = assemble_image(&[], &[], &product_data);
+    let fru = decode_fru(&image).unwrap();
+    assert!(fru.is_compliant(), "{:?}", fru.violations);
+    let decoded = fru.product.as_ref().unwrap();
+    assert_eq!(decoded.language_code, 22);
+    assert_eq!(decoded.product_product_name, "Überwachungsgerät");
+}
+
+#[test]
+fn test_area_errors_are_returned() {
+    let chassis = Chassis::new(0x17, "P".repeat(0x40), "SN".to_string(), "".to_string());
+    assert_eq!(
+        chassis.validate(),
+        Err(FruError::FieldTooLong {
+            area: "Chassis Info Area".to_string(),
+            field: "Chassis Part Number".to_string(),
+            len: 0x40,
+        })
+    );
+    // Building with the default field settings checks the lengths as well
+    assert!(matches!(
+        FruBuilder::new().chassis(chassis).size(1024).build(),
+        Err(FruError::FieldTooLong { ref field, len: 0x40, .. }) if field == "Chassis Part Number"
+    ));
+
+    let chassis = Chassis::new(0x17, "lower".to_string(), "SN".to_string(), "".to_string());
+    let configs = vec![
+        FieldConfig { enabled: true, ..Default::default() },
+        FieldConfig { enabled: true, encoding: Encoding::SixBitAscii, ..Default::default() },
+        FieldConfig { enabled: true, ..Default::default() },
+        FieldConfig { enabled: true, ..Default::default() },
+    ];
+    match chassis.transfer_with_config(&configs) {
+        Err(FruError::FieldEncoding { field, .. }) => assert_eq!(field, "Chassis Part Number"),
+        other => panic!("unexpected result: {:?}", other),
+    }
+    // The builder needs one config per fixed field instead of slicing past a short list
+    assert_eq!(
+        FruBuilder::new().chassis(chassis).field_configs(configs).size(1024).build().unwrap_err(),
+        FruError::FieldConfigCount { expected: 19, actual: 4 }
+    );
+
+    let multirecord = MultiRecord::new(vec![Record::ManagementAccess(ManagementAccess {
+        sub_record: 0x01,
+        value: "short".to_string(),
+    })]);
+    assert!(matches!(multirecord.transfer_as_byte(), Err(FruError::InvalidRecord(_))));
+}
+
+#[test]
+fn test_fru_builder_lays_out_areas() {
+    let chassis = Chassis::new(0x17, "CPN".to_string(), "CSN".to_string(), "".to_string());
+    let product = Product::new(
+        "MFG".to_string(),
+        "NAME".to_string(),
+        "PN".to_string(),
+        "V1".to_string(),
+        "SN".to_string(),
+        "TAG".to_string(),
+        "ID".to_string(),
+        "".to_string(),
+    );
+    let image = FruBuilder::new().chassis(chassis).product(product).size(512).build().unwrap();
+    assert_eq!(image.data.len(), 512);
+
+    let chassis_area = image.area("Chassis Info Area").unwrap();
+    let product_area = image.area("Product Info Area").unwrap();
+    assert_eq!(image.data[2] as usize * 8, chassis_area.offset);
+    assert_eq!(image.data[3], 0); // no board area
+    assert_eq!(image.data[4] as usize * 8, product_area.offset);
+    assert_eq!(image.used, product_area.offset + product_area.length);
+
+    let fru = decode_fru(&image.data).unwrap();
+    assert!(fru.is_compliant(), "{:?}", fru.violations);
+    assert_eq!(fru.product.as_ref().unwrap().product_asset_tag.trim_end(), "TAG");
+
+    let chassis = Chassis::new(0x17, "CPN".to_string(), "CSN".to_string(), "".to_string());
+    assert!(matches!(
+        FruBuilder::new().chassis(chassis).size(16).build(),
+        Err(FruError::ImageTooLarge { limit: 16, .. })
+    ));
+}
+
+#[test]
+fn test_validate_image_findings() {
+    let chassis = Chassis::new(0x40, "CPN".to_string(), "CSN".to_string(), "".to_string());
+    let image = FruBuilder::new().chassis(chassis).size(256).build().unwrap();
+    let report = validate_image(&image.data);
+    assert_eq!(report.errors(), 1);
+    assert!(report.findings[0].message.contains("outside the SMBIOS range"));
+
+    // Point the board offset into the middle of the chassis area
+    let chassis = Chassis::new(0x17, "CPN".to_string(), "CSN".to_string(), "".to_string());
+    let mut data = FruBuilder::new().chassis(chassis).size(256).build().unwrap().data;
+    data[3] = 2;
+    data[7] = data[7].wrapping_sub(2);
+    let report = validate_image(&data);
+    assert!(!report.is_ok());
+    assert!(report.findings.iter().any(|f| f.message.contains("overlaps Board Info Area")));
+}
+
+#[test]
+fn test_check_mfg_time() {
+    assert_eq!(check_mfg_time(""), Ok(0));
+    assert_eq!(check_mfg_time("19960101010000"), Ok(60));
+    assert_eq!(check_mfg_time("0xFFFFFF"), Ok(0xFFFFFF));
+    assert!(check_mfg_time("19951231235959").is_err());
+    assert!(check_mfg_time("20280101000000").is_err()); // past the 24-bit range
+    assert!(check_mfg_time("20241399000000").is_err());
+    assert!(check_mfg_time("yesterday").is_err());
+}
+
+#[test]
+fn test_batch_json_manifest_reports_failed_rows() {
+    let dir = tempfile::tempdir().unwrap();
+    let config_path = dir.path().join("base.toml");
+    fru_gen::build_config_template(config_path.to_str().unwrap()).unwrap();
+    let template = std::fs::read_to_string(&config_path).unwrap();
+    let base: String = template
+        .lines()
+        .map(|line| match line.split('=').next().unwrap().trim() {
+            "Board_mfg_date_time" => "Board_mfg_date_time = \"20240101120000\"".to_string(),
+            "Product_Name" => "Product_Name = { value = \"Server\", reserve_bytes = 8 }".to_string(),
+            _ => line.to_string(),
+        })
+        .collect::<Vec<_>>()
+        .join("\n");
+    std::fs::write(&config_path, base + r#"
+
+[[multirecord]]
+type = "oem"
+type_id = 0xC0
+manufacturer_id = 0x00A2B3
+payload = { hex = "00:00:00:00:00:00" }
+"#).unwrap();
+
+    let manifest_path = dir.path().join("units.json");
+    std::fs::write(&manifest_path, r#"[
+        { "Board_Serial_Number": "A1", "Product_Name": "Server X", "multirecord[0].payload.hex": "001B21000001" },
+        { "Board_Serial_Number": "A1" },
+        { "Board_Serial_Number": "A3", "Chassis_type": "Rack Mount" },
+        { "output": "spare.bin", "Board_mfg_date_time": 60 },
+        { "output": "../escape.bin", "Board_Serial_Number": "A5" },
+        { "output": "/tmp/escape.bin", "Board_Serial_Number": "A6" }
+    ]"#).unwrap();
+
+    let rows = read_manifest(&manifest_path).unwrap();
+    assert_eq!(rows.len(), 6);
+    assert!(rows[3].values.contains(&("Board_mfg_date_time".to_string(), "60".to_string())));
+
+    let options = BatchOptions {
+        pattern: "unit-{board_serial_number}.bin".to_string(),
+        out_dir: dir.path().join("out"),
+        size: FruSize::Bytes(1024),
+        state_file: dir.path().join("state.json"),
+        start: None,
+        strict: false,
+        format: ImageFormat::Binary,
+        base_address: 0,
+    };
+    let report = run_batch(config_path.to_str().unwrap(), &rows, &options).unwrap();
+    assert_eq!((report.produced, report.failed), (2, 4));
+    assert!(report.units[1].errors[0].contains("already written by row 1"));
+    assert!(report.units[2].errors[0].contains("Unknown chassis type"));
+    // Manifest outputs stay inside the output directory
+    assert!(report.units[4].errors[0].contains("must be a file name inside the output directory"));
+    assert!(report.units[5].errors[0].contains("must be a file name inside the output directory"));
+    assert!(!dir.path().join("escape.bin").exists());
+
+    let image = std::fs::read(dir.path().join("out/unit-A1.bin")).unwrap();
+    let fru = decode_fru(&image).unwrap();
+    // Overridden value keeps the base field's reserve_bytes
+    assert_eq!(fru.area("Product Info Area").unwrap().fields[1].data.len(), 8);
+    match &fru.multirecord.as_ref().unwrap().records[0] {
+        Record::Oem(oem) => assert_eq!(oem.data, vec![0x00, 0x1B, 0x21, 0x00, 0x00, 0x01]),
+        other => panic!("unexpected record {:?}", other),
+    }
+    assert!(dir.path().join("out/spare.bin").exists());
+}
+
+#[test]
+fn test_sequence_patterns_never_reuse_serials() {
+    use chrono::TimeZone;
+    use fru_gen::modules::sequence::{expand_pattern, SequenceAllocator, SequenceState};
+    use fru_gen::ConfigField;
+    use std::collections::HashMap;
+
+    let now = chrono::Utc.with_ymd_and_hms(2025, 1, 2, 3, 4, 5).unwrap();
+    assert_eq!(expand_pattern("SN{YY}{WW}{seq:05}", 42, now).unwrap(), "SN250100042");
+    assert_eq!(expand_pattern("{YYYY}-{MM}-{DD}/{seq}", 7, now).unwrap(), "2025-01-02/7");
+    assert!(expand_pattern("SN{lot}", 1, now).unwrap_err().contains("unknown token"));
+    assert!(expand_pattern("SN{seq:99999999999}", 1, now).unwrap_err().contains("invalid width"));
+    assert!(expand_pattern("SN{seq:64}", 1, now).unwrap_err().contains("invalid width"));
+
+    let dir = tempfile::tempdir().unwrap();
+    let state_path = dir.path().join("state.json");
+    let config = || {
+        let mut map = HashMap::new();
+        map.insert("board_serial_number".to_string(), ConfigField::Simple("SN{seq:03}".to_string()));
+        map.insert("board_part_number".to_string(), ConfigField::Simple("PN-1".to_string()));
+        map
+    };
+
+    let mut allocator = SequenceAllocator::new(SequenceState::load(&state_path).unwrap(), None);
+    for unit in 0..3 {
+        let mut map = config();
+        let used = allocator.expand_unit(&mut map, unit).unwrap();
+        assert_eq!(map["board_serial_number"].value(), format!("SN{:03}", unit + 1));
+        assert_eq!(map["board_part_number"].value(), "PN-1");
+        // The last unit is never produced: its number is skipped, not persisted
+        if unit < 2 {
+            allocator.commit(&used);
+        }
+    }
+    allocator.save().unwrap();
+
+    let state = SequenceState::load(&state_path).unwrap();
+    assert_eq!(state.last("SN{seq:03}"), Some(2));
+
+    let mut map = config();
+    let mut allocator = SequenceAllocator::new(state.clone(), None);
+    allocator.expand_unit(&mut map, 0).unwrap();
+    assert_eq!(map["board_serial_number"].value(), "SN003");
+
+    let mut allocator = SequenceAllocator::new(state, Some(2));
+    assert!(allocator.expand_unit(&mut config(), 0).unwrap_err().contains("would reuse"));
+
+    // Running past u64::MAX is an error, never a wrap back to reused numbers
+    let mut allocator = SequenceAllocator::new(SequenceState::default(), Some(u64::MAX));
+    allocator.expand_unit(&mut config(), 0).unwrap();
+    assert!(allocator.expand_unit(&mut config(), 1).unwrap_err().contains("out of sequence numbers"));
+    let mut exhausted = SequenceState::default();
+    exhausted.record("SN{seq:03}", u64::MAX);
+    let mut allocator = SequenceAllocator::new(exhausted, None);
+    assert!(allocator.expand_unit(&mut config(), 0).unwrap_err().contains("out of sequence numbers"));
+}
+
+#[test]
+fn test_patch_image_relayouts_and_preserves_other_areas() {
+    use fru_gen::modules::internal_area::Internal;
+
+    let text = |s: &str| s.to_string();
+    let board = Board::new(text("0"), text("MFG"), text("BOARD"), text("SN1"), text("PN1"), text("ID"), text(""));
+    let product = Product::new(text("MFG"), text("NAME"), text("PN"), text("V1"), text("PSN"), text("TAG"), text("ID"), text(""));
+    let records = MultiRecord::new(vec![Record::Unknown { type_id: 0x09, data: vec![0xDE, 0xAD, 0xBE, 0xEF] }]);
+    let image = FruBuilder::new()
+        .internal(Internal::new(text("")))
+        .board(board)
+        .product(product)
+        .multirecord(records)
+        .size(1024)
+        .build()
+        .unwrap();
+    let data = image.data.clone();
+    let set = |key: &str, value: &str| vec![(key.to_string(), value.to_string())];
+
+    // A short value fits in place: only bytes inside the product area change
+    let product_area = image.area("Product Info Area").unwrap();
+    let patched = patch_image(&data, &set("Product_Asset_Tag", "A1")).unwrap();
+    assert_eq!(patched.len(), data.len());
+    assert_eq!(patched[..product_area.offset], data[..product_area.offset]);
+    assert_eq!(patched[product_area.offset + product_area.length..], data[product_area.offset + product_area.length..]);
+    let fru = decode_fru(&patched).unwrap();
+    assert!(fru.is_compliant(), "{:?}", fru.violations);
+    assert_eq!(fru.product.as_ref().unwrap().product_asset_tag.trim_end(), "A1");
+
+    // A long value grows the board area and moves the areas behind it
+    let serial = "S".repeat(40);
+    let patched = patch_image(&data, &set("board_serial_number", &serial)).unwrap();
+    let fru = decode_fru(&patched).unwrap();
+    assert!(fru.is_compliant(), "{:?}", fru.violations);
+    assert_eq!(fru.board.as_ref().unwrap().board_serial_number, serial);
+    assert!(patched[4] > data[4]);
+    assert_eq!(fru.product.as_ref().unwrap().product_asset_tag.trim_end(), "TAG");
+    assert_eq!(patched[8..16], data[8..16]); // internal use area untouched
+    let old_records = image.area_data("MultiRecord Area").unwrap();
+    let new_records = fru.area("MultiRecord Area").unwrap();
+    assert_eq!(&patched[new_records.offset..new_records.offset + new_records.length], old_records);
+
+    assert!(matches!(patch_image(&data, &set("chassis_part_number", "X")), Err(FruError::MissingArea(_))));
+    assert!(matches!(patch_image(&data, &set("board_color", "red")), Err(FruError::UnknownField(_))));
+    assert!(matches!(
+        patch_image(&data, &set("product_name", &"N".repeat(70))),
+        Err(FruError::FieldTooLong { .. })
+    ));
+
+    // A truncated dump whose header points an area past the end is rejected, not sliced
+    let mut truncated = data.clone();
+    truncated[5] = 0xF0;
+    truncated[7] = 0;
+    truncated[7] = zero_checksum(&truncated[..7]);
+    assert!(matches!(
+        patch_image(&truncated, &set("product_asset_tag", "X")),
+        Err(FruError::InvalidImage(_))
+    ));
+}
+
+#[test]
+fn test_patch_image_pads_single_character_fields() {
+    // An empty field patched to one 8-bit character must not read as the end marker
+    let chassis = Chassis::new(0x17, "x".to_string(), "".to_string(), "y".to_string());
+    let config = FieldConfig { enabled: true, reserved_bytes: 0, ..Default::default() };
+    let image = assemble_image(&chassis.transfer_with_config(&vec![config; 4]).unwrap(), &[], &[]);
+    let patched = patch_image(&image, &[("chassis_serial_number".to_string(), "z".to_string())]).unwrap();
+    let fru = decode_fru(&patched).unwrap();
+    assert!(fru.is_compliant(), "{:?}", fru.violations);
+    let fields = &fru.area("Chassis Info Area").unwrap().fields;
+    assert_eq!((fields.len(), fields[1].data.as_slice()), (3, &b"z "[..]));
+}
+
+#[test]
+fn test_diff_images_reports_fields_and_ranges() {
+    let text = |s: &str| s.to_string();
+    let chassis = |serial: &str| Chassis::new(0x17, text("CPN"), text(serial), text(""));
+    let configs = |reserved: usize, encoding: Encoding| vec![
+        FieldConfig { enabled: true, reserved_bytes: reserved, encoding, ..Default::default() };
+        3
+    ];
+    let build = |serial: &str, reserved: usize, encoding: Encoding| {
+        let mut field_configs = configs(reserved, encoding);
+        field_configs.extend(vec![FieldConfig { enabled: true, ..Default::default() }; 16]);
+        FruBuilder::new().chassis(chassis(serial)).field_configs(field_configs).size(128).build().unwrap().data
+    };
+
+    let golden = build("CSN1", 8, Encoding::Ascii8);
+    assert!(diff_images(&golden, &golden).unwrap().is_empty());
+
+    let diff = diff_images(&golden, &build("CSN2", 8, Encoding::Ascii8)).unwrap();
+    let fields: Vec<&str> = diff.differences.iter().map(|d| d.field.as_str()).collect();
+    assert_eq!(fields, vec!["Checksum", "Chassis Serial Number"]);
+    assert_eq!(diff.differences[1].left, "\"CSN1\"");
+    assert_eq!(diff.ranges.len(), 2);
+    assert_eq!(diff.ranges[0].area.as_deref(), Some("Chassis Info Area"));
+
+    // Same value, only the reserved padding differs
+    let diff = diff_images(&golden, &build("CSN1", 12, Encoding::Ascii8)).unwrap();
+    assert!(diff.differences.iter().any(|d| d.field == "Chassis Serial Number length" && d.right == "12 bytes"));
+    assert!(!diff.differences.iter().any(|d| d.field == "Chassis Serial Number"));
+
+    let diff = diff_images(&golden, &build("CSN1", 8, Encoding::SixBitAscii)).unwrap();
+    assert!(diff.differences.iter().any(|d| d.field == "Chassis Serial Number encoding" && d.right == "6bit_ascii"));
+}
+
+#[test]
+fn test_export_round_trips_to_identical_image() {
+    let dir = tempfile::tempdir().unwrap();
+    let config_path = dir.path().join("source.toml");
+    std::fs::write(&config_path, r#"
+Chassis_type = "Blade"
+Chassis_Part_Number = "CPN"
+Chassis_Serial_Number = { value = "CSN", reserve_bytes = 0 }
+Board_mfg_date_time = "20240102030400"
+Board_Language = "fr"
+Board_Manufacturer = "Fabricant"
+Board_Product_Name = { value = "BOARD", encoding = "6bit_ascii", reserve_bytes = 12 }
+Board_Serial_Number = "SN1"
+Board_Part_Number = { value = "1234-5", encoding = "bcd_plus" }
+Board_Fruid = { value = "DEADBEEF", encoding = "binary", reserve_bytes = 0 }
+Product_Manufacturer = "MFG"
+Product_Name = { value = "A name longer than thirty-two bytes", reserve_bytes = 0 }
+Product_Version = ""
+
+[[multirecord]]
+type = "dc_output"
+output_number = 1
+nominal_voltage = 1200
+max_current = 60000
+
+[[multirecord]]
+type = "oem"
+type_id = 0xC0
+manufacturer_id = 0x00A2B3
+payload = { hex = "00:1B:21:3A:4F:5C" }
+"#).unwrap();
+    let original = FruBuilder::from_config_file(config_path.to_str().unwrap()).unwrap().size(1024).build().unwrap();
+
+    for (name, format) in [
+        ("exported.toml", ConfigFormat::Toml),
+        ("exported.yaml", ConfigFormat::Yaml),
+        ("exported.json", ConfigFormat::Json),
+    ] {
+        let exported = export_image(&original.data, format).unwrap();
+        assert!(exported.contains("Board_Language"), "{}", exported);
+        let path = dir.path().join(name);
+        std::fs::write(&path, &exported).unwrap();
+        let rebuilt = FruBuilder::from_config_file(path.to_str().unwrap()).unwrap().size(1024).build().unwrap();
+        assert_eq!(rebuilt.data, original.data, "{}", exported);
+    }
+
+    let exported = export_image(&original.data, ConfigFormat::Toml).unwrap();
+    assert!(exported.contains("Chassis_Part_Number = \"CPN\"\n"));
+    assert!(exported.contains("Board_Product_Name = { value = \"BOARD\", reserve_bytes = 12, encoding = \"6bit_ascii\" }"));
+    assert!(exported.contains("[[multirecord]]\ntype = \"dc_output\""));
+}
+
+#[test]
+fn test_custom_field_lists() {
+    let board = Board::new(
+        "0".to_string(), "MFG".to_string(), "PROD".to_string(), "SN".to_string(),
+        "PN".to_string(), "FRUID".to_string(), "EXTRA".to_string(),
+    )
+    .with_custom_fields(vec![
+        CustomField::new("REV A".to_string()),
+        CustomField {
+            value: "LOT42".to_string(),
+            config: FieldConfig { enabled: true, reserved_bytes: 0, encoding: Encoding::SixBitAscii, ..Default::default() },
+        },
+    ]);
+    let image = FruBuilder::new().board(board).size(512).build().unwrap();
+    let fru = decode_fru(&image.data).unwrap();
+    assert!(fru.violations.is_empty(), "{:?}", fru.violations);
+    let fields = &fru.area("Board Info Area").unwrap().fields;
+    assert_eq!(fields.len(), 8);
+    assert_eq!(fields[6].value.trim_end(), "REV A");
+    assert_eq!(fields[6].data.len(), 32);
+    assert_eq!(fields[7].value, "LOT42");
+    assert_eq!(fields[7].type_code(), 0b10);
+
+    let dir = tempfile::tempdir().unwrap();
+    let config_path = dir.path().join("custom.toml");
+    std::fs::write(&config_path, r#"
+Chassis_type = "Blade"
+Chassis_Part_Number = "CPN"
+Chassis_Serial_Number = "CSN"
+Board_Manufacturer = "MFG"
+Board_Product_Name = "PROD"
+Board_Serial_Number = "SN"
+Board_Part_Number = "PN"
+Board_Fruid = "FRUID"
+Board_Extra = "EXTRA"
+Board_Custom = ["REV A", { value = "LOT42", encoding = "6bit_ascii", reserve_bytes = 0 }]
+
+[product]
+manufacturer = "MFG"
+custom = [{ value = "0102", encoding = "binary" }]
+"#).unwrap();
+    let path = config_path.to_str().unwrap();
+    let config = fru_gen::load_config(path).unwrap();
+    assert_eq!(config.get("board_custom").unwrap().items().len(), 2);
+    assert!(fru_gen::unknown_config_keys(&config).is_empty());
+
+    let built = FruBuilder::from_config_file(path).unwrap().size(1024).build().unwrap();
+    let fru = decode_fru(&built.data).unwrap();
+    assert_eq!(fru.area("Board Info Area").unwrap().fields.len(), 8);
+    assert!(fru.area("Product Info Area").unwrap().fields[1].data.starts_with(&[0x01, 0x02]));
+
+    // Custom fields beyond *_Extra export as a list and rebuild the same image
+    let exported = export_image(&built.data, ConfigFormat::Toml).unwrap();
+    assert!(exported.contains("Board_Extra = \"EXTRA\"\n"), "{}", exported);
+    assert!(exported.contains("Board_Custom = [\"REV A\", { value = \"LOT42\", reserve_bytes = 0, encoding = \"6bit_ascii\" }]"), "{}", exported);
+    let exported_path = dir.path().join("exported.toml");
+    std::fs::write(&exported_path, &exported).unwrap();
+    let rebuilt = FruBuilder::from_config_file(exported_path.to_str().unwrap()).unwrap().size(1024).build().unwrap();
+    assert_eq!(rebuilt.data, built.data);
+
+    let mut config = config;
+    config.insert("board_custom".to_string(), fru_gen::ConfigField::List(vec![
+        fru_gen::ConfigField::Simple("x".repeat(64)),
+    ]));
+    let report = validate_config(&config, Vec::new(), None, None, 1024, false);
+    assert!(report.findings.iter().any(|f| f.message.contains("'board_custom[0]' encodes to 64 bytes")), "{:?}", report.findings);
+}
+
+#[test]
+fn test_internal_use_area_from_config() {
+    let dir = tempfile::tempdir().unwrap();
+    let blob_path = dir.path().join("blob.bin");
+    std::fs::write(&blob_path, [0xDE, 0xAD, 0xBE, 0xEF]).unwrap();
+    let config_path = dir.path().join("internal.toml");
+    std::fs::write(&config_path, r#"
+Board_Manufacturer = "MFG"
+
+[internal_use]
+payload = { hex = "01:02:03" }
+reserve_bytes = 20
+"#).unwrap();
+    let path = config_path.to_str().unwrap();
+
+    let built = FruBuilder::from_config_file(path).unwrap().size(512).build().unwrap();
+    let fru = decode_fru(&built.data).unwrap();
+    assert!(!fru.violations.iter().any(|v| v.area == "Internal Use Area"), "{:?}", fru.violations);
+    let area = fru.area("Internal Use Area").unwrap();
+    assert_eq!(area.length, 24);
+    assert_eq!(&built.data[area.offset..area.offset + 4], &[0x01, 0x01, 0x02, 0x03]);
+
+    // The reservation is exported as a trailing-zero payload and rebuilds the same image
+    let exported = export_image(&built.data, ConfigFormat::Toml).unwrap();
+    assert!(exported.contains("[internal_use]"), "{}", exported);
+    let exported_path = dir.path().join("exported.toml");
+    std::fs::write(&exported_path, &exported).unwrap();
+    let rebuilt = FruBuilder::from_config_file(exported_path.to_str().unwrap()).unwrap().size(512).build().unwrap();
+    assert_eq!(rebuilt.data, built.data);
+
+    let file_config = fru_gen::InternalUseConfig {
+        payload: Some(fru_gen::PayloadSource { file: Some(blob_path.to_str().unwrap().to_string()), ..Default::default() }),
+        reserve_bytes: None,
+    };
+    let internal = fru_gen::modules::internal_area::Internal::from_config(&file_config).unwrap();
+    assert_eq!(internal.transfer_as_byte().unwrap(), vec![0x01, 0xDE, 0xAD, 0xBE, 0xEF, 0x00, 0x00, 0x00]);
+
+    let too_large = fru_gen::InternalUseConfig {
+        payload: Some(fru_gen::PayloadSource { base64: Some("AQIDBAU=".to_string()), ..Default::default() }),
+        reserve_bytes: Some(4),
+    };
+    assert!(matches!(
+        FruBuilder::new().internal_use(Some(&too_large)),
+        Err(FruError::InternalUseTooLarge { len: 5, reserved: 4 })
+    ));
+    let config = fru_gen::load_config(path).unwrap();
+    let report = validate_config(&config, Vec::new(), Some(&too_large), None, 512, false);
+    assert!(report.findings.iter().any(|f| f.area == "Internal Use Area"), "{:?}", report.findings);
+
+    // A reservation the common header cannot address is rejected before it is allocated
+    let out_of_range = fru_gen::InternalUseConfig { payload: None, reserve_bytes: Some(100_000_000_000) };
+    assert!(matches!(
+        FruBuilder::new().internal_use(Some(&out_of_range)),
+        Err(FruError::InternalUseOutOfRange { max: 2031, .. })
+    ));
+    let report = validate_config(&config, Vec::new(), Some(&out_of_range), None, 512, false);
+    assert!(report.findings.iter().any(|f| f.message.contains("0x7F8")), "{:?}", report.findings);
+    let largest = fru_gen::InternalUseConfig { payload: None, reserve_bytes: Some(2031) };
+    assert!(FruBuilder::new().internal_use(Some(&largest)).is_ok());
+
+    // Without an [internal_use] table the legacy empty area is kept
+    let plain = FruBuilder::new().size(512).build().unwrap();
+    let offset = plain.data[1] as usize * 8;
+    assert_eq!(&plain.data[offset..offset + 8], &[0x01, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0xFF]);
+}
+
+#[test]
+fn test_image_formats_round_trip() {
+    let image = FruBuilder::new().size(256).build().unwrap().into_bytes();
+
+    // Base addresses below 64 KiB, straddling a 64 KiB boundary and beyond 16 MiB
+    for base_address in [0, 0x1000, 0xFFF8, 0x0100_0000] {
+        for format in [ImageFormat::IntelHex, ImageFormat::Srec] {
+            let encoded = encode_image(&image, format, base_address).unwrap();
+            let loaded = read_image_data(&encoded).unwrap();
+            assert_eq!(loaded.format, format);
+            assert_eq!(loaded.base_address, base_address);
+            assert_eq!(loaded.data, image, "{:?} at 0x{:X}", format, base_address);
+        }
+    }
+    let hex = String::from_utf8(encode_image(&image, ImageFormat::IntelHex, 0xFFF8).unwrap()).unwrap();
+    assert!(hex.starts_with(":08FFF800"), "{}", hex);
+    assert!(hex.contains(":020000040001F9\n"), "{}", hex);
+    assert!(hex.ends_with(":00000001FF\n"));
+    let srec = String::from_utf8(encode_image(&image, ImageFormat::Srec, 0x0100_0000).unwrap()).unwrap();
+    assert!(srec.starts_with("S0060000465255"), "{}", srec);
+    assert!(srec.lines().nth(1).unwrap().starts_with("S31501000000"), "{}", srec);
+    assert!(srec.contains("S5030010EC\n"), "{}", srec);
+    assert!(srec.ends_with("S70500000000FA\n"));
+
+    let c_array = String::from_utf8(encode_image(&image, ImageFormat::CArray, 0).unwrap()).unwrap();
+    assert!(c_array.contains("static const uint8_t fru[256] = {\n    0x01, "), "{}", c_array);
+    assert!(c_array.contains("#ifndef FRU_GEN_IMAGE_H\n#define FRU_GEN_IMAGE_H\n"), "{}", c_array);
+    assert!(c_array.ends_with("};\n\n#endif /* FRU_GEN_IMAGE_H */\n"), "{}", c_array);
+    let base64 = String::from_utf8(encode_image(&image, ImageFormat::Base64, 0).unwrap()).unwrap();
+    assert!(base64.starts_with("AQ") && base64.ends_with("==\n") && base64.lines().count() == 1, "{}", base64);
+
+    // Raw binary passes through; corrupt records are rejected with their line number
+    let raw = read_image_data(&image).unwrap();
+    assert_eq!((raw.format, raw.data), (ImageFormat::Binary, image.clone()));
+    let corrupt = hex.replacen(":08FFF800", ":08FFF801", 1);
+    assert_eq!(
+        read_image_data(corrupt.as_bytes()),
+        Err(FruError::InvalidImage("line 1: bad record checksum".to_string()))
+    );
+    assert!(read_image_data(b":0000000000\n").is_err());
+    assert!(matches!(read_image_data("S00600004652550C\nSé\n".as_bytes()), Err(FruError::InvalidImage(_))));
+    assert!(encode_image(&image, ImageFormat::IntelHex, 0xFFFF_FF80).is_err());
+
+    // Records far apart are rejected before a buffer for the whole span is allocated
+    let mut sparse = encode_image(&image[..16], ImageFormat::Srec, 0).unwrap();
+    sparse.extend(encode_image(&image[..16], ImageFormat::Srec, 0xFFFF_FF00).unwrap());
+    assert!(matches!(read_image_data(&sparse), Err(FruError::InvalidImage(ref m)) if m.contains("spans")));
+}
+
+#[test]
+fn test_layout_pins_area_offsets() {
+    let dir = tempfile::tempdir().unwrap();
+    let config_path = dir.path().join("layout.toml");
+    let write_config = |serial: &str, layout: &str| {
+        std::fs::write(&config_path, format!(r#"
+Chassis_type = "Rack Mount Chassis"
+Chassis_Part_Number = "CPN"
+Chassis_Serial_Number = "CSN"
+Chassis_Extra = "EXTRA"
+Board_mfg_date_time = "2024-01-01 00:00"
+Board_Manufacturer = "MFG"
+Board_Product_Name = "PROD"
+Board_Serial_Number = "{}"
+Board_Part_Number = "PN"
+Board_Fruid = "FRUID"
+Board_Extra = "EXTRA"
+Product_Manufacturer = "MFG"
+Product_Name = "NAME"
+Product_Part_Number = "PPN"
+Product_Version = "V1"
+Product_Serial_Number = "PSN"
+Product_Asset_Tag = "TAG"
+Product_Fruid = "PFRUID"
+Product_Extra = "EXTRA"
+
+[layout]
+{}
+"#, serial, layout)).unwrap();
+    };
+    let build = || FruBuilder::from_config_file(config_path.to_str().unwrap()).unwrap().size(1024).build();
+
+    let layout = "pad_byte = \"0xFF\"\nchassis = { offset = 2, max_size = 16 }\nboard = { offset = 18, max_size = 32 }\nproduct = { offset = 50 }";
+    write_config("SN1", layout);
+    let image = build().unwrap();
+    assert_eq!(&image.data[1..6], &[0x01, 0x02, 0x12, 0x32, 0x00]);
+    // Unpinned internal use area fills the gap after the header; gaps and tail get the pad byte
+    assert_eq!(image.area("Internal Use Area").unwrap().offset, 8);
+    let chassis = image.area("Chassis Info Area").unwrap();
+    assert!(image.data[chassis.offset + chassis.length..0x90].iter().all(|&b| b == 0xFF));
+    assert_eq!(image.data[1023], 0xFF);
+    let report = validate_image(&image.data);
+    assert!(report.is_ok(), "{:?}", report.findings);
+
+    // Patching treats the 0xFF padding as free space and keeps it when refilling
+    let set = |key: &str, value: &str| vec![(key.to_string(), value.to_string())];
+    let patched = patch_image(&image.data, &set("product_serial_number", &"P".repeat(60))).unwrap();
+    assert_eq!(patched.len(), 1024);
+    assert_eq!(patched[1023], 0xFF);
+    let patched = patch_image(&patched, &set("chassis_serial_number", &"C".repeat(40))).unwrap();
+    let fru = decode_fru(&patched).unwrap();
+    assert!(fru.is_compliant(), "{:?}", fru.violations);
+    let chassis = fru.area("Chassis Info Area").unwrap();
+    let board = fru.area("Board Info Area").unwrap();
+    assert!(chassis.length > image.area("Chassis Info Area").unwrap().length);
+    assert_eq!(board.offset, 0x90);
+    assert!(patched[chassis.offset + chassis.length..board.offset].iter().all(|&b| b == 0xFF));
+
+    // A longer value grows the board area in place; the product area does not move
+    write_config("SN1-LONGER-SERIAL-NUMBER-0123456789", layout);
+    let grown = build().unwrap();
+    assert_eq!(&grown.data[1..6], &image.data[1..6]);
+    assert_eq!(grown.area_data("Product Info Area"), image.area_data("Product Info Area"));
+
+    write_config("SN1", "board = { offset = 18, max_size = 8 }");
+    assert!(matches!(build(), Err(FruError::AreaTooLarge { ref area, max: 64, .. }) if area == "Board Info Area"));
+    write_config("SN1", "chassis = { offset = 2, max_size = 16 }\nboard = { offset = 10 }");
+    assert!(matches!(build(), Err(FruError::InvalidLayout(message)) if message.contains("overlaps Chassis Info Area")));
+    write_config("SN1", "board = { offset = 0 }");
+    assert!(matches!(build(), Err(FruError::InvalidLayout(_))));
+    // Out-of-range slots are rejected before the image is allocated
+    write_config("SN1", "board = { offset = 4000000000000000000 }");
+    assert!(matches!(build(), Err(FruError::InvalidLayout(message)) if message.contains("offset")));
+    write_config("SN1", "board = { offset = 256 }");
+    assert!(matches!(build(), Err(FruError::InvalidLayout(_))));
+    write_config("SN1", "board = { offset = 2, max_size = 100000000000 }");
+    assert!(matches!(build(), Err(FruError::InvalidLayout(message)) if message.contains("max_size")));
+
+    // Areas after a pinned area of odd length still start on 8-byte boundaries
+    write_config("SN1", "multirecord = { offset = 1 }\n\n[[multirecord]]\ntype = \"dc_output\"\noutput_number = 1\nnominal_voltage = 1200\nmax_current = 60000");
+    let aligned = build().unwrap();
+    assert_eq!(aligned.area("MultiRecord Area").unwrap().offset, 8);
+    assert!(!aligned.area("MultiRecord Area").unwrap().length.is_multiple_of(8));
+    assert!(aligned.areas.iter().all(|a| a.offset.is_multiple_of(8)), "{:?}", aligned.areas);
+    let report = validate_image(&aligned.data);
+    assert!(report.is_ok(), "{:?}", report.findings);
+
+    // Without a layout the areas stay packed and padded with 0x00
+    write_config("SN1", "");
+    let packed = build().unwrap();
+    assert_eq!(packed.areas.windows(2).filter(|p| p[0].offset + p[0].length != p[1].offset).count(), 0);
+    assert_eq!(packed.data[1023], 0x00);
+}
+
+#[test]
+fn test_size_presets_and_auto_size() {
+    assert_eq!(FruSize::parse("24C02"), Ok(FruSize::Bytes(256)));
+    assert_eq!(FruSize::parse("24c256"), Ok(FruSize::Bytes(32768)));
+    assert_eq!(FruSize::parse(" auto "), Ok(FruSize::Auto));
+    assert_eq!(FruSize::parse("1000"), Ok(FruSize::Bytes(1000)));
+    assert!(FruSize::parse("24C99").unwrap_err().contains("24C02, 24C04"));
+    assert_eq!(FruSize::Bytes(4096).to_string(), "4096 bytes (24C32)");
+    assert_eq!(FruSize::Bytes(1000).to_string(), "1000 bytes");
+    assert_eq!(FruSize::smallest_part(600), Some("24C08"));
+    assert_eq!(FruSize::smallest_part(40000), None);
+
+    let board = || Board::new(
+        "0".to_string(), "MFG".to_string(), "PROD".to_string(), "SN".to_string(),
+        "PN".to_string(), "FRUID".to_string(), "".to_string(),
+    );
+    let auto = FruBuilder::new().board(board()).size(FruSize::Auto).build().unwrap();
+    assert_eq!(auto.data.len(), auto.used);
+    assert_eq!(auto.data.len() % 8, 0);
+    assert_eq!(auto.headroom(), 0);
+
+    let roomy = FruBuilder::new().board(board()).size(FruSize::Bytes(2048)).build().unwrap();
+    assert_eq!(roomy.used, auto.used);
+    assert_eq!(roomy.headroom(), 2048 - auto.used);
+    assert!(roomy.headroom_warning().is_none());
+    let tight = FruBuilder::new().board(board()).size(auto.used + 8).build().unwrap();
+    assert!(tight.headroom_warning().unwrap().starts_with("Only 8 of"));
+}
+
+#[test]
+fn test_field_padding() {
+    assert_eq!(Padding::parse("NUL"), Some(Padding::Byte(0x00)));
+    assert_eq!(Padding::parse("0xA5"), Some(Padding::Byte(0xA5)));
+    assert_eq!(Padding::parse("free-space"), Some(Padding::AfterEnd));
+    assert_eq!(Padding::parse("dots"), None);
+    assert_eq!(Padding::Byte(0xFF).name(), "ff");
+
+    let config = |padding| FieldConfig { enabled: true, reserved_bytes: 6, padding, ..Default::default() };
+    assert_eq!(encode_field("AB", &config(Padding::Space)).unwrap().unwrap(), vec![0xC6, b'A', b'B', b' ', b' ', b' ', b' ']);
+    assert_eq!(encode_field("AB", &config(Padding::Byte(0x00))).unwrap().unwrap(), vec![0xC6, b'A', b'B', 0, 0, 0, 0]);
+    assert_eq!(encode_field("AB", &config(Padding::AfterEnd)).unwrap().unwrap(), vec![0xC2, b'A', b'B']);
+
+    let dir = tempfile::tempdir().unwrap();
+    let config_path = dir.path().join("padding.toml");
+    std::fs::write(&config_path, r#"
+Chassis_type = "Rack Mount Chassis"
+Chassis_Part_Number = "CPN"
+Chassis_Serial_Number = "CSN"
+Board_Pad = "ff"
+Board_mfg_date_time = "2024-01-01 00:00"
+Board_Manufacturer = { value = "MFG", pad = "nul" }
+Board_Product_Name = "PROD"
+Board_Serial_Number = { value = "SN1", reserve_bytes = 16, pad = "after_end" }
+Board_Part_Number = "PN"
+Board_Fruid = "FRUID"
+Product_Manufacturer = "MFG"
+Product_Name = "NAME"
+Product_Part_Number = "PPN"
+Product_Version = "V1"
+Product_Serial_Number = "PSN"
+Product_Asset_Tag = "TAG"
+Product_Fruid = "PFRUID"
+"#).unwrap();
+    let image = FruBuilder::from_config_file(config_path.to_str().unwrap()).unwrap().size(512).build().unwrap();
+    let fru = decode_fru(&image.data).unwrap();
+    let board = fru.areas.iter().find(|a| a.name == "Board Info Area").unwrap();
+    let field = |key: &str| board.fields.iter().find(|f| f.key == key).unwrap();
+    assert_eq!(field("board_manufacturer").data, [&b"MFG"[..], &[0x00; 29]].concat());
+    assert_eq!(field("board_product_name").data, [&b"PROD"[..], &[0xFF; 28]].concat());
+    assert_eq!(field("board_serial_number").data, b"SN1");
+    // The 13 bytes the serial number did not use follow the end marker as zeros
+    let area = image.area_data("Board Info Area").unwrap();
+    let end = area.iter().rposition(|&b| b == 0xC1).unwrap();
+    assert!(area.len() - end - 2 >= 13 && area[end + 1..area.len() - 1].iter().all(|&b| b == 0));
+    let report = validate_image(&image.data);
+    assert!(report.is_ok(), "{:?}", report.findings);
+
+    // Export keeps the padding bytes
+    let exported = export_image(&image.data, ConfigFormat::Toml).unwrap();
+    assert!(exported.contains("Board_Manufacturer = { value = \"MFG\", reserve_bytes = 32, pad = \"nul\" }"), "{}", exported);
+    assert!(exported.contains("Board_Product_Name = { value = \"PROD\", reserve_bytes = 32, pad = \"ff\" }"), "{}", exported);
+
+    // An area filled with 0xFF padding sums past what a u16 holds
+    let product = Product::new(
+        "A".to_string(), "A".to_string(), "A".to_string(), "A".to_string(),
+        "A".to_string(), "A".to_string(), "A".to_string(), "A".to_string(),
+    );
+    let ff = FieldConfig { enabled: true, reserved_bytes: 63, padding: Padding::Byte(0xFF), ..Default::default() };
+    let image = FruBuilder::new().product(product).field_configs(vec![ff; 19]).size(1024).build().unwrap();
+    assert!(image.area_data("Product Info Area").unwrap().iter().filter(|&&b| b == 0xFF).count() > 257);
+    let report = validate_image(&image.data);
+    assert!(report.is_ok(), "{:?}", report.findings);
+
+    let mut config = fru_gen::load_config(config_path.to_str().unwrap()).unwrap();
+    config.insert("board_pad".to_string(), fru_gen::ConfigField::Simple("dots".to_string()));
+    assert!(matches!(
+        FruBuilder::from_config(&config, Vec::new()),
+        Err(FruError::UnknownPadding { ref field, ref name }) if field == "board_pad" && name == "dots"
+    ));
+    let report = validate_config(&config, Vec::new(), None, None, 512, false);
+    assert!(!report.is_ok());
+}
+
+#[test]
+fn test_reserve_beyond_field_limit_is_an_error() {
+    let config = |reserved_bytes| FieldConfig { enabled: true, reserved_bytes, ..Default::default() };
+    assert_eq!(encode_field("SN", &config(63)).unwrap().unwrap()[0], 0xC0 | 63);
+    let err = encode_field("SN", &config(100)).unwrap_err();
+    assert!(err.contains("reserve_bytes of 100 exceeds the 63"), "{}", err);
+
+    // Built in code, the failing field is named with its area
+    let board = Board::new(
+        "0".to_string(), "MFG".to_string(), "PROD".to_string(), "SN".to_string(),
+        "PN".to_string(), "FRUID".to_string(), "x".repeat(64),
+    );
+    let err = FruBuilder::new().board(board).size(1024).build().unwrap_err();
+    assert!(err.to_string().contains("Board Extra"), "{}", err);
+
+    // From a config the key is named up front, custom list items with their index
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("reserve.toml");
+    std::fs::write(&path, r#"
+Board_Manufacturer = "MFG"
+Board_Serial_Number = { value = "SN1", reserve_bytes = 100 }
+Product_Custom = ["A", { value = "B", reserve_bytes = 64 }]
+"#).unwrap();
+    let config = fru_gen::load_config(path.to_str().unwrap()).unwrap();
+    assert!(matches!(
+        FruBuilder::from_config(&config, Vec::new()),
+        Err(FruError::ReserveTooLarge { ref field, reserved: 64, max: 63 }) if field == "product_custom[1]"
+    ));
+    let report = validate_config(&config, Vec::new(), None, None, 1024, false);
+    let messages: Vec<_> = report.findings.iter().map(|f| f.message.as_str()).collect();
+    assert!(messages.contains(&"Field 'board_serial_number' reserves 100 bytes, more than the 63 a type/length byte allows"), "{:?}", messages);
+    assert!(messages.contains(&"Field 'product_custom[1]' reserves 64 bytes, more than the 63 a type/length byte allows"), "{:?}", messages);
+}